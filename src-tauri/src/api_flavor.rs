@@ -0,0 +1,259 @@
+// Endpoint flavors supported by the STT and translation services.
+// OpenAI-compatible servers use Bearer auth and flat paths; Azure OpenAI uses an
+// `api-key` header, deployment-scoped paths and an `api-version` query parameter.
+
+use crate::debug_logger::DebugLogger;
+use std::collections::HashMap;
+
+/// API version sent to Azure OpenAI deployments
+pub const AZURE_API_VERSION: &str = "2024-06-01";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiFlavor {
+    OpenAI,
+    Azure,
+}
+
+impl ApiFlavor {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "azure" | "azure_openai" => ApiFlavor::Azure,
+            _ => ApiFlavor::OpenAI,
+        }
+    }
+
+    /// Build the URL for an operation such as "audio/transcriptions" or "chat/completions".
+    /// For Azure the model name is used as the deployment name.
+    pub fn endpoint_url(&self, api_endpoint: &str, model: &str, path: &str) -> String {
+        let base = api_endpoint.trim_end_matches('/');
+        match self {
+            ApiFlavor::OpenAI => format!("{}/{}", base, path),
+            ApiFlavor::Azure => format!(
+                "{}/openai/deployments/{}/{}?api-version={}",
+                base, model, path, AZURE_API_VERSION
+            ),
+        }
+    }
+
+    /// URL used to list models when testing connectivity
+    pub fn models_url(&self, api_endpoint: &str) -> String {
+        let base = api_endpoint.trim_end_matches('/');
+        match self {
+            ApiFlavor::OpenAI => format!("{}/models", base),
+            ApiFlavor::Azure => format!("{}/openai/models?api-version={}", base, AZURE_API_VERSION),
+        }
+    }
+
+    /// Attach the flavor-specific authentication header (none at all for an empty key,
+    /// which key-less self-hosted servers expect)
+    pub fn authorize(
+        &self,
+        request: reqwest::RequestBuilder,
+        api_key: &str,
+    ) -> reqwest::RequestBuilder {
+        if api_key.trim().is_empty() {
+            return request;
+        }
+        match self {
+            ApiFlavor::OpenAI => request.header("Authorization", format!("Bearer {}", api_key)),
+            ApiFlavor::Azure => request.header("api-key", api_key),
+        }
+    }
+}
+
+/// Headers the HTTP client sets itself; overriding them would break the requests
+const CLIENT_MANAGED_HEADERS: &[&str] = &["content-type", "content-length", "host", "transfer-encoding", "connection"];
+
+/// Credentials headers, only accepted as custom headers when explicitly allowed
+const AUTH_HEADERS: &[&str] = &["authorization", "api-key", "proxy-authorization"];
+
+/// Check one `custom_headers` entry; the error says why it won't be sent
+pub fn check_custom_header(name: &str, value: &str, allow_auth_headers: bool) -> Result<(), String> {
+    // RFC 9110 token characters
+    let valid_name = !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+    if !valid_name {
+        return Err(format!("invalid header name '{}'", name));
+    }
+    let lower = name.to_ascii_lowercase();
+    if CLIENT_MANAGED_HEADERS.contains(&lower.as_str()) {
+        return Err(format!("'{}' is set by the HTTP client", name));
+    }
+    if AUTH_HEADERS.contains(&lower.as_str()) && !allow_auth_headers {
+        return Err(format!("'{}' is reserved (enable allow_auth_headers to send it)", name));
+    }
+    if value.bytes().any(|b| (b < 0x20 && b != b'\t') || b == 0x7f) {
+        return Err(format!("invalid characters in the value of '{}'", name));
+    }
+    Ok(())
+}
+
+/// The valid `custom_headers`, ready to be sent with every request. Invalid or reserved entries
+/// are logged and skipped. The flavor's own auth header still wins when an API key is set.
+pub fn custom_header_map(headers: &HashMap<String, String>, allow_auth_headers: bool) -> reqwest::header::HeaderMap {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        let (name, value) = (name.trim(), value.trim());
+        if let Err(e) = check_custom_header(name, value, allow_auth_headers) {
+            DebugLogger::log_warn(&format!("Skipping custom header: {}", e));
+            continue;
+        }
+        match (
+            reqwest::header::HeaderName::from_bytes(name.as_bytes()),
+            reqwest::header::HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                map.insert(name, value);
+            }
+            _ => DebugLogger::log_warn(&format!("Skipping custom header '{}': not a valid HTTP header", name)),
+        }
+    }
+    map
+}
+
+/// Model IDs from a `/models` response (`{"data": [{"id": ...}]}`), sorted and de-duplicated
+pub fn parse_model_ids(response: &serde_json::Value) -> Vec<String> {
+    let mut ids: Vec<String> = response["data"]
+        .as_array()
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|model| model["id"].as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// `local_only` for a request: the saved setting can only be tightened by the caller, never switched off
+pub fn effective_local_only(requested: Option<bool>, stored: bool) -> bool {
+    stored || requested.unwrap_or(false)
+}
+
+/// With the `local_only` setting on, anything that isn't a server on this machine is refused
+pub fn check_local_only(api_endpoint: &str, local_only: bool) -> Result<(), String> {
+    if local_only && !is_local_endpoint(api_endpoint) {
+        return Err(format!(
+            "Local-only mode is on: refusing to send data to {} (only localhost endpoints are allowed)",
+            api_endpoint.trim()
+        ));
+    }
+    Ok(())
+}
+
+/// True for servers on this machine (e.g. a whisper.cpp or faster-whisper server on localhost)
+pub fn is_local_endpoint(api_endpoint: &str) -> bool {
+    let rest = api_endpoint.trim();
+    let rest = rest
+        .strip_prefix("http://")
+        .or_else(|| rest.strip_prefix("https://"))
+        .unwrap_or(rest);
+    let authority = rest.split('/').next().unwrap_or("");
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+    let host = if authority.starts_with('[') {
+        authority.split(']').next().map(|h| &h[1..]).unwrap_or("")
+    } else {
+        authority.split(':').next().unwrap_or("")
+    };
+    let host = host.to_lowercase();
+    // Only literal addresses count; a DNS name like 127.example.com can resolve anywhere
+    host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback() || ip.is_unspecified())
+}
+
+/// Whether requests to `api_endpoint` need an API key; local servers never do
+pub fn api_key_required(api_endpoint: &str, require_api_key: bool) -> bool {
+    require_api_key && !is_local_endpoint(api_endpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_url() {
+        let url = ApiFlavor::OpenAI.endpoint_url("https://api.openai.com/v1/", "whisper-1", "audio/transcriptions");
+        assert_eq!(url, "https://api.openai.com/v1/audio/transcriptions");
+    }
+
+    #[test]
+    fn test_azure_url() {
+        let url = ApiFlavor::Azure.endpoint_url("https://corp.openai.azure.com", "whisper", "audio/transcriptions");
+        assert_eq!(
+            url,
+            format!(
+                "https://corp.openai.azure.com/openai/deployments/whisper/audio/transcriptions?api-version={}",
+                AZURE_API_VERSION
+            )
+        );
+    }
+
+    #[test]
+    fn test_is_local_endpoint() {
+        assert!(is_local_endpoint("http://localhost:8080"));
+        assert!(is_local_endpoint("http://127.0.0.1:8000/v1"));
+        assert!(is_local_endpoint("http://[::1]:9000/v1"));
+        assert!(!is_local_endpoint("https://api.openai.com/v1"));
+        assert!(!is_local_endpoint("https://localhost.example.com/v1"));
+        assert!(is_local_endpoint("http://127.1.2.3:8000"));
+        assert!(is_local_endpoint("http://0.0.0.0:8000"));
+        assert!(!is_local_endpoint("http://127.evil.com/v1"));
+        assert!(!is_local_endpoint("http://127.0.0.1.nip.io:8000/v1"));
+        assert!(!api_key_required("http://localhost:8080", true));
+        assert!(!api_key_required("https://api.openai.com/v1", false));
+        assert!(api_key_required("https://api.openai.com/v1", true));
+    }
+
+    #[test]
+    fn test_check_local_only() {
+        assert!(check_local_only("http://localhost:8080/v1", true).is_ok());
+        assert!(check_local_only("https://api.openai.com/v1", false).is_ok());
+        let err = check_local_only("https://api.openai.com/v1", true).unwrap_err();
+        assert!(err.contains("api.openai.com"));
+    }
+
+    #[test]
+    fn test_effective_local_only() {
+        // The stored setting holds even when the caller doesn't pass one, or passes false
+        assert!(effective_local_only(None, true));
+        assert!(effective_local_only(Some(false), true));
+        assert!(effective_local_only(Some(true), false));
+        assert!(!effective_local_only(None, false));
+        assert!(check_local_only("https://api.openai.com/v1", effective_local_only(None, true)).is_err());
+    }
+
+    #[test]
+    fn test_check_custom_header() {
+        assert!(check_custom_header("HTTP-Referer", "https://example.com", false).is_ok());
+        assert!(check_custom_header("X-Title", "TalkToMe", false).is_ok());
+        assert!(check_custom_header("Authorization", "Bearer x", false).is_err());
+        assert!(check_custom_header("authorization", "Bearer x", true).is_ok());
+        assert!(check_custom_header("Content-Type", "text/plain", true).is_err());
+        assert!(check_custom_header("Bad Name", "x", false).is_err());
+        assert!(check_custom_header("X-Injected", "a\r\nHost: evil", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_model_ids() {
+        let response = serde_json::json!({
+            "object": "list",
+            "data": [{ "id": "whisper-1" }, { "id": "gpt-4o-mini" }, { "id": "whisper-1" }, { "object": "model" }]
+        });
+        assert_eq!(parse_model_ids(&response), vec!["gpt-4o-mini", "whisper-1"]);
+        assert!(parse_model_ids(&serde_json::json!({ "error": "not found" })).is_empty());
+    }
+
+    #[test]
+    fn test_flavor_from_setting() {
+        assert_eq!(ApiFlavor::from_setting("azure"), ApiFlavor::Azure);
+        assert_eq!(ApiFlavor::from_setting("openai"), ApiFlavor::OpenAI);
+        assert_eq!(ApiFlavor::from_setting(""), ApiFlavor::OpenAI);
+    }
+}
@@ -4,11 +4,70 @@ use crate::debug_logger::DebugLogger;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{FromSample, Sample};
 use nnnoiseless::DenoiseState;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
+/// How multi-channel input is folded into the mono signal we process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonoMixdown {
+    /// Keep only the first channel
+    First,
+    /// Average all channels (standard downmix)
+    Average,
+}
+
+impl MonoMixdown {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "first" => MonoMixdown::First,
+            _ => MonoMixdown::Average,
+        }
+    }
+
+    /// Fold one interleaved frame into a single sample
+    fn mix<T>(self, frame: &[T]) -> f32
+    where
+        T: Sample,
+        f32: FromSample<T>,
+    {
+        match self {
+            MonoMixdown::First => frame[0].to_sample(),
+            MonoMixdown::Average => {
+                frame.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / frame.len() as f32
+            }
+        }
+    }
+}
+
+/// What `start_capture` records from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureSource {
+    Microphone,
+    /// What the default output device is playing (WASAPI loopback, Windows only)
+    SystemLoopback,
+    /// Microphone with the system loopback mixed in, for recording both sides of a call
+    Both,
+}
+
+impl CaptureSource {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "system_loopback" | "loopback" | "system" => CaptureSource::SystemLoopback,
+            "both" | "microphone_and_system" => CaptureSource::Both,
+            _ => CaptureSource::Microphone,
+        }
+    }
+
+    /// Whether the loopback sources can work on this platform
+    pub fn loopback_supported() -> bool {
+        cfg!(target_os = "windows")
+    }
+}
+
 /// Simple WAV file encoder for debugging purposes
-fn encode_wav_bytes(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+pub fn encode_wav_bytes(samples: &[f32], sample_rate: u32) -> Vec<u8> {
     let num_samples = samples.len() as u32;
     let num_channels = 1u16; // Mono
     let bits_per_sample = 16u16;
@@ -47,148 +106,704 @@ fn encode_wav_bytes(samples: &[f32], sample_rate: u32) -> Vec<u8> {
     wav_data
 }
 
-/// Simple downsampling function using decimation
-fn downsample_audio(input: &[f32], input_rate: u32, target_rate: u32) -> Vec<f32> {
-    if input_rate == target_rate {
+/// Decode a PCM (8/16/24/32-bit) or 32-bit float WAV file into mono f32 samples.
+/// Returns the samples and their sample rate; channels are averaged.
+pub fn decode_wav(bytes: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("Not a RIFF/WAVE file".to_string());
+    }
+    let u16_at = |pos: usize| u16::from_le_bytes([bytes[pos], bytes[pos + 1]]);
+    let u32_at = |pos: usize| u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]);
+
+    // (format tag, channels, sample rate, bits per sample)
+    let mut format = None;
+    let mut data = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32_at(pos + 4) as usize;
+        let body = pos + 8;
+        let end = body.saturating_add(size).min(bytes.len());
+        if id == b"fmt " && end - body >= 16 {
+            let mut tag = u16_at(body);
+            // WAVE_FORMAT_EXTENSIBLE keeps the real format in the sub-format GUID
+            if tag == 0xFFFE && end - body >= 26 {
+                tag = u16_at(body + 24);
+            }
+            format = Some((tag, u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+        } else if id == b"data" {
+            data = Some(&bytes[body..end]);
+        }
+        // Chunks are padded to an even size
+        pos = body.saturating_add(size + (size & 1));
+    }
+
+    let (tag, channels, sample_rate, bits) = format.ok_or("WAV file has no fmt chunk")?;
+    let data = data.ok_or("WAV file has no data chunk")?;
+    if channels == 0 || sample_rate == 0 {
+        return Err("WAV file has an invalid channel count or sample rate".to_string());
+    }
+    let width = bits as usize / 8;
+    let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+        (1, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+        (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (1, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2_147_483_648.0,
+        (1, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => return Err(format!("Unsupported WAV encoding (format {}, {} bits)", tag, bits)),
+    };
+
+    let frame_len = width * channels as usize;
+    let samples = data
+        .chunks_exact(frame_len)
+        .map(|frame| frame.chunks_exact(width).map(decode).sum::<f32>() / channels as f32)
+        .collect();
+    Ok((samples, sample_rate))
+}
+
+/// Minimum interval between microphone level updates sent to the UI (~30Hz)
+const LEVEL_UPDATE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(33);
+
+/// Number of sinc zero crossings on each side of the resampling kernel
+const SINC_ZERO_CROSSINGS: f64 = 16.0;
+
+/// Largest number of kernel phases precomputed for one rate pair; rarer ratios compute taps per sample
+const MAX_KERNEL_PHASES: usize = 4096;
+
+// Windowed-sinc taps for an output sample `frac` (0..1) input samples past the input index it is
+// anchored to. Returns the offset of the first tap relative to that index and the tap weights.
+fn sinc_kernel(frac: f64, cutoff: f64, half_width: f64) -> (i64, Vec<f64>) {
+    let first = (frac - half_width).ceil() as i64;
+    let last = (frac + half_width).floor() as i64;
+    let taps = (first..=last)
+        .map(|j| {
+            let distance = j as f64 - frac;
+            let x = cutoff * distance;
+            let sinc = if x.abs() < 1e-12 {
+                1.0
+            } else {
+                (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+            };
+            // Blackman window over [-half_width, half_width]
+            let phase = std::f64::consts::PI * (distance / half_width + 1.0);
+            let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+            sinc * window
+        })
+        .collect();
+    (first, taps)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Band-limited resampler using a Blackman-windowed sinc kernel.
+/// When downsampling, the kernel cutoff is lowered to the target Nyquist frequency so
+/// content above it is filtered out instead of aliasing into the speech band.
+/// Output sample positions repeat every `target_rate / gcd` samples, so the kernel for each
+/// of those phases is computed once per call instead of once per output sample.
+pub fn resample(input: &[f32], input_rate: u32, target_rate: u32) -> Vec<f32> {
+    if input_rate == target_rate || input.is_empty() || input_rate == 0 || target_rate == 0 {
         return input.to_vec();
     }
 
-    let ratio = input_rate as f32 / target_rate as f32;
-    let output_len = (input.len() as f32 / ratio) as usize;
+    let ratio = target_rate as f64 / input_rate as f64;
+    // Cutoff relative to the input Nyquist, with a little headroom for the transition band
+    let cutoff = ratio.min(1.0) * 0.95;
+    let half_width = SINC_ZERO_CROSSINGS / cutoff;
+    let output_len = (input.len() as f64 * ratio).round().max(1.0) as usize;
     let mut output = Vec::with_capacity(output_len);
 
-    for i in 0..output_len {
-        let src_index = (i as f32 * ratio) as usize;
-        if src_index < input.len() {
-            output.push(input[src_index]);
+    // Output sample i sits at input position i * step / phases
+    let divisor = gcd(input_rate as u64, target_rate as u64);
+    let phases = target_rate as u64 / divisor;
+    let step = input_rate as u64 / divisor;
+    let table: Vec<(i64, Vec<f64>)> = if phases as usize <= MAX_KERNEL_PHASES {
+        (0..phases)
+            .map(|p| sinc_kernel(p as f64 / phases as f64, cutoff, half_width))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    for i in 0..output_len as u64 {
+        let position = i * step;
+        let anchor = (position / phases) as i64;
+        let phase = position % phases;
+        let computed;
+        let (offset, taps) = match table.get(phase as usize) {
+            Some(kernel) => kernel,
+            None => {
+                computed = sinc_kernel(phase as f64 / phases as f64, cutoff, half_width);
+                &computed
+            }
+        };
+
+        // Taps falling outside the input are dropped
+        let start = anchor + offset;
+        let skip = (-start).max(0) as usize;
+        let end = (input.len() as i64 - start).clamp(0, taps.len() as i64) as usize;
+        let mut acc = 0.0f64;
+        let mut weight_sum = 0.0f64;
+        if skip < end {
+            let first = (start + skip as i64) as usize;
+            for (&weight, &sample) in taps[skip..end].iter().zip(&input[first..]) {
+                acc += sample as f64 * weight;
+                weight_sum += weight;
+            }
         }
+
+        // Normalize by the kernel sum so DC gain stays at unity, including at the edges
+        let value = if weight_sum.abs() > 1e-12 {
+            acc / weight_sum
+        } else {
+            0.0
+        };
+        output.push(value as f32);
     }
 
     output
 }
 
+/// Split `samples` into pieces of at most `max_len` samples, cutting each piece at the
+/// quietest 20ms frame within its last quarter so words are not split mid-syllable.
+pub fn split_at_silence(samples: &[f32], sample_rate: u32, max_len: usize) -> Vec<std::ops::Range<usize>> {
+    let frame = (sample_rate as usize / 50).max(1);
+    let max_len = max_len.max(frame * 2);
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while samples.len() - start > max_len {
+        let search_from = start + max_len * 3 / 4;
+        let search_to = start + max_len - frame;
+        let mut cut = start + max_len;
+        let mut quietest = f32::MAX;
+        let mut pos = search_from;
+        while pos <= search_to {
+            let energy: f32 = samples[pos..pos + frame].iter().map(|s| s * s).sum();
+            if energy < quietest {
+                quietest = energy;
+                // Cut in the middle of the quiet frame
+                cut = pos + frame / 2;
+            }
+            pos += frame;
+        }
+        pieces.push(start..cut);
+        start = cut;
+    }
+    if start < samples.len() {
+        pieces.push(start..samples.len());
+    }
+    pieces
+}
+
+/// Peak level quiet recordings are raised to (-3 dBFS)
+const NORMALIZE_TARGET_PEAK: f32 = 0.708;
+/// Gain cap (+20 dB) so background noise in near-silent recordings isn't blown up into "speech"
+const NORMALIZE_MAX_GAIN: f32 = 10.0;
+
+/// Scale `samples` so the peak reaches -3 dBFS (gain capped at +20 dB). Only boosts; loud
+/// audio is left alone. Returns the applied gain.
+pub fn normalize_peak(samples: &mut [f32]) -> f32 {
+    let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+    if peak <= f32::EPSILON || peak >= NORMALIZE_TARGET_PEAK {
+        return 1.0;
+    }
+    let gain = (NORMALIZE_TARGET_PEAK / peak).min(NORMALIZE_MAX_GAIN);
+    for s in samples.iter_mut() {
+        *s = (*s * gain).clamp(-1.0, 1.0);
+    }
+    gain
+}
+
+/// Silence trimming applied to the final single-recording buffer before upload
+#[derive(Debug, Clone, Copy)]
+pub struct SilenceTrim {
+    /// Interior pauses longer than this are shortened to it (0 keeps pauses intact)
+    pub max_gap_ms: u32,
+}
+
+const TRIM_FRAME_MS: usize = 20;
+/// Frames quieter than this fraction of the loudest frame's RMS count as silence
+const TRIM_RELATIVE_THRESHOLD: f32 = 0.05;
+/// Absolute floor so near-digital-silence is never mistaken for speech
+const TRIM_MIN_THRESHOLD: f32 = 0.001;
+/// Silence kept around speech so onsets and word endings aren't clipped
+const TRIM_PADDING_MS: usize = 200;
+
+/// Remove leading/trailing silence and optionally collapse long interior pauses.
+/// Audio with no frame above the threshold is returned unchanged.
+pub fn trim_silence(samples: &[f32], sample_rate: u32, trim: SilenceTrim) -> Vec<f32> {
+    let frame = (sample_rate as usize * TRIM_FRAME_MS / 1000).max(1);
+    let rms: Vec<f32> = samples
+        .chunks(frame)
+        .map(|f| (f.iter().map(|s| s * s).sum::<f32>() / f.len() as f32).sqrt())
+        .collect();
+    let loudest = rms.iter().cloned().fold(0.0f32, f32::max);
+    let threshold = (loudest * TRIM_RELATIVE_THRESHOLD).max(TRIM_MIN_THRESHOLD);
+    let speech: Vec<bool> = rms.iter().map(|&r| r >= threshold).collect();
+
+    let (Some(first), Some(last)) = (speech.iter().position(|&s| s), speech.iter().rposition(|&s| s)) else {
+        return samples.to_vec();
+    };
+    let pad_frames = TRIM_PADDING_MS / TRIM_FRAME_MS;
+    let start_frame = first.saturating_sub(pad_frames);
+    let end_frame = (last + 1 + pad_frames).min(speech.len());
+
+    let max_gap_frames = trim.max_gap_ms as usize / TRIM_FRAME_MS;
+    let mut out = Vec::with_capacity((end_frame - start_frame) * frame);
+    let mut index = start_frame;
+    while index < end_frame {
+        if max_gap_frames > 0 && !speech[index] {
+            let gap_end = (index..end_frame).find(|&i| speech[i]).unwrap_or(end_frame);
+            let gap_len = gap_end - index;
+            // Keep the edges of a long pause so the result still sounds like a pause
+            let keep = gap_len.min(max_gap_frames);
+            let head = keep / 2;
+            let tail = keep - head;
+            for i in (index..index + head).chain(gap_end - tail..gap_end) {
+                out.extend_from_slice(&samples[i * frame..((i + 1) * frame).min(samples.len())]);
+            }
+            index = gap_end;
+        } else {
+            out.extend_from_slice(&samples[index * frame..((index + 1) * frame).min(samples.len())]);
+            index += 1;
+        }
+    }
+    out
+}
+
+/// Samples louder than this count as audio activity (same threshold the pipeline uses)
+const ACTIVITY_THRESHOLD: f32 = 0.01;
+
+/// Capture rate preferred when the default input config has to be replaced (what STT receives)
+const FALLBACK_CAPTURE_RATE: u32 = 16000;
+
+/// Add loopback samples (already at the microphone rate) onto the microphone samples, one for
+/// one. Microphone samples past the end of the loopback audio pass through unchanged.
+fn mix_loopback(samples: &mut [f32], loopback: &[f32]) {
+    for (sample, &loopback) in samples.iter_mut().zip(loopback) {
+        *sample = (*sample + loopback).clamp(-1.0, 1.0);
+    }
+}
+
+/// Sample formats the input stream builders handle
+fn is_readable_format(format: cpal::SampleFormat) -> bool {
+    matches!(
+        format,
+        cpal::SampleFormat::F32 | cpal::SampleFormat::I16 | cpal::SampleFormat::U16
+    )
+}
+
+/// Rate to use within a supported range and its rank (lower is better):
+/// mono first, then the rate closest to `FALLBACK_CAPTURE_RATE`
+fn rank_config_range(channels: u16, min_rate: u32, max_rate: u32) -> (u32, (bool, u32)) {
+    let rate = FALLBACK_CAPTURE_RATE.clamp(min_rate, max_rate.max(min_rate));
+    (rate, (channels != 1, rate.abs_diff(FALLBACK_CAPTURE_RATE)))
+}
+
+/// Signal levels of a captured clip, reported by the mic diagnostics command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipLevels {
+    pub peak: f32,
+    pub rms: f32,
+    pub non_silent_samples: usize,
+}
+
+pub fn measure_levels(samples: &[f32]) -> ClipLevels {
+    let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+    let rms = if samples.is_empty() {
+        0.0
+    } else {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    };
+    let non_silent_samples = samples.iter().filter(|s| s.abs() > ACTIVITY_THRESHOLD).count();
+    ClipLevels { peak, rms, non_silent_samples }
+}
+
+// nnnoiseless's model runs on 48kHz audio in 10ms frames, with samples in 16-bit PCM range
+const NNNOISE_SAMPLE_RATE: u32 = 48000;
+const NNNOISE_FRAME_SIZE: usize = DenoiseState::FRAME_SIZE;
+const NNNOISE_PCM_SCALE: f32 = i16::MAX as f32;
+const STT_SAMPLE_RATE: u32 = 16000;
+// Recordings estimated cleaner than this skip denoising, which would only dull the voice
+const HIGH_SNR_BYPASS_DB: f32 = 30.0;
+
+/// Rough signal-to-noise ratio (dB) from the spread of frame energies: the loud frames (speech)
+/// against the quiet ones (noise floor). None when the audio is too short to tell.
+pub fn estimate_snr_db(samples: &[f32], frame_len: usize) -> Option<f32> {
+    let mut energies: Vec<f32> = samples
+        .chunks_exact(frame_len)
+        .map(|frame| frame.iter().map(|s| s * s).sum::<f32>() / frame_len as f32)
+        .collect();
+    if energies.len() < 10 {
+        return None;
+    }
+    energies.sort_by(f32::total_cmp);
+    let noise = energies[energies.len() / 10].max(1e-10);
+    let signal = energies[energies.len() * 9 / 10];
+    Some(10.0 * (signal / noise).log10())
+}
+
 /// Noise reduction processor using nnnoiseless
 pub struct NoiseReducer {
     denoise_state: DenoiseState<'static>,
     frame_buffer: Vec<f32>,
     sample_rate: u32,
+    // Share of the denoised signal in the output (0.0-1.0); the rest is the original
+    strength: f32,
+    // Previous input frame: nnnoiseless output lags its input by one frame
+    dry_delay: Vec<f32>,
+    // Whether the first (fade-in) output frame has been discarded
+    primed: bool,
+    // Decided on the first processed audio: skip denoising for clean input
+    bypass: Option<bool>,
 }
 
 impl NoiseReducer {
-    pub fn new(sample_rate: u32) -> Self {
+    pub fn new(sample_rate: u32, strength: f32) -> Self {
         Self {
             denoise_state: *DenoiseState::new(),
             frame_buffer: Vec::new(),
             sample_rate,
+            strength: strength.clamp(0.0, 1.0),
+            dry_delay: vec![0.0; NNNOISE_FRAME_SIZE],
+            primed: false,
+            bypass: None,
         }
     }
 
-    /// Process audio samples through nnnoiseless to reduce background noise
-    /// Downsamples to 16kHz for optimal noise reduction and keeps it at 16kHz for efficiency
+    pub fn strength(&self) -> f32 {
+        self.strength
+    }
+
+    /// Denoise one 48kHz frame and blend it with the original according to `strength`.
+    /// The output belongs to the previous frame; None for the very first one.
+    fn denoise_frame(&mut self, frame: &[f32]) -> Option<Vec<f32>> {
+        let scaled: Vec<f32> = frame.iter().map(|s| s * NNNOISE_PCM_SCALE).collect();
+        let mut out_frame = vec![0.0f32; NNNOISE_FRAME_SIZE];
+        self.denoise_state.process_frame(&mut out_frame[..], &scaled);
+        for sample in &mut out_frame {
+            *sample /= NNNOISE_PCM_SCALE;
+        }
+        if self.strength < 1.0 {
+            for (wet, dry) in out_frame.iter_mut().zip(&self.dry_delay) {
+                *wet = self.strength * *wet + (1.0 - self.strength) * dry;
+            }
+        }
+        self.dry_delay.copy_from_slice(frame);
+        if !self.primed {
+            self.primed = true;
+            return None;
+        }
+        Some(out_frame)
+    }
+
+    /// Process audio samples through nnnoiseless to reduce background noise.
+    /// The model is trained on 48kHz audio in 480-sample (10ms) frames, so the input is brought to
+    /// 48kHz for denoising and the result downsampled to 16kHz for STT.
     pub fn process_audio(&mut self, input: &[f32]) -> Vec<f32> {
         use crate::debug_logger::DebugLogger;
 
-        const TARGET_SAMPLE_RATE: u32 = 16000;
-        const NNNOISE_FRAME_SIZE: usize = 480; // 30ms at 16kHz
-
-        DebugLogger::log_info(&format!(
-            "NOISE_REDUCER: Processing {} input samples at {}Hz, downsampling to {}Hz",
+        DebugLogger::log_trace(&format!(
+            "NOISE_REDUCER: Processing {} input samples at {}Hz, denoising at {}Hz",
             input.len(),
             self.sample_rate,
-            TARGET_SAMPLE_RATE
+            NNNOISE_SAMPLE_RATE
         ));
 
-        // First, downsample the input to 16kHz if needed
-        let downsampled_input = if self.sample_rate != TARGET_SAMPLE_RATE {
-            let target_length =
-                (input.len() as f32 * TARGET_SAMPLE_RATE as f32 / self.sample_rate as f32) as usize;
-            let mut downsampled = Vec::with_capacity(target_length);
-
-            // Simple decimation - take every nth sample
-            let step = self.sample_rate as f32 / TARGET_SAMPLE_RATE as f32;
-            for i in 0..target_length {
-                let src_index = (i as f32 * step) as usize;
-                if src_index < input.len() {
-                    downsampled.push(input[src_index]);
-                } else {
-                    downsampled.push(0.0);
-                }
-            }
-
+        if self.bypass.is_none() {
+            // Frame energies don't depend on the rate, so the estimate runs on the input as captured
+            let frame_len = (self.sample_rate as usize / 100).max(1);
+            let snr_db = estimate_snr_db(input, frame_len);
+            let bypass = self.strength <= 0.0 || snr_db.is_some_and(|db| db >= HIGH_SNR_BYPASS_DB);
             DebugLogger::log_info(&format!(
-                "NOISE_REDUCER: Downsampled from {} samples at {}Hz to {} samples at {}Hz",
-                input.len(),
-                self.sample_rate,
-                downsampled.len(),
-                TARGET_SAMPLE_RATE
+                "NOISE_REDUCER: estimated SNR {:?} dB, strength {:.2}, {}",
+                snr_db.map(|db| (db * 10.0).round() / 10.0),
+                self.strength,
+                if bypass { "bypassing denoise" } else { "denoising" }
             ));
-            downsampled
-        } else {
-            input.to_vec()
-        };
+            self.bypass = Some(bypass);
+        }
+        if self.bypass == Some(true) {
+            return resample(input, self.sample_rate, STT_SAMPLE_RATE);
+        }
 
-        // Add downsampled samples to the frame buffer
-        self.frame_buffer.extend_from_slice(&downsampled_input);
+        // Bring the input to the model's native rate (anti-aliased when coming down)
+        self.frame_buffer
+            .extend_from_slice(&resample(input, self.sample_rate, NNNOISE_SAMPLE_RATE));
 
-        let mut output = Vec::new();
+        let mut denoised = Vec::new();
         let mut frames_processed = 0;
 
-        // Process complete frames at 16kHz
+        // Process complete 10ms frames at 48kHz
         while self.frame_buffer.len() >= NNNOISE_FRAME_SIZE {
-            // Take one frame from the buffer
             let frame: Vec<f32> = self.frame_buffer.drain(0..NNNOISE_FRAME_SIZE).collect();
-
-            // Calculate input frame statistics
-            let _input_max = frame.iter().map(|x| x.abs()).fold(0.0f32, f32::max);
-            let _input_rms = (frame.iter().map(|x| x * x).sum::<f32>() / frame.len() as f32).sqrt();
-
-            // Apply noise reduction directly on 16kHz audio
-            let mut out_frame = vec![0.0f32; NNNOISE_FRAME_SIZE];
-            self.denoise_state
-                .process_frame(&mut out_frame[..], &frame[..]);
-
-            // Calculate output frame statistics
-            let _output_max = out_frame.iter().map(|x| x.abs()).fold(0.0f32, f32::max);
-            let _output_rms =
-                (out_frame.iter().map(|x| x * x).sum::<f32>() / out_frame.len() as f32).sqrt();
-
-            output.extend_from_slice(&out_frame);
+            denoised.extend(self.denoise_frame(&frame).unwrap_or_default());
             frames_processed += 1;
         }
 
-        DebugLogger::log_info(&format!(
-            "NOISE_REDUCER: Processed {} frames at 16kHz, {} samples remaining in buffer, returning {} samples at 16kHz",
+        let output = resample(&denoised, NNNOISE_SAMPLE_RATE, STT_SAMPLE_RATE);
+        DebugLogger::log_trace(&format!(
+            "NOISE_REDUCER: Processed {} frames at {}Hz, {} samples remaining in buffer, returning {} samples at {}Hz",
             frames_processed,
+            NNNOISE_SAMPLE_RATE,
             self.frame_buffer.len(),
-            output.len()
+            output.len(),
+            STT_SAMPLE_RATE
         ));
 
         output
     }
 
-    /// Get any remaining samples in the buffer (useful for final processing)
     /// Get any remaining samples in the buffer (useful for final processing)
     /// Returns samples at 16kHz
     pub fn flush(&mut self) -> Vec<f32> {
-        if self.frame_buffer.is_empty() {
+        let remaining = self.frame_buffer.len();
+        if !self.primed && remaining == 0 {
             return Vec::new();
         }
 
-        const NNNOISE_FRAME_SIZE: usize = 480; // 30ms at 16kHz
-
         // Pad the remaining buffer to complete frame size with zeros
-        while self.frame_buffer.len() < NNNOISE_FRAME_SIZE {
-            self.frame_buffer.push(0.0);
+        self.frame_buffer.resize(NNNOISE_FRAME_SIZE, 0.0);
+        let frame = std::mem::take(&mut self.frame_buffer);
+        let mut output = self.denoise_frame(&frame).unwrap_or_default();
+
+        // A silent frame pushes out the one nnnoiseless still holds back, minus the padding
+        let tail = self.denoise_frame(&[0.0; NNNOISE_FRAME_SIZE]).unwrap_or_default();
+        output.extend_from_slice(&tail[..remaining.min(tail.len())]);
+        // What the model holds back now is that silent frame, so the next output is dropped again
+        self.primed = false;
+        resample(&output, NNNOISE_SAMPLE_RATE, STT_SAMPLE_RATE)
+    }
+}
+
+/// Energy-based voice activity detector that splits the live input into speech segments.
+/// Frames are classified by RMS against a threshold; a segment is closed once silence has
+/// lasted for the hangover period, so short pauses between words don't split a sentence.
+pub struct VadChunker {
+    sample_rate: u32,
+    frame_len: usize,
+    frame: Vec<f32>,
+    // Recent non-speech audio kept so the onset of speech isn't clipped
+    pre_roll: VecDeque<f32>,
+    pre_roll_len: usize,
+    segment: Vec<f32>,
+    // Samples fed through complete frames so far, and where the current segment starts
+    position: usize,
+    segment_start: usize,
+    in_speech: bool,
+    speech_frames: usize,
+    silence_frames: usize,
+}
+
+impl VadChunker {
+    const FRAME_MS: u32 = 30;
+    const PRE_ROLL_MS: u32 = 300;
+    const HANGOVER_MS: u32 = 700;
+    const MIN_SPEECH_MS: u32 = 250;
+    const MAX_SEGMENT_SECS: u32 = 30;
+    const ENERGY_THRESHOLD: f32 = 0.015;
+
+    pub fn new(sample_rate: u32) -> Self {
+        let frame_len = (sample_rate * Self::FRAME_MS / 1000).max(1) as usize;
+        let pre_roll_len = (sample_rate * Self::PRE_ROLL_MS / 1000) as usize;
+        Self {
+            sample_rate,
+            frame_len,
+            frame: Vec::with_capacity(frame_len),
+            pre_roll: VecDeque::with_capacity(pre_roll_len + frame_len),
+            pre_roll_len,
+            segment: Vec::new(),
+            position: 0,
+            segment_start: 0,
+            in_speech: false,
+            speech_frames: 0,
+            silence_frames: 0,
         }
+    }
 
-        let frame = self.frame_buffer.clone();
-        self.frame_buffer.clear();
+    fn frames_for_ms(&self, ms: u32) -> usize {
+        (ms / Self::FRAME_MS).max(1) as usize
+    }
 
-        // Process the final frame at 16kHz
-        let mut out_frame = vec![0.0f32; NNNOISE_FRAME_SIZE];
-        self.denoise_state
-            .process_frame(&mut out_frame[..], &frame[..]);
-        out_frame
+    /// Feed mono samples and return any speech segments that were completed
+    pub fn push(&mut self, samples: &[f32]) -> Vec<AudioChunk> {
+        let mut completed = Vec::new();
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() == self.frame_len {
+                let frame = std::mem::replace(&mut self.frame, Vec::with_capacity(self.frame_len));
+                if let Some(chunk) = self.process_frame(frame) {
+                    completed.push(chunk);
+                }
+            }
+        }
+        completed
+    }
+
+    fn process_frame(&mut self, frame: Vec<f32>) -> Option<AudioChunk> {
+        let rms = (frame.iter().map(|x| x * x).sum::<f32>() / frame.len() as f32).sqrt();
+        let is_speech = rms > Self::ENERGY_THRESHOLD;
+        let frame_start = self.position;
+        self.position += frame.len();
+
+        if !self.in_speech {
+            if is_speech {
+                self.segment_start = frame_start - self.pre_roll.len();
+                self.in_speech = true;
+                self.speech_frames = 1;
+                self.silence_frames = 0;
+                self.segment.clear();
+                self.segment.extend(self.pre_roll.drain(..));
+                self.segment.extend_from_slice(&frame);
+            } else {
+                self.pre_roll.extend(frame);
+                while self.pre_roll.len() > self.pre_roll_len {
+                    self.pre_roll.pop_front();
+                }
+            }
+            return None;
+        }
+
+        self.segment.extend_from_slice(&frame);
+        if is_speech {
+            self.speech_frames += 1;
+            self.silence_frames = 0;
+        } else {
+            self.silence_frames += 1;
+        }
+
+        let max_segment_len = (self.sample_rate * Self::MAX_SEGMENT_SECS) as usize;
+        if self.silence_frames >= self.frames_for_ms(Self::HANGOVER_MS)
+            || self.segment.len() >= max_segment_len
+        {
+            return self.finish_segment();
+        }
+        None
+    }
+
+    /// Close the current segment, returning it if it contained enough speech
+    fn finish_segment(&mut self) -> Option<AudioChunk> {
+        let segment = std::mem::take(&mut self.segment);
+        let enough_speech = self.speech_frames >= self.frames_for_ms(Self::MIN_SPEECH_MS);
+        self.in_speech = false;
+        self.speech_frames = 0;
+        self.silence_frames = 0;
+        if enough_speech {
+            Some(AudioChunk {
+                data: segment,
+                sample_rate: self.sample_rate,
+                start_secs: self.segment_start as f64 / self.sample_rate as f64,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Return the trailing segment when recording stops mid-speech
+    pub fn flush(&mut self) -> Option<AudioChunk> {
+        if !self.frame.is_empty() {
+            let frame = std::mem::take(&mut self.frame);
+            if self.in_speech {
+                self.segment.extend_from_slice(&frame);
+            }
+        }
+        self.pre_roll.clear();
+        if self.in_speech {
+            self.finish_segment()
+        } else {
+            None
+        }
+    }
+}
+
+/// Standby input stream that keeps the last few hundred milliseconds of audio between
+/// recordings, so speech that starts just before the hotkey registers isn't clipped.
+/// The microphone stays open while this exists, so it is only armed while pre-roll is enabled.
+pub struct PreRollBuffer {
+    _stream: cpal::Stream,
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    sample_rate: u32,
+}
+
+impl PreRollBuffer {
+    pub fn start(
+        audio_device: &str,
+        pre_roll_ms: u32,
+        mono_mixdown: MonoMixdown,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let host = cpal::default_host();
+        let device = AudioCapture::find_input_device(&host, audio_device)?;
+        let config = AudioCapture::input_config(&device)?;
+        let sample_rate = config.sample_rate().0;
+        let capacity = (sample_rate as u64 * pre_roll_ms as u64 / 1000) as usize;
+        let samples = Arc::new(Mutex::new(VecDeque::with_capacity(capacity)));
+
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => {
+                Self::build_stream::<f32>(&device, &config.into(), samples.clone(), capacity, mono_mixdown)?
+            }
+            cpal::SampleFormat::I16 => {
+                Self::build_stream::<i16>(&device, &config.into(), samples.clone(), capacity, mono_mixdown)?
+            }
+            cpal::SampleFormat::U16 => {
+                Self::build_stream::<u16>(&device, &config.into(), samples.clone(), capacity, mono_mixdown)?
+            }
+            _ => return Err("Unsupported sample format".into()),
+        };
+        stream.play()?;
+
+        DebugLogger::log_info(&format!(
+            "Pre-roll buffer armed: {}ms at {}Hz",
+            pre_roll_ms, sample_rate
+        ));
+        Ok(Self {
+            _stream: stream,
+            samples,
+            sample_rate,
+        })
+    }
+
+    fn build_stream<T>(
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        samples: Arc<Mutex<VecDeque<f32>>>,
+        capacity: usize,
+        mono_mixdown: MonoMixdown,
+    ) -> Result<cpal::Stream, Box<dyn std::error::Error + Send + Sync>>
+    where
+        T: Sample + cpal::SizedSample + Send + 'static,
+        f32: FromSample<T>,
+    {
+        let channels = config.channels as usize;
+        let stream = device.build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let mut ring = samples.lock().unwrap();
+                for frame in data.chunks(channels) {
+                    if ring.len() == capacity {
+                        ring.pop_front();
+                    }
+                    // Same downmix as the recording the samples are prepended to
+                    ring.push_back(mono_mixdown.mix(frame));
+                }
+            },
+            move |err| {
+                DebugLogger::log_info(&format!("Pre-roll input error: {}", err));
+            },
+            None,
+        )?;
+        Ok(stream)
+    }
+
+    /// Snapshot of the buffered audio and its sample rate
+    pub fn take(&self) -> (Vec<f32>, u32) {
+        let mut ring = self.samples.lock().unwrap();
+        (ring.drain(..).collect(), self.sample_rate)
     }
 }
 
@@ -198,6 +813,32 @@ pub struct AudioCapture {
     audio_buffer: Arc<Mutex<Vec<f32>>>,
     sample_rate: Arc<Mutex<u32>>,
     noise_reducer: Arc<Mutex<Option<NoiseReducer>>>,
+    // Present only when real-time chunking is enabled
+    vad_chunker: Arc<Mutex<Option<VadChunker>>>,
+    // Set when the recording is cancelled so the captured audio is discarded instead of sent
+    is_cancelled: Arc<Mutex<bool>>,
+    // Set by the stream error callback when the input device disappears (e.g. USB mic unplugged)
+    device_lost: Arc<AtomicBool>,
+    // Kept so the stream can be rebuilt on another device mid-recording
+    chunk_tx: Option<mpsc::Sender<AudioChunk>>,
+    level_tx: Option<mpsc::SyncSender<f32>>,
+    device_tx: Option<mpsc::Sender<DeviceEvent>>,
+    mono_mixdown: MonoMixdown,
+    capture_source: CaptureSource,
+    // Second stream in `CaptureSource::Both`; its audio is kept at the loopback device rate and
+    // resampled and mixed into the microphone once, when the capture is finalized
+    loopback_stream: Option<cpal::Stream>,
+    loopback_buffer: Arc<Mutex<Vec<f32>>>,
+    loopback_rate: Arc<Mutex<u32>>,
+}
+
+/// Input device changes detected while recording
+#[derive(Debug, Clone)]
+pub enum DeviceEvent {
+    /// The device was lost and capture continued on this (default) device
+    Rebound(String),
+    /// The device was lost and no replacement could be opened; capture was stopped
+    Lost(String),
 }
 
 /// Simple audio chunk containing raw audio data
@@ -205,11 +846,13 @@ pub struct AudioCapture {
 pub struct AudioChunk {
     pub data: Vec<f32>,
     pub sample_rate: u32,
+    /// Where the chunk starts in the recording, in seconds (0 for the whole recording)
+    pub start_secs: f64,
 }
 
 impl AudioChunk {
     pub fn new(data: Vec<f32>, sample_rate: u32) -> Self {
-        Self { data, sample_rate }
+        Self { data, sample_rate, start_secs: 0.0 }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -219,8 +862,7 @@ impl AudioChunk {
     /// Check if audio chunk has sufficient volume to process
     pub fn has_audio_activity(&self) -> bool {
         // Simple volume check - consider it active if any sample is above threshold
-        let threshold = 0.01; // Adjust as needed
-        self.data.iter().any(|&sample| sample.abs() > threshold)
+        self.data.iter().any(|&sample| sample.abs() > ACTIVITY_THRESHOLD)
     }
 }
 impl AudioCapture {
@@ -231,13 +873,38 @@ impl AudioCapture {
             audio_buffer: Arc::new(Mutex::new(Vec::new())),
             sample_rate: Arc::new(Mutex::new(16000)), // Default sample rate
             noise_reducer: Arc::new(Mutex::new(None)),
+            vad_chunker: Arc::new(Mutex::new(None)),
+            is_cancelled: Arc::new(Mutex::new(false)),
+            device_lost: Arc::new(AtomicBool::new(false)),
+            chunk_tx: None,
+            mono_mixdown: MonoMixdown::Average,
+            level_tx: None,
+            device_tx: None,
+            capture_source: CaptureSource::Microphone,
+            loopback_stream: None,
+            loopback_buffer: Arc::new(Mutex::new(Vec::new())),
+            loopback_rate: Arc::new(Mutex::new(16000)),
         }
     }
 
-    /// Start recording audio from the default microphone
+    /// Whether the stream is still capturing (false once stopped, cancelled or the device was lost)
+    pub fn is_active(&self) -> bool {
+        *self.is_recording.lock().unwrap()
+    }
+
+    /// Start recording audio from the configured microphone ("default" uses the host default),
+    /// or from the default output device when `capture_source` is loopback
     pub fn start_capture(
         &mut self,
-        _audio_chunking_enabled: bool,
+        capture_source: CaptureSource,
+        audio_device: &str,
+        audio_chunking_enabled: bool,
+        noise_reduction_enabled: bool,
+        denoise_strength: f32,
+        silence_trim: Option<SilenceTrim>,
+        mono_mixdown: MonoMixdown,
+        level_tx: Option<mpsc::SyncSender<f32>>,
+        device_tx: Option<mpsc::Sender<DeviceEvent>>,
     ) -> Result<mpsc::Receiver<AudioChunk>, Box<dyn std::error::Error + Send + Sync>> {
         DebugLogger::log_info("AudioCapture::start_capture() called");
 
@@ -252,15 +919,19 @@ impl AudioCapture {
         let host = cpal::default_host();
         DebugLogger::log_info(&format!("Audio host: {:?}", host.id()));
 
-        let device = host
-            .default_input_device()
-            .ok_or("No input device available")?;
+        let (device, config) = match capture_source {
+            CaptureSource::Microphone | CaptureSource::Both => {
+                let device = Self::find_input_device(&host, audio_device)?;
+                let config = Self::input_config(&device)?;
+                (device, config)
+            }
+            CaptureSource::SystemLoopback => Self::loopback_device(&host)?,
+        };
         DebugLogger::log_info(&format!(
-            "Input device: {:?}",
-            device.name().unwrap_or_default()
+            "Input device: {:?} ({:?})",
+            device.name().unwrap_or_default(),
+            capture_source
         ));
-
-        let config = device.default_input_config()?;
         let sample_rate = config.sample_rate().0;
         DebugLogger::log_info(&format!(
             "Audio config: sample_rate={}Hz, channels={}, format={:?}",
@@ -275,20 +946,24 @@ impl AudioCapture {
             *sr = sample_rate;
         }
 
-        // Initialize noise reducer
-        {
+        // Initialize noise reducer (when disabled the final audio is only resampled to 16kHz)
+        if !noise_reduction_enabled {
+            let mut noise_reducer = self.noise_reducer.lock().unwrap();
+            *noise_reducer = None;
+            DebugLogger::log_info("Noise reduction disabled - final audio will only be resampled");
+        } else {
             let mut noise_reducer = self.noise_reducer.lock().unwrap();
-            *noise_reducer = Some(NoiseReducer::new(sample_rate));
+            *noise_reducer = Some(NoiseReducer::new(sample_rate, denoise_strength));
             DebugLogger::log_info(&format!(
-                "Noise reducer initialized for {}Hz (nnnoiseless works best at 16kHz)",
-                sample_rate
+                "Noise reducer initialized for {}Hz input at strength {:.2} (denoised at {}Hz)",
+                sample_rate, denoise_strength, NNNOISE_SAMPLE_RATE
             ));
 
-            // Warn if sample rate is not optimal for nnnoiseless
-            if sample_rate != 16000 {
+            // Upsampled input leaves the model's upper bands empty, which it handles but wasn't trained on
+            if sample_rate < NNNOISE_SAMPLE_RATE {
                 DebugLogger::log_info(&format!(
-                    "WARNING: Sample rate is {}Hz, but nnnoiseless is optimized for 16kHz. Noise reduction may be less effective.",
-                    sample_rate
+                    "Input at {}Hz is upsampled to {}Hz for noise reduction",
+                    sample_rate, NNNOISE_SAMPLE_RATE
                 ));
             }
         }
@@ -299,7 +974,24 @@ impl AudioCapture {
             buffer.clear();
         }
 
-        // Create a channel for sending the final audio chunk when recording stops
+        // Reset cancellation from any previous session
+        {
+            let mut cancelled = self.is_cancelled.lock().unwrap();
+            *cancelled = false;
+        }
+
+        // Set up voice activity detection when real-time chunking is requested
+        {
+            let mut vad_chunker = self.vad_chunker.lock().unwrap();
+            *vad_chunker = if audio_chunking_enabled {
+                DebugLogger::log_info("VAD chunking enabled: speech segments will be emitted while recording");
+                Some(VadChunker::new(sample_rate))
+            } else {
+                None
+            };
+        }
+
+        // Create a channel for sending audio chunks (VAD segments while recording, final audio on stop)
         let (tx, rx) = mpsc::channel();
 
         // Set recording state to true
@@ -309,32 +1001,36 @@ impl AudioCapture {
         }
         DebugLogger::log_info("Audio recording state set to true");
 
+        self.device_lost.store(false, Ordering::SeqCst);
+        self.chunk_tx = Some(tx.clone());
+        self.level_tx = level_tx;
+        self.device_tx = device_tx;
+        self.mono_mixdown = mono_mixdown;
+        self.capture_source = capture_source;
+
         // Build the audio stream
-        let stream = match config.sample_format() {
-            cpal::SampleFormat::F32 => {
-                DebugLogger::log_info("Building F32 input stream");
-                self.build_input_stream::<f32>(&device, &config.into(), sample_rate)?
-            }
-            cpal::SampleFormat::I16 => {
-                DebugLogger::log_info("Building I16 input stream");
-                self.build_input_stream::<i16>(&device, &config.into(), sample_rate)?
-            }
-            cpal::SampleFormat::U16 => {
-                DebugLogger::log_info("Building U16 input stream");
-                self.build_input_stream::<u16>(&device, &config.into(), sample_rate)?
-            }
-            _ => return Err("Unsupported sample format".into()),
-        };
+        let stream = self.open_stream(&device, config, sample_rate)?;
 
         DebugLogger::log_info("Starting audio stream");
         stream.play()?;
         self.stream = Some(stream);
 
+        if capture_source == CaptureSource::Both {
+            if audio_chunking_enabled {
+                DebugLogger::log_warn("Loopback audio is mixed into the finished recording only; live speech segments carry the microphone");
+            }
+            self.start_loopback_mix(&host);
+        }
+
         // Spawn a thread to monitor for stop and send the final audio chunk
         let audio_buffer = self.audio_buffer.clone();
         let is_recording = self.is_recording.clone();
         let sample_rate_arc = self.sample_rate.clone();
         let noise_reducer_arc = self.noise_reducer.clone();
+        let vad_chunker_arc = self.vad_chunker.clone();
+        let is_cancelled = self.is_cancelled.clone();
+        let loopback_buffer = self.loopback_buffer.clone();
+        let loopback_rate_arc = self.loopback_rate.clone();
 
         std::thread::spawn(move || {
             // Wait for recording to stop
@@ -346,8 +1042,16 @@ impl AudioCapture {
                 }
             }
 
+            // A cancelled recording is dropped without sending anything downstream
+            if *is_cancelled.lock().unwrap() {
+                DebugLogger::log_info("Recording was cancelled - discarding captured audio");
+                audio_buffer.lock().unwrap().clear();
+                loopback_buffer.lock().unwrap().clear();
+                return;
+            }
+
             // Get the final audio data
-            let final_audio = {
+            let mut final_audio = {
                 let buffer = audio_buffer.lock().unwrap();
                 buffer.clone()
             };
@@ -357,6 +1061,40 @@ impl AudioCapture {
                 *sample_rate
             };
 
+            // In chunking mode the speech segments were already sent; only the trailing one is left
+            let trailing_chunk = {
+                let mut vad_chunker = vad_chunker_arc.lock().unwrap();
+                vad_chunker.take().map(|mut chunker| chunker.flush())
+            };
+            if let Some(trailing_chunk) = trailing_chunk {
+                match trailing_chunk {
+                    Some(chunk) => {
+                        DebugLogger::log_info(&format!(
+                            "VAD: Sending trailing speech segment: {} samples at {}Hz",
+                            chunk.data.len(),
+                            chunk.sample_rate
+                        ));
+                        let _ = tx.send(chunk);
+                    }
+                    None => DebugLogger::log_info("VAD: No trailing speech segment to send"),
+                }
+                loopback_buffer.lock().unwrap().clear();
+                return;
+            }
+
+            // Both sources: resample the whole loopback recording once and add it onto the microphone
+            let loopback = std::mem::take(&mut *loopback_buffer.lock().unwrap());
+            if !loopback.is_empty() {
+                let loopback_rate = *loopback_rate_arc.lock().unwrap();
+                DebugLogger::log_info(&format!(
+                    "Mixing {} loopback samples at {}Hz into the microphone at {}Hz",
+                    loopback.len(),
+                    loopback_rate,
+                    sr
+                ));
+                mix_loopback(&mut final_audio, &resample(&loopback, loopback_rate, sr));
+            }
+
             if !final_audio.is_empty() {
                 DebugLogger::log_info(&format!(
                     "Processing {} samples through noise reduction",
@@ -388,14 +1126,29 @@ impl AudioCapture {
                         processed.extend_from_slice(&remaining);
                         processed
                     } else {
-                        // If no noise reducer, just downsample
+                        // If no noise reducer, just resample
                         DebugLogger::log_info(
-                            "NOISE_REDUCTION: No noise reducer available, downsampling only",
+                            "NOISE_REDUCTION: No noise reducer available, resampling only",
                         );
-                        downsample_audio(&final_audio, sr, 16000)
+                        resample(&final_audio, sr, 16000)
                     }
                 };
 
+                // Trim on the 16kHz buffer so the thresholds don't depend on the device rate
+                let processed_audio = match silence_trim {
+                    Some(trim) => {
+                        let trimmed = trim_silence(&processed_audio, 16000, trim);
+                        DebugLogger::log_info(&format!(
+                            "SILENCE_TRIM: {} -> {} samples ({:.1}s removed)",
+                            processed_audio.len(),
+                            trimmed.len(),
+                            (processed_audio.len() - trimmed.len()) as f32 / 16000.0
+                        ));
+                        trimmed
+                    }
+                    None => processed_audio,
+                };
+
                 // Log comparison for debugging
                 let original_samples = final_audio.len();
                 let processed_samples = processed_audio.len();
@@ -465,6 +1218,292 @@ impl AudioCapture {
         Ok(rx)
     }
 
+    /// Insert audio captured before the stream started (pre-roll) at the front of the recording.
+    /// Only the single-recording path uses it; VAD chunking keeps its own pre-roll.
+    pub fn prepend_audio(&self, samples: Vec<f32>, sample_rate: u32) {
+        if samples.is_empty() || self.vad_chunker.lock().unwrap().is_some() {
+            return;
+        }
+        let capture_rate = *self.sample_rate.lock().unwrap();
+        let samples = if sample_rate == capture_rate {
+            samples
+        } else {
+            resample(&samples, sample_rate, capture_rate)
+        };
+        DebugLogger::log_info(&format!(
+            "Prepending {} pre-roll samples at {}Hz",
+            samples.len(),
+            capture_rate
+        ));
+        // Keep the loopback audio lined up with the microphone it is mixed into later
+        if self.loopback_stream.is_some() {
+            let loopback_rate = *self.loopback_rate.lock().unwrap();
+            let silence = (samples.len() as u64 * loopback_rate as u64 / capture_rate as u64) as usize;
+            let mut loopback = self.loopback_buffer.lock().unwrap();
+            loopback.splice(0..0, std::iter::repeat_n(0.0, silence));
+        }
+        let mut buffer = self.audio_buffer.lock().unwrap();
+        buffer.splice(0..0, samples);
+    }
+
+    /// Build an input stream for the device's sample format
+    fn open_stream(
+        &self,
+        device: &cpal::Device,
+        config: cpal::SupportedStreamConfig,
+        sample_rate: u32,
+    ) -> Result<cpal::Stream, Box<dyn std::error::Error + Send + Sync>> {
+        let chunk_tx = self
+            .chunk_tx
+            .clone()
+            .ok_or("Audio chunk channel is closed")?;
+        let level_tx = self.level_tx.clone();
+
+        match config.sample_format() {
+            cpal::SampleFormat::F32 => {
+                DebugLogger::log_info("Building F32 input stream");
+                self.build_input_stream::<f32>(device, &config.into(), sample_rate, chunk_tx, level_tx)
+            }
+            cpal::SampleFormat::I16 => {
+                DebugLogger::log_info("Building I16 input stream");
+                self.build_input_stream::<i16>(device, &config.into(), sample_rate, chunk_tx, level_tx)
+            }
+            cpal::SampleFormat::U16 => {
+                DebugLogger::log_info("Building U16 input stream");
+                self.build_input_stream::<u16>(device, &config.into(), sample_rate, chunk_tx, level_tx)
+            }
+            _ => Err("Unsupported sample format".into()),
+        }
+    }
+
+    /// Called periodically by the audio manager: if the input device disappeared mid-recording,
+    /// rebind to the current default device, or stop cleanly so the audio captured so far is kept
+    pub fn check_device(&mut self) {
+        if !self.device_lost.swap(false, Ordering::SeqCst) || !self.is_active() {
+            return;
+        }
+
+        DebugLogger::log_warn("Input device lost during recording, trying to rebind to the default device");
+        let event = match self.rebind_default_device() {
+            Ok(name) => {
+                DebugLogger::log_info(&format!("Recording continued on input device: {}", name));
+                DeviceEvent::Rebound(name)
+            }
+            Err(e) => {
+                let msg = format!("Input device lost and no replacement available: {}", e);
+                DebugLogger::log_pipeline_error("audio", &msg);
+                // Stop and release our sender so the pipeline finishes with what was captured
+                if let Err(stop_err) = self.stop_recording() {
+                    DebugLogger::log_pipeline_error("audio", &format!("Error stopping capture: {}", stop_err));
+                }
+                DeviceEvent::Lost(msg)
+            }
+        };
+
+        if let Some(ref device_tx) = self.device_tx {
+            let _ = device_tx.send(event);
+        }
+    }
+
+    fn rebind_default_device(&mut self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        // Drop the dead stream before opening a new one
+        self.stream = None;
+
+        let host = cpal::default_host();
+        let (device, config) = match self.capture_source {
+            CaptureSource::Microphone | CaptureSource::Both => {
+                let device = host
+                    .default_input_device()
+                    .ok_or("No input device available")?;
+                let config = Self::input_config(&device)?;
+                (device, config)
+            }
+            CaptureSource::SystemLoopback => Self::loopback_device(&host)?,
+        };
+        let name = device.name().unwrap_or_default();
+        let new_rate = config.sample_rate().0;
+
+        let old_rate = *self.sample_rate.lock().unwrap();
+        if new_rate != old_rate {
+            DebugLogger::log_info(&format!(
+                "Replacement device runs at {}Hz (was {}Hz), resampling captured audio",
+                new_rate, old_rate
+            ));
+            {
+                let mut buffer = self.audio_buffer.lock().unwrap();
+                let converted = resample(&buffer, old_rate, new_rate);
+                *buffer = converted;
+            }
+            *self.sample_rate.lock().unwrap() = new_rate;
+            {
+                let mut noise_reducer = self.noise_reducer.lock().unwrap();
+                if let Some(strength) = noise_reducer.as_ref().map(NoiseReducer::strength) {
+                    *noise_reducer = Some(NoiseReducer::new(new_rate, strength));
+                }
+            }
+            // A partially detected speech segment at the old rate can't be continued
+            let mut vad_chunker = self.vad_chunker.lock().unwrap();
+            if vad_chunker.is_some() {
+                *vad_chunker = Some(VadChunker::new(new_rate));
+            }
+        }
+
+        let stream = self.open_stream(&device, config, new_rate)?;
+        stream.play()?;
+        self.stream = Some(stream);
+        Ok(name)
+    }
+
+    /// Open the loopback stream whose audio is mixed into the microphone when the capture is
+    /// finalized. On failure the recording continues with the microphone alone.
+    fn start_loopback_mix(&mut self, host: &cpal::Host) {
+        self.loopback_stream = None;
+        self.loopback_buffer.lock().unwrap().clear();
+
+        let result = Self::loopback_device(host).and_then(|(device, config)| {
+            let loopback_rate = config.sample_rate().0;
+            DebugLogger::log_info(&format!(
+                "Loopback mix: {:?} at {}Hz",
+                device.name().unwrap_or_default(),
+                loopback_rate
+            ));
+            *self.loopback_rate.lock().unwrap() = loopback_rate;
+            let buffer = self.loopback_buffer.clone();
+            let stream = match config.sample_format() {
+                cpal::SampleFormat::F32 => Self::build_loopback_stream::<f32>(&device, &config.into(), buffer),
+                cpal::SampleFormat::I16 => Self::build_loopback_stream::<i16>(&device, &config.into(), buffer),
+                cpal::SampleFormat::U16 => Self::build_loopback_stream::<u16>(&device, &config.into(), buffer),
+                _ => Err("Unsupported sample format".into()),
+            }?;
+            stream.play()?;
+            Ok(stream)
+        });
+        match result {
+            Ok(stream) => self.loopback_stream = Some(stream),
+            Err(e) => DebugLogger::log_warn(&format!(
+                "Loopback mix unavailable, recording the microphone only: {}",
+                e
+            )),
+        }
+    }
+
+    fn build_loopback_stream<T>(
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        buffer: Arc<Mutex<Vec<f32>>>,
+    ) -> Result<cpal::Stream, Box<dyn std::error::Error + Send + Sync>>
+    where
+        T: Sample + cpal::SizedSample + Send + 'static,
+        f32: FromSample<T>,
+    {
+        let channels = config.channels as usize;
+        let stream = device.build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let mut buffer = buffer.lock().unwrap();
+                buffer.extend(data.chunks(channels).map(|frame| MonoMixdown::Average.mix(frame)));
+            },
+            move |err| {
+                DebugLogger::log_info(&format!("Loopback input error: {}", err));
+            },
+            None,
+        )?;
+        Ok(stream)
+    }
+
+    /// Look up an input device by name, falling back to the host default if it can't be found
+    fn find_input_device(
+        host: &cpal::Host,
+        audio_device: &str,
+    ) -> Result<cpal::Device, Box<dyn std::error::Error + Send + Sync>> {
+        let requested = audio_device.trim();
+        if !requested.is_empty() && requested != "default" {
+            match host.input_devices() {
+                Ok(devices) => {
+                    for device in devices {
+                        if device.name().map(|name| name == requested).unwrap_or(false) {
+                            DebugLogger::log_info(&format!(
+                                "Using configured input device: {}",
+                                requested
+                            ));
+                            return Ok(device);
+                        }
+                    }
+                    DebugLogger::log_warn(&format!(
+                        "WARNING: Configured input device '{}' not found, falling back to default input device",
+                        requested
+                    ));
+                }
+                Err(e) => {
+                    DebugLogger::log_warn(&format!(
+                        "WARNING: Failed to enumerate input devices ({}), falling back to default input device",
+                        e
+                    ));
+                }
+            }
+        }
+
+        host.default_input_device()
+            .ok_or_else(|| "No input device available".into())
+    }
+
+    /// The default output device and its mix format. cpal opens an input stream on a WASAPI
+    /// output device in loopback mode, capturing what it plays. WASAPI delivers no data while
+    /// nothing is playing, so silent stretches are simply absent from the recording.
+    fn loopback_device(
+        host: &cpal::Host,
+    ) -> Result<(cpal::Device, cpal::SupportedStreamConfig), Box<dyn std::error::Error + Send + Sync>> {
+        if !CaptureSource::loopback_supported() {
+            return Err("System audio loopback capture is only supported on Windows (WASAPI)".into());
+        }
+        let device = host
+            .default_output_device()
+            .ok_or("No output device available for loopback capture")?;
+        let config = device.default_output_config()?;
+        if !is_readable_format(config.sample_format()) {
+            return Err(format!("Unsupported loopback sample format {:?}", config.sample_format()).into());
+        }
+        Ok((device, config))
+    }
+
+    /// The device's default input config, or the best supported one when the default is
+    /// unavailable or in a sample format we can't read (seen on some ALSA setups)
+    pub fn input_config(
+        device: &cpal::Device,
+    ) -> Result<cpal::SupportedStreamConfig, Box<dyn std::error::Error + Send + Sync>> {
+        let default_err = match device.default_input_config() {
+            Ok(config) if is_readable_format(config.sample_format()) => return Ok(config),
+            Ok(config) => format!("unsupported sample format {:?}", config.sample_format()),
+            Err(e) => e.to_string(),
+        };
+        DebugLogger::log_warn(&format!(
+            "Default input config unusable ({}), searching supported configs",
+            default_err
+        ));
+
+        let config = device
+            .supported_input_configs()?
+            .filter(|range| is_readable_format(range.sample_format()))
+            .map(|range| {
+                let (rate, rank) = rank_config_range(
+                    range.channels(),
+                    range.min_sample_rate().0,
+                    range.max_sample_rate().0,
+                );
+                (rank, range.with_sample_rate(cpal::SampleRate(rate)))
+            })
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, config)| config)
+            .ok_or_else(|| format!("No usable input config (default config: {})", default_err))?;
+        DebugLogger::log_info(&format!(
+            "Using fallback input config: sample_rate={}Hz, channels={}, format={:?}",
+            config.sample_rate().0,
+            config.channels(),
+            config.sample_format()
+        ));
+        Ok(config)
+    }
+
     /// Stop recording and clean up
     pub fn stop_recording(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         DebugLogger::log_info("AudioCapture::stop_recording() called");
@@ -476,11 +1515,15 @@ impl AudioCapture {
         }
         DebugLogger::log_info("Recording state set to false");
 
+        // Release our copy of the chunk sender so the channel closes once the monitor thread is done
+        self.chunk_tx = None;
+
         // Stop and drop the stream
         if let Some(stream) = self.stream.take() {
             drop(stream);
             DebugLogger::log_info("Audio stream stopped and dropped");
         }
+        self.loopback_stream = None;
 
         // Note: We don't clean up the noise reducer here because the background thread
         // might still be processing the final audio chunk. The noise reducer will be
@@ -489,11 +1532,38 @@ impl AudioCapture {
         Ok(())
     }
 
+    /// Cancel recording: stop the stream and discard all captured audio
+    pub fn cancel_recording(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        DebugLogger::log_info("AudioCapture::cancel_recording() called");
+
+        // Mark as cancelled before stopping so the monitor thread sees it when it wakes up
+        {
+            let mut cancelled = self.is_cancelled.lock().unwrap();
+            *cancelled = true;
+        }
+        {
+            let mut vad_chunker = self.vad_chunker.lock().unwrap();
+            *vad_chunker = None;
+        }
+
+        self.stop_recording()?;
+
+        {
+            let mut buffer = self.audio_buffer.lock().unwrap();
+            buffer.clear();
+        }
+        DebugLogger::log_info("Captured audio discarded");
+
+        Ok(())
+    }
+
     fn build_input_stream<T>(
         &self,
         device: &cpal::Device,
         config: &cpal::StreamConfig,
         sample_rate: u32,
+        chunk_tx: mpsc::Sender<AudioChunk>,
+        level_tx: Option<mpsc::SyncSender<f32>>,
     ) -> Result<cpal::Stream, Box<dyn std::error::Error + Send + Sync>>
     where
         T: Sample + cpal::SizedSample + Send + 'static,
@@ -507,6 +1577,12 @@ impl AudioCapture {
 
         let is_recording = self.is_recording.clone();
         let audio_buffer = self.audio_buffer.clone();
+        let vad_chunker = self.vad_chunker.clone();
+        let device_lost = self.device_lost.clone();
+        let mono_mixdown = self.mono_mixdown;
+        // Level meter state lives in the callback; peaks are held between throttled updates
+        let mut last_level_sent = std::time::Instant::now();
+        let mut level_peak = 0.0f32;
 
         let stream = device.build_input_stream(
             config,
@@ -516,21 +1592,48 @@ impl AudioCapture {
                     return;
                 }
 
-                // Convert samples to f32 and take only first channel (mono)
+                // Convert samples to f32 and fold the channels down to mono
                 let samples: Vec<f32> = data
                     .chunks(channels)
-                    .map(|chunk| chunk[0].to_sample())
+                    .map(|chunk| mono_mixdown.mix(chunk))
                     .collect();
 
+                // Report the input level (~30Hz) without ever blocking the audio thread
+                if let Some(ref level_tx) = level_tx {
+                    let peak = samples.iter().map(|x| x.abs()).fold(0.0f32, f32::max);
+                    level_peak = level_peak.max(peak);
+                    if last_level_sent.elapsed() >= LEVEL_UPDATE_INTERVAL {
+                        let _ = level_tx.try_send(level_peak.clamp(0.0, 1.0));
+                        level_peak = 0.0;
+                        last_level_sent = std::time::Instant::now();
+                    }
+                }
+
                 // Append to buffer
                 {
                     let mut buffer = audio_buffer.lock().unwrap();
                     buffer.extend_from_slice(&samples);
                 }
+
+                // Emit completed speech segments when chunking is enabled
+                if let Some(chunker) = vad_chunker.lock().unwrap().as_mut() {
+                    for chunk in chunker.push(&samples) {
+                        DebugLogger::log_info(&format!(
+                            "VAD: Emitting speech segment: {} samples at {}Hz",
+                            chunk.data.len(),
+                            chunk.sample_rate
+                        ));
+                        let _ = chunk_tx.send(chunk);
+                    }
+                }
             },
             move |err| {
                 eprintln!("Audio input error: {}", err);
                 DebugLogger::log_info(&format!("Audio input error: {}", err));
+                // The audio manager polls this flag and rebinds off the audio thread
+                if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                    device_lost.store(true, Ordering::SeqCst);
+                }
             },
             None,
         )?;
@@ -538,3 +1641,258 @@ impl AudioCapture {
         Ok(stream)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_config_range_prefers_mono_near_16k() {
+        assert_eq!(rank_config_range(1, 8000, 48000), (16000, (false, 0)));
+        assert_eq!(rank_config_range(2, 44100, 48000), (44100, (true, 28100)));
+        let mono_44k = rank_config_range(1, 44100, 44100).1;
+        let stereo_16k = rank_config_range(2, 16000, 16000).1;
+        assert!(mono_44k < stereo_16k);
+    }
+
+    #[test]
+    fn test_resample_same_rate_is_passthrough() {
+        let input = vec![0.1, -0.2, 0.3];
+        assert_eq!(resample(&input, 16000, 16000), input);
+    }
+
+    #[test]
+    fn test_resample_output_length() {
+        let input = vec![0.0f32; 48000];
+        assert_eq!(resample(&input, 48000, 16000).len(), 16000);
+    }
+
+    #[test]
+    fn test_resample_preserves_dc() {
+        let input = vec![0.5f32; 4800];
+        let output = resample(&input, 48000, 16000);
+        assert!(output.iter().all(|&s| (s - 0.5).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_resample_rejects_above_nyquist() {
+        // 12kHz tone at 48kHz would alias to 4kHz with naive decimation to 16kHz
+        let input: Vec<f32> = (0..48000)
+            .map(|i| (2.0 * std::f32::consts::PI * 12000.0 * i as f32 / 48000.0).sin())
+            .collect();
+        let output = resample(&input, 48000, 16000);
+        let inner = &output[100..output.len() - 100];
+        let peak = inner.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+        assert!(peak < 0.05, "aliased peak too high: {}", peak);
+    }
+
+    #[test]
+    fn test_capture_source_from_setting() {
+        assert_eq!(CaptureSource::from_setting("system_loopback"), CaptureSource::SystemLoopback);
+        assert_eq!(CaptureSource::from_setting("both"), CaptureSource::Both);
+        assert_eq!(CaptureSource::from_setting("microphone"), CaptureSource::Microphone);
+        assert_eq!(CaptureSource::from_setting(""), CaptureSource::Microphone);
+    }
+
+    #[test]
+    fn test_mix_loopback() {
+        let mut samples = vec![0.25, 0.5, 0.9, 0.1];
+        mix_loopback(&mut samples, &[0.25, 0.5, 0.5]);
+        assert_eq!(samples, vec![0.5, 1.0, 1.0, 0.1]);
+
+        // Loopback audio longer than the microphone is cut off at the microphone's end
+        let mut samples = vec![0.0, 0.0];
+        mix_loopback(&mut samples, &[0.1, 0.2, 0.3]);
+        assert_eq!(samples, vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn test_mono_mixdown() {
+        let frame = [0.2f32, 0.6];
+        assert_eq!(MonoMixdown::First.mix(&frame), 0.2);
+        assert!((MonoMixdown::Average.mix(&frame) - 0.4).abs() < 1e-6);
+        assert_eq!(MonoMixdown::from_setting("first"), MonoMixdown::First);
+        assert_eq!(MonoMixdown::from_setting("unknown"), MonoMixdown::Average);
+    }
+
+    #[test]
+    fn test_estimate_snr_db() {
+        // Speech-like bursts over a faint noise floor
+        let mut noisy: Vec<f32> = (0..48_000).map(|i| if i % 7 == 0 { 0.001 } else { -0.001 }).collect();
+        for sample in &mut noisy[16_000..32_000] {
+            *sample += 0.3;
+        }
+        let snr = estimate_snr_db(&noisy, 480).unwrap();
+        assert!(snr > 40.0, "{}", snr);
+
+        // Steady noise has no quiet frames to compare against
+        let flat = vec![0.2f32; 48_000];
+        assert!(estimate_snr_db(&flat, 480).unwrap().abs() < 0.1);
+        assert_eq!(estimate_snr_db(&flat[..4_000], 480), None);
+    }
+
+    // Voiced-speech-like test signal: 150Hz harmonics with a 4Hz syllable envelope over light noise
+    fn speech_like(sample_rate: u32, len: usize) -> Vec<f32> {
+        let mut seed = 7u32;
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                let envelope = (std::f32::consts::PI * 4.0 * t).sin().abs();
+                let voice: f32 = (1..=5)
+                    .map(|h| (2.0 * std::f32::consts::PI * 150.0 * h as f32 * t).sin() / h as f32)
+                    .sum();
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                let noise = ((seed >> 16) as f32 / 32768.0 - 1.0) * 0.02;
+                0.3 * envelope * voice + noise
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+    }
+
+    #[test]
+    fn test_noise_reducer_output_length_and_level() {
+        for rate in [16000, 44100, 48000] {
+            let input = speech_like(rate, rate as usize);
+            let mut reducer = NoiseReducer::new(rate, 1.0);
+            let mut output = Vec::new();
+            // Capture callbacks rarely line up with the 10ms model frames
+            for chunk in input.chunks(1000) {
+                output.extend(reducer.process_audio(chunk));
+            }
+            output.extend(reducer.flush());
+            let expected = input.len() * STT_SAMPLE_RATE as usize / rate as usize;
+            assert!(
+                output.len().abs_diff(expected) <= 16,
+                "{}Hz: {} samples out, expected about {}",
+                rate,
+                output.len(),
+                expected
+            );
+            assert!(rms(&output) > 0.2 * rms(&input), "{}Hz: denoised output is near silent", rate);
+        }
+    }
+
+    #[test]
+    fn test_noise_reducer_flush_keeps_partial_frame() {
+        // Ten full 48kHz frames plus a partial one; each input sample maps to exactly 1/3 output sample
+        let input = speech_like(NNNOISE_SAMPLE_RATE, NNNOISE_FRAME_SIZE * 10 + 123);
+        let mut reducer = NoiseReducer::new(NNNOISE_SAMPLE_RATE, 1.0);
+        let mut output = reducer.process_audio(&input);
+        // The first frame is held back by the model lag
+        assert_eq!(output.len(), NNNOISE_FRAME_SIZE * 9 / 3);
+        output.extend(reducer.flush());
+        assert_eq!(output.len(), input.len() / 3);
+        // A second flush has nothing left to emit
+        assert!(reducer.flush().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_peak() {
+        let mut quiet = vec![0.1f32, -0.2, 0.05];
+        let gain = normalize_peak(&mut quiet);
+        assert!((gain - 0.708 / 0.2).abs() < 1e-4);
+        assert!((quiet[1] + 0.708).abs() < 1e-4);
+
+        // Gain is capped for near-silent input
+        let mut faint = vec![0.001f32; 4];
+        assert_eq!(normalize_peak(&mut faint), 10.0);
+
+        let mut loud = vec![0.9f32, -0.5];
+        assert_eq!(normalize_peak(&mut loud), 1.0);
+        assert_eq!(loud, vec![0.9, -0.5]);
+    }
+
+    #[test]
+    fn test_decode_wav_roundtrip() {
+        let samples = vec![0.0, 0.5, -0.5, 0.25];
+        let (decoded, rate) = decode_wav(&encode_wav_bytes(&samples, 16000)).unwrap();
+        assert_eq!(rate, 16000);
+        assert_eq!(decoded.len(), samples.len());
+        for (a, b) in decoded.iter().zip(&samples) {
+            assert!((a - b).abs() < 1e-3);
+        }
+        assert!(decode_wav(b"not a wav file").is_err());
+    }
+
+    #[test]
+    fn test_measure_levels() {
+        assert_eq!(measure_levels(&[]), ClipLevels { peak: 0.0, rms: 0.0, non_silent_samples: 0 });
+        let levels = measure_levels(&[0.5, -0.5, 0.005, -0.005]);
+        assert_eq!(levels.peak, 0.5);
+        assert!((levels.rms - (0.5f32 * 0.5 / 2.0 + 0.005 * 0.005 / 2.0).sqrt()).abs() < 1e-6);
+        assert_eq!(levels.non_silent_samples, 2);
+    }
+
+    #[test]
+    fn test_trim_silence_edges_and_gaps() {
+        let tone = |n: usize| (0..n).map(|i| (i as f32 * 0.1).sin() * 0.5);
+        let silence = |n: usize| std::iter::repeat_n(0.0f32, n);
+        // 1s silence, 0.5s tone, 3s silence, 0.5s tone, 1s silence at 16kHz
+        let samples: Vec<f32> = silence(16000)
+            .chain(tone(8000))
+            .chain(silence(48000))
+            .chain(tone(8000))
+            .chain(silence(16000))
+            .collect();
+
+        let trimmed = trim_silence(&samples, 16000, SilenceTrim { max_gap_ms: 0 });
+        // Only the 200ms padding survives on each side
+        assert_eq!(trimmed.len(), 8000 + 48000 + 8000 + 2 * 3200);
+
+        let collapsed = trim_silence(&samples, 16000, SilenceTrim { max_gap_ms: 1000 });
+        assert_eq!(collapsed.len(), 8000 + 16000 + 8000 + 2 * 3200);
+
+        let quiet = vec![0.0f32; 1600];
+        assert_eq!(trim_silence(&quiet, 16000, SilenceTrim { max_gap_ms: 0 }), quiet);
+    }
+
+    #[test]
+    fn test_split_at_silence_cuts_in_gap() {
+        // 1s tone, 0.1s silence, 1s tone at 16kHz with a 1.2s limit
+        let tone = |n: usize| (0..n).map(|i| (i as f32 * 0.1).sin() * 0.5);
+        let samples: Vec<f32> = tone(16000)
+            .chain(std::iter::repeat_n(0.0, 1600))
+            .chain(tone(16000))
+            .collect();
+        let pieces = split_at_silence(&samples, 16000, 19200);
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces[0].end > 16000 && pieces[0].end < 17600);
+        assert_eq!(pieces[1].end, samples.len());
+        assert!(pieces.iter().all(|p| p.len() <= 19200));
+    }
+
+    #[test]
+    fn test_vad_ignores_silence() {
+        let mut vad = VadChunker::new(16000);
+        assert!(vad.push(&vec![0.0f32; 16000]).is_empty());
+        assert!(vad.flush().is_none());
+    }
+
+    #[test]
+    fn test_vad_emits_segment_after_hangover() {
+        let mut vad = VadChunker::new(16000);
+        let mut chunks = vad.push(&vec![0.0f32; 8000]);
+        chunks.extend(vad.push(&vec![0.2f32; 16000]));
+        assert!(chunks.is_empty());
+        chunks.extend(vad.push(&vec![0.0f32; 16000]));
+        assert_eq!(chunks.len(), 1);
+        // Segment includes the pre-roll, the speech and the hangover
+        assert!(chunks[0].data.len() > 16000);
+        // Speech is detected in the frame at 0.48s; the segment starts 300ms of pre-roll earlier
+        assert!((chunks[0].start_secs - 0.18).abs() < 1e-9, "start {}", chunks[0].start_secs);
+        assert!(vad.flush().is_none());
+    }
+
+    #[test]
+    fn test_vad_flush_returns_trailing_speech() {
+        let mut vad = VadChunker::new(16000);
+        assert!(vad.push(&vec![0.2f32; 8000]).is_empty());
+        let chunk = vad.flush().expect("trailing segment");
+        assert_eq!(chunk.sample_rate, 16000);
+        assert_eq!(chunk.data.len(), 8000);
+        assert_eq!(chunk.start_secs, 0.0);
+    }
+}
@@ -0,0 +1,613 @@
+// Compressed upload encoders for STT requests.
+// WAV stays the default; FLAC is lossless (~50% smaller for speech) and Ogg/Opus is lossy
+// but roughly 20x smaller, which matters for long recordings on slow connections.
+
+/// Container/codec used for the audio sent to the transcription endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadFormat {
+    Wav,
+    Flac,
+    Opus,
+}
+
+impl UploadFormat {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "flac" => UploadFormat::Flac,
+            "opus" | "ogg" if cfg!(feature = "opus") => UploadFormat::Opus,
+            // Built without Opus: still upload compressed, losslessly
+            "opus" | "ogg" => UploadFormat::Flac,
+            _ => UploadFormat::Wav,
+        }
+    }
+
+    /// File name sent in the multipart part; endpoints use the extension to pick a decoder
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            UploadFormat::Wav => "audio.wav",
+            UploadFormat::Flac => "audio.flac",
+            UploadFormat::Opus => "audio.ogg",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            UploadFormat::Wav => "audio/wav",
+            UploadFormat::Flac => "audio/flac",
+            UploadFormat::Opus => "audio/ogg",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FLAC
+// ---------------------------------------------------------------------------
+
+const FLAC_BLOCK_SIZE: usize = 4096;
+const FLAC_BITS_PER_SAMPLE: u32 = 16;
+const FLAC_MAX_FIXED_ORDER: usize = 4;
+const FLAC_MAX_RICE_PARAM: u32 = 14;
+
+/// MSB-first bit writer used to build FLAC frames
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            current: 0,
+            bits: 0,
+        }
+    }
+
+    fn write(&mut self, value: u64, count: u32) {
+        for i in (0..count).rev() {
+            self.current = (self.current << 1) | ((value >> i) & 1);
+            self.bits += 1;
+            if self.bits == 8 {
+                self.bytes.push(self.current as u8);
+                self.current = 0;
+                self.bits = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, count: u32) {
+        let mask = if count == 64 {
+            u64::MAX
+        } else {
+            (1u64 << count) - 1
+        };
+        self.write(value as u64 & mask, count);
+    }
+
+    fn write_unary(&mut self, zeros: u32) {
+        for _ in 0..zeros {
+            self.write(0, 1);
+        }
+        self.write(1, 1);
+    }
+
+    fn pad_to_byte(&mut self) {
+        if self.bits > 0 {
+            let remaining = 8 - self.bits;
+            self.write(0, remaining);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.pad_to_byte();
+        self.bytes
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// FLAC's UTF-8-like variable length encoding of the frame number
+fn write_utf8_number(writer: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        writer.write(value, 8);
+        return;
+    }
+    let continuation_bytes = match value {
+        v if v < 0x800 => 1,
+        v if v < 0x1_0000 => 2,
+        v if v < 0x20_0000 => 3,
+        v if v < 0x400_0000 => 4,
+        v if v < 0x8000_0000 => 5,
+        _ => 6,
+    };
+    let lead_marker = (0xFF00u64 >> (continuation_bytes + 1)) & 0xFF;
+    writer.write(lead_marker | (value >> (6 * continuation_bytes)), 8);
+    for i in (0..continuation_bytes).rev() {
+        writer.write(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+/// Residuals of the fixed polynomial predictor of the given order
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i32> {
+    samples
+        .iter()
+        .enumerate()
+        .skip(order)
+        .map(|(i, &s)| {
+            let prediction = match order {
+                0 => 0,
+                1 => samples[i - 1],
+                2 => 2 * samples[i - 1] - samples[i - 2],
+                3 => 3 * samples[i - 1] - 3 * samples[i - 2] + samples[i - 3],
+                _ => 4 * samples[i - 1] - 6 * samples[i - 2] + 4 * samples[i - 3] - samples[i - 4],
+            };
+            s - prediction
+        })
+        .collect()
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// Pick the Rice parameter with the smallest encoded size; returns (param, bits)
+fn best_rice_param(residuals: &[i32]) -> (u32, u64) {
+    (0..=FLAC_MAX_RICE_PARAM)
+        .map(|k| {
+            let bits: u64 = residuals
+                .iter()
+                .map(|&r| (zigzag(r) >> k) as u64 + 1 + k as u64)
+                .sum();
+            (k, bits)
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, 0))
+}
+
+fn write_subframe(writer: &mut BitWriter, block: &[i32]) {
+    // Constant subframe: digital silence compresses to a single sample
+    if block.iter().all(|&s| s == block[0]) {
+        writer.write(0, 1);
+        writer.write(0b000000, 6);
+        writer.write(0, 1);
+        writer.write_signed(block[0] as i64, FLAC_BITS_PER_SAMPLE);
+        return;
+    }
+
+    let verbatim_bits = block.len() as u64 * FLAC_BITS_PER_SAMPLE as u64;
+    let best = (0..=FLAC_MAX_FIXED_ORDER.min(block.len().saturating_sub(1)))
+        .map(|order| {
+            let residuals = fixed_residuals(block, order);
+            let (param, bits) = best_rice_param(&residuals);
+            let total = bits + order as u64 * FLAC_BITS_PER_SAMPLE as u64 + 10;
+            (order, param, residuals, total)
+        })
+        .min_by_key(|(_, _, _, total)| *total);
+
+    match best {
+        Some((order, param, residuals, total)) if total < verbatim_bits => {
+            writer.write(0, 1);
+            writer.write(0b001000 | order as u64, 6);
+            writer.write(0, 1);
+            for &warmup in &block[..order] {
+                writer.write_signed(warmup as i64, FLAC_BITS_PER_SAMPLE);
+            }
+            // Rice coding with 4-bit parameters and a single partition
+            writer.write(0b00, 2);
+            writer.write(0, 4);
+            writer.write(param as u64, 4);
+            for &residual in &residuals {
+                let value = zigzag(residual);
+                writer.write_unary(value >> param);
+                writer.write((value & ((1u32 << param) - 1)) as u64, param);
+            }
+        }
+        _ => {
+            writer.write(0, 1);
+            writer.write(0b000001, 6);
+            writer.write(0, 1);
+            for &sample in block {
+                writer.write_signed(sample as i64, FLAC_BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+fn encode_flac_frame(block: &[i32], frame_number: u64) -> Vec<u8> {
+    let mut header = BitWriter::new();
+    header.write(0b11111111111110, 14); // sync code
+    header.write(0, 1); // reserved
+    header.write(0, 1); // fixed block size stream
+    header.write(0b0111, 4); // block size stored as 16-bit value after the header
+    header.write(0b0000, 4); // sample rate from STREAMINFO
+    header.write(0b0000, 4); // mono
+    header.write(0b100, 3); // 16 bits per sample
+    header.write(0, 1); // reserved
+    write_utf8_number(&mut header, frame_number);
+    header.write(block.len() as u64 - 1, 16);
+    let mut frame = header.into_bytes();
+    let header_crc = crc8(&frame);
+    frame.push(header_crc);
+
+    let mut body = BitWriter::new();
+    write_subframe(&mut body, block);
+    frame.extend(body.into_bytes());
+
+    let frame_crc = crc16(&frame);
+    frame.extend_from_slice(&frame_crc.to_be_bytes());
+    frame
+}
+
+/// Encode mono f32 samples as a 16-bit FLAC stream
+pub fn encode_flac(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    if samples.is_empty() {
+        return Err("No samples to encode".to_string());
+    }
+
+    let pcm: Vec<i32> = samples
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i32)
+        .collect();
+
+    let mut out = Vec::with_capacity(pcm.len());
+    out.extend_from_slice(b"fLaC");
+
+    // STREAMINFO metadata block (last metadata block, 34 bytes)
+    let mut info = BitWriter::new();
+    info.write(1, 1);
+    info.write(0, 7);
+    info.write(34, 24);
+    // Fixed-blocksize stream: the short final block is exempt from the minimum
+    info.write(FLAC_BLOCK_SIZE as u64, 16);
+    info.write(FLAC_BLOCK_SIZE as u64, 16);
+    info.write(0, 24); // min frame size unknown
+    info.write(0, 24); // max frame size unknown
+    info.write(sample_rate as u64, 20);
+    info.write(0, 3); // channels - 1
+    info.write((FLAC_BITS_PER_SAMPLE - 1) as u64, 5);
+    info.write(pcm.len() as u64, 36);
+    info.write(0, 64); // MD5 not computed
+    info.write(0, 64);
+    out.extend(info.into_bytes());
+
+    for (frame_number, block) in pcm.chunks(FLAC_BLOCK_SIZE).enumerate() {
+        out.extend(encode_flac_frame(block, frame_number as u64));
+    }
+
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Ogg/Opus
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "opus")]
+const OPUS_BITRATE: i32 = 24_000;
+#[cfg(feature = "opus")]
+const OPUS_MAX_PACKET: usize = 4000;
+#[cfg(feature = "opus")]
+const OGG_STREAM_SERIAL: u32 = 0x5454_4d45;
+
+#[cfg(not(feature = "opus"))]
+pub fn encode_opus(_samples: &[f32], _sample_rate: u32) -> Result<Vec<u8>, String> {
+    Err("Opus uploads are not available in this build (enable the `opus` feature)".to_string())
+}
+
+/// Encode mono f32 samples as an Ogg/Opus stream (20ms frames, VoIP tuning)
+#[cfg(feature = "opus")]
+pub fn encode_opus(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    use audiopus::coder::Encoder;
+    use audiopus::{Application, Bitrate, Channels, SampleRate};
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+    if samples.is_empty() {
+        return Err("No samples to encode".to_string());
+    }
+
+    let opus_rate = match sample_rate {
+        8000 => SampleRate::Hz8000,
+        12000 => SampleRate::Hz12000,
+        16000 => SampleRate::Hz16000,
+        24000 => SampleRate::Hz24000,
+        48000 => SampleRate::Hz48000,
+        other => return Err(format!("Unsupported Opus sample rate: {}Hz", other)),
+    };
+
+    let mut encoder = Encoder::new(opus_rate, Channels::Mono, Application::Voip)
+        .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(OPUS_BITRATE))
+        .map_err(|e| format!("Failed to set Opus bitrate: {}", e))?;
+    let lookahead = encoder.lookahead().unwrap_or(0) as usize;
+
+    // Ogg/Opus granule positions always count 48kHz samples
+    let granule_scale = (48_000 / sample_rate) as u64;
+    let pre_skip = lookahead as u64 * granule_scale;
+    let frame_len = (sample_rate / 50) as usize;
+
+    let mut writer = PacketWriter::new(Vec::new());
+    let write_err = |e: std::io::Error| format!("Failed to write Ogg page: {}", e);
+
+    // Identification header
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channel count
+    head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+    head.extend_from_slice(&sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+    writer
+        .write_packet(
+            head.into_boxed_slice(),
+            OGG_STREAM_SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )
+        .map_err(write_err)?;
+
+    // Comment header
+    let vendor = b"talktome";
+    let mut tags = Vec::new();
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    writer
+        .write_packet(
+            tags.into_boxed_slice(),
+            OGG_STREAM_SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )
+        .map_err(write_err)?;
+
+    // Pad with the encoder delay so the tail isn't cut off after pre-skip is applied
+    let mut padded = samples.to_vec();
+    padded.resize(samples.len() + lookahead, 0.0);
+    let total_frames = padded.len().div_ceil(frame_len);
+
+    let mut packet = vec![0u8; OPUS_MAX_PACKET];
+    for (i, chunk) in padded.chunks(frame_len).enumerate() {
+        let mut frame = chunk.to_vec();
+        frame.resize(frame_len, 0.0);
+        let len = encoder
+            .encode_float(&frame, &mut packet)
+            .map_err(|e| format!("Opus encoding failed: {}", e))?;
+
+        let is_last = i + 1 == total_frames;
+        let (end_info, granule) = if is_last {
+            // Final granule trims the zero padding from playback
+            (
+                PacketWriteEndInfo::EndStream,
+                pre_skip + samples.len() as u64 * granule_scale,
+            )
+        } else {
+            (
+                PacketWriteEndInfo::NormalPacket,
+                ((i + 1) * frame_len) as u64 * granule_scale,
+            )
+        };
+        writer
+            .write_packet(
+                packet[..len].to_vec().into_boxed_slice(),
+                OGG_STREAM_SERIAL,
+                end_info,
+                granule,
+            )
+            .map_err(write_err)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_format_from_setting() {
+        assert_eq!(UploadFormat::from_setting("flac"), UploadFormat::Flac);
+        let opus = if cfg!(feature = "opus") { UploadFormat::Opus } else { UploadFormat::Flac };
+        assert_eq!(UploadFormat::from_setting("opus"), opus);
+        assert_eq!(UploadFormat::from_setting("wav"), UploadFormat::Wav);
+        assert_eq!(UploadFormat::from_setting("unknown"), UploadFormat::Wav);
+    }
+
+    #[test]
+    fn test_flac_header() {
+        let samples: Vec<f32> = (0..10_000).map(|i| (i as f32 * 0.01).sin() * 0.5).collect();
+        let flac = encode_flac(&samples, 16000).unwrap();
+        assert_eq!(&flac[..4], b"fLaC");
+        // Smooth signals should compress well below PCM16 size
+        assert!(flac.len() < samples.len() * 2);
+    }
+
+    /// MSB-first reader for the test decoder
+    struct BitReader<'a> {
+        data: &'a [u8],
+        bit: usize,
+    }
+
+    impl BitReader<'_> {
+        fn read(&mut self, count: u32) -> u64 {
+            let mut value = 0;
+            for _ in 0..count {
+                let byte = self.data[self.bit / 8];
+                value = (value << 1) | ((byte >> (7 - self.bit % 8)) & 1) as u64;
+                self.bit += 1;
+            }
+            value
+        }
+
+        fn read_signed(&mut self, count: u32) -> i32 {
+            let value = self.read(count) as i64;
+            (if value >> (count - 1) & 1 == 1 { value - (1 << count) } else { value }) as i32
+        }
+
+        fn read_unary(&mut self) -> u32 {
+            let mut zeros = 0;
+            while self.read(1) == 0 {
+                zeros += 1;
+            }
+            zeros
+        }
+
+        fn byte_pos(&self) -> usize {
+            self.bit.div_ceil(8)
+        }
+    }
+
+    /// Decodes the subset of FLAC `encode_flac` writes (mono, 16-bit, constant/verbatim/fixed
+    /// subframes), checking both CRCs; returns (sample rate, samples)
+    fn decode_flac(flac: &[u8]) -> (u32, Vec<i32>) {
+        assert_eq!(&flac[..4], b"fLaC");
+        let mut reader = BitReader { data: flac, bit: 4 * 8 };
+        assert_eq!(reader.read(8), 0x80); // last metadata block, STREAMINFO
+        assert_eq!(reader.read(24), 34);
+        reader.read(16 + 16 + 24 + 24);
+        let sample_rate = reader.read(20) as u32;
+        assert_eq!(reader.read(3), 0);
+        assert_eq!(reader.read(5), 15);
+        let total = reader.read(36) as usize;
+        reader.read(128);
+
+        let mut samples = Vec::new();
+        let mut frame_number = 0;
+        while reader.byte_pos() < flac.len() {
+            let frame_start = reader.byte_pos();
+            assert_eq!(reader.read(14), 0b11111111111110);
+            // Fixed blocking, 16-bit block size, rate from STREAMINFO, mono, 16 bits per sample
+            let fields = [reader.read(2), reader.read(4), reader.read(4), reader.read(4), reader.read(3), reader.read(1)];
+            assert_eq!(fields, [0, 0b0111, 0, 0, 0b100, 0]);
+            let lead = reader.read(8);
+            let continuation = (lead as u8).leading_ones().saturating_sub(1);
+            let mut number = lead & (0x7F >> continuation);
+            for _ in 0..continuation {
+                number = (number << 6) | (reader.read(8) & 0x3F);
+            }
+            assert_eq!(number, frame_number);
+            let block_len = reader.read(16) as usize + 1;
+            assert_eq!(reader.read(8) as u8, crc8(&flac[frame_start..reader.byte_pos() - 1]));
+
+            assert_eq!(reader.read(1), 0);
+            let kind = reader.read(6);
+            assert_eq!(reader.read(1), 0);
+            let mut block = Vec::with_capacity(block_len);
+            match kind {
+                0 => block.resize(block_len, reader.read_signed(16)),
+                1 => (0..block_len).for_each(|_| block.push(reader.read_signed(16))),
+                k if k & 0b111000 == 0b001000 => {
+                    let order = (k & 0b111) as usize;
+                    (0..order).for_each(|_| block.push(reader.read_signed(16)));
+                    assert_eq!(reader.read(2 + 4), 0);
+                    let param = reader.read(4) as u32;
+                    for i in order..block_len {
+                        let value = ((reader.read_unary() << param) as u64 | reader.read(param)) as u32;
+                        let residual = ((value >> 1) as i32) ^ -((value & 1) as i32);
+                        let prediction = match order {
+                            0 => 0,
+                            1 => block[i - 1],
+                            2 => 2 * block[i - 1] - block[i - 2],
+                            3 => 3 * block[i - 1] - 3 * block[i - 2] + block[i - 3],
+                            _ => 4 * block[i - 1] - 6 * block[i - 2] + 4 * block[i - 3] - block[i - 4],
+                        };
+                        block.push(prediction + residual);
+                    }
+                }
+                other => panic!("unexpected subframe type {:#b}", other),
+            }
+            let crc_pos = reader.byte_pos();
+            reader.bit = crc_pos * 8;
+            assert_eq!(reader.read(16) as u16, crc16(&flac[frame_start..crc_pos]));
+            samples.extend(block);
+            frame_number += 1;
+        }
+        assert_eq!(samples.len(), total);
+        (sample_rate, samples)
+    }
+
+    #[test]
+    fn test_flac_round_trip() {
+        // Smooth speech-like signal, digital silence and white noise, so the fixed, constant and
+        // verbatim subframes are all used; the length leaves a short final block
+        let mut seed = 12345u32;
+        let samples: Vec<f32> = (0..6000)
+            .map(|i| (i as f32 * 0.02).sin() * 0.6)
+            .chain(std::iter::repeat_n(0.0, FLAC_BLOCK_SIZE))
+            .chain((0..FLAC_BLOCK_SIZE + 123).map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (seed >> 16) as f32 / 32768.0 - 1.0
+            }))
+            .collect();
+        let flac = encode_flac(&samples, 16000).unwrap();
+        let (sample_rate, decoded) = decode_flac(&flac);
+        assert_eq!(sample_rate, 16000);
+        assert_eq!(decoded.len(), samples.len());
+        let expected: Vec<i32> = samples.iter().map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i32).collect();
+        assert_eq!(decoded, expected);
+
+        let (sample_rate, decoded) = decode_flac(&encode_flac(&samples[..100], 44100).unwrap());
+        assert_eq!((sample_rate, decoded.len()), (44100, 100));
+    }
+
+    #[cfg(feature = "opus")]
+    #[test]
+    fn test_opus_round_trip() {
+        use audiopus::coder::Decoder;
+        use audiopus::{Channels, SampleRate};
+        use ogg::reading::PacketReader;
+
+        let samples: Vec<f32> = (0..16000 + 123).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let opus = encode_opus(&samples, 16000).unwrap();
+        let mut reader = PacketReader::new(std::io::Cursor::new(opus));
+
+        let head = reader.read_packet_expected().unwrap();
+        assert_eq!(&head.data[..8], b"OpusHead");
+        let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+        assert_eq!(u32::from_le_bytes(head.data[12..16].try_into().unwrap()), 16000);
+        let tags = reader.read_packet_expected().unwrap();
+        assert_eq!(&tags.data[..8], b"OpusTags");
+
+        let mut decoder = Decoder::new(SampleRate::Hz16000, Channels::Mono).unwrap();
+        let mut output = vec![0f32; 16000 / 50];
+        let mut decoded = 0;
+        let mut last_granule = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            decoded += decoder.decode_float(Some(&packet.data[..]), &mut output[..], false).unwrap();
+            last_granule = packet.absgp_page();
+        }
+        // Granules count 48kHz samples; the final one marks the end of the real audio
+        assert_eq!((last_granule - pre_skip) / 3, samples.len() as u64);
+        assert!(decoded as u64 - pre_skip / 3 >= samples.len() as u64);
+    }
+}
@@ -1,12 +1,55 @@
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 
 // Global state for debug logging
 static DEBUG_ENABLED: Mutex<bool> = Mutex::new(false);
 static LOG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+// Size at which talktome.log is rotated (configurable via max_log_mb)
+static MAX_LOG_BYTES: AtomicU64 = AtomicU64::new(10 * 1024 * 1024);
+// Number of rotated logs kept (talktome.log.1 .. talktome.log.N)
+const ROTATED_LOG_KEEP: usize = 3;
+// Number of most recent WAV dumps kept in the logs directory (configurable via wav_dump_retention)
+static WAV_DUMP_KEEP: AtomicUsize = AtomicUsize::new(20);
+// Write one JSON object per line instead of free text (log_format = "json")
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+// Entries less severe than this are dropped (log_level setting)
+static MIN_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// Severity of a log entry, most severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+    Trace = 4,
+}
+
+impl LogLevel {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "error" => LogLevel::Error,
+            "warn" | "warning" => LogLevel::Warn,
+            "debug" => LogLevel::Debug,
+            "trace" => LogLevel::Trace,
+            _ => LogLevel::Info,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
 
 pub struct DebugLogger;
 
@@ -113,6 +156,7 @@ impl DebugLogger {
                     bytes.len(),
                     out_path.display()
                 ));
+                Self::prune_wav_dumps(&logs_dir);
                 Some(out_path)
             }
             Err(e) => {
@@ -122,6 +166,85 @@ impl DebugLogger {
         }
     }
 
+    /// Delete the oldest WAV dumps beyond the retention count
+    fn prune_wav_dumps(logs_dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(logs_dir) else {
+            return;
+        };
+        let mut dumps: Vec<(std::time::SystemTime, PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+            .filter_map(|path| {
+                let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                Some((modified, path))
+            })
+            .collect();
+
+        let keep = WAV_DUMP_KEEP.load(Ordering::Relaxed);
+        if dumps.len() <= keep {
+            return;
+        }
+        // Newest first; everything after the retention count goes
+        dumps.sort_by(|a, b| b.0.cmp(&a.0));
+        for (_, path) in dumps.into_iter().skip(keep) {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    /// Set how many WAV dumps are kept; applied after the next dump is written
+    pub fn set_wav_dump_retention(keep: u32) {
+        WAV_DUMP_KEEP.store(keep.max(1) as usize, Ordering::Relaxed);
+    }
+
+    /// Directory holding the log file and WAV dumps
+    pub fn dump_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+        let log_path = Self::get_log_path(app_handle)?;
+        log_path
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| "Log path has no parent directory".to_string())
+    }
+
+    /// Delete every WAV dump; returns how many were removed
+    pub fn clear_wav_dumps(app_handle: &AppHandle) -> Result<usize, String> {
+        let Ok(entries) = std::fs::read_dir(Self::dump_dir(app_handle)?) else {
+            return Ok(0);
+        };
+        let mut removed = 0;
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "wav") {
+                std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Set the size cap (in MB) at which the log file is rotated
+    pub fn set_max_log_mb(max_log_mb: u32) {
+        MAX_LOG_BYTES.store(max_log_mb.max(1) as u64 * 1024 * 1024, Ordering::Relaxed);
+    }
+
+    /// Shift talktome.log -> talktome.log.1 -> ... -> talktome.log.N once it exceeds the size cap
+    fn rotate_if_needed(log_path: &Path) {
+        let size = std::fs::metadata(log_path).map(|m| m.len()).unwrap_or(0);
+        if size < MAX_LOG_BYTES.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let rotated = |index: usize| {
+            let mut name = log_path.as_os_str().to_os_string();
+            name.push(format!(".{}", index));
+            PathBuf::from(name)
+        };
+        let _ = std::fs::remove_file(rotated(ROTATED_LOG_KEEP));
+        for index in (1..ROTATED_LOG_KEEP).rev() {
+            let _ = std::fs::rename(rotated(index), rotated(index + 1));
+        }
+        let _ = std::fs::rename(log_path, rotated(1));
+    }
+
     /// Initialize debug logging with explicit state
     pub fn init_with_state(app_handle: &AppHandle, enabled: bool) -> Result<(), String> {
         // Update global state
@@ -156,8 +279,39 @@ impl DebugLogger {
         Ok(())
     }
 
-    /// Write a message directly to the log file
+    /// Select the on-disk format: "text" (default) or "json" for JSON lines
+    pub fn set_log_format(format: &str) {
+        JSON_FORMAT.store(format.trim().eq_ignore_ascii_case("json"), Ordering::Relaxed);
+    }
+
+    fn is_json_format() -> bool {
+        JSON_FORMAT.load(Ordering::Relaxed)
+    }
+
+    /// Set the minimum severity that gets written ("error" .. "trace")
+    pub fn set_min_level(level: LogLevel) {
+        MIN_LEVEL.store(level as u8, Ordering::Relaxed);
+    }
+
+    fn level_enabled(level: LogLevel) -> bool {
+        level as u8 <= MIN_LEVEL.load(Ordering::Relaxed)
+    }
+
+    /// Write a plain message directly to the log file
     fn write_log(message: &str) {
+        Self::write_log_at(LogLevel::Info, message);
+    }
+
+    fn write_log_at(level: LogLevel, message: &str) {
+        Self::write_entry(level, "general", message, Value::Null);
+    }
+
+    /// Write one log entry; in JSON mode `stage` and `fields` are kept as structured data
+    fn write_entry(level: LogLevel, stage: &str, message: &str, fields: Value) {
+        if !Self::level_enabled(level) {
+            return;
+        }
+
         // Check if logging is enabled
         let enabled = if let Ok(enabled) = DEBUG_ENABLED.lock() {
             *enabled
@@ -180,9 +334,9 @@ impl DebugLogger {
             return;
         };
 
-        // Format message with timestamp
-        let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f UTC");
-        let formatted_message = format!("[{}] {}\n", timestamp, message);
+        let formatted_message = format!("{}\n", format_line(Self::is_json_format(), level, stage, message, fields));
+
+        Self::rotate_if_needed(&log_path);
 
         // Write to file
         if let Ok(mut file) = std::fs::OpenOptions::new()
@@ -202,18 +356,43 @@ impl DebugLogger {
         has_activity: bool,
         max_amplitude: f32,
     ) {
-        Self::write_log(&format!(
+        if Self::is_json_format() {
+            Self::write_entry(
+                LogLevel::Debug,
+                "audio",
+                "Audio chunk processed",
+                json!({
+                    "data_len": data_len,
+                    "sample_rate": sample_rate,
+                    "has_activity": has_activity,
+                    "max_amplitude": max_amplitude,
+                }),
+            );
+            return;
+        }
+
+        Self::write_log_at(LogLevel::Debug, &format!(
             "AUDIO_CHUNK: length={} samples, rate={}Hz, has_activity={}, max_amplitude={:.6}",
             data_len, sample_rate, has_activity, max_amplitude
         ));
 
         if !has_activity {
-            Self::write_log("AUDIO_CHUNK: Skipping silent chunk (max_amplitude < 0.01)");
+            Self::write_log_at(LogLevel::Debug, "AUDIO_CHUNK: Skipping silent chunk (max_amplitude < 0.01)");
         }
     }
 
     /// Log transcription request details
     pub fn log_transcription_request(audio_size: usize, endpoint: &str) {
+        if Self::is_json_format() {
+            Self::write_entry(
+                LogLevel::Info,
+                "stt",
+                "Sending audio to transcription API",
+                json!({ "audio_size": audio_size, "endpoint": endpoint }),
+            );
+            return;
+        }
+
         Self::write_log(&format!("STT_REQUEST: Sending audio to Whisper API"));
         Self::write_log(&format!(
             "STT_REQUEST: audio_size={} bytes, endpoint={}",
@@ -223,6 +402,21 @@ impl DebugLogger {
 
     /// Log transcription response
     pub fn log_transcription_response(success: bool, text: Option<&str>, error: Option<&str>) {
+        if Self::is_json_format() {
+            Self::write_entry(
+                if success { LogLevel::Info } else { LogLevel::Error },
+                "stt",
+                "Transcription response",
+                json!({
+                    "success": success,
+                    "text": text,
+                    "transcript_length": text.map(str::len),
+                    "error": error,
+                }),
+            );
+            return;
+        }
+
         if success {
             if let Some(text) = text {
                 Self::write_log(&format!("STT_RESPONSE: SUCCESS - '{}'", text));
@@ -233,7 +427,7 @@ impl DebugLogger {
             }
         } else {
             if let Some(error) = error {
-                Self::write_log(&format!("STT_RESPONSE: ERROR - {}", error));
+                Self::write_log_at(LogLevel::Error, &format!("STT_RESPONSE: ERROR - {}", error));
             }
         }
     }
@@ -246,6 +440,22 @@ impl DebugLogger {
         translation_enabled: bool,
         prompt: &str,
     ) {
+        if Self::is_json_format() {
+            Self::write_entry(
+                LogLevel::Info,
+                "translation",
+                "Processing text",
+                json!({
+                    "original": original_text,
+                    "source_lang": source_lang,
+                    "target_lang": target_lang,
+                    "enabled": translation_enabled,
+                    "prompt": prompt,
+                }),
+            );
+            return;
+        }
+
         Self::write_log(&format!("TRANSLATION_REQUEST: Processing text"));
         Self::write_log(&format!(
             "TRANSLATION_REQUEST: original='{}', source_lang={}, target_lang={}, enabled={}",
@@ -256,8 +466,22 @@ impl DebugLogger {
 
     /// Log translation API request payload
     pub fn log_api_payload(payload: &Value, endpoint: &str) {
+        if Self::is_json_format() {
+            Self::write_entry(
+                LogLevel::Debug,
+                "api",
+                "Sending API request",
+                json!({
+                    "endpoint": endpoint,
+                    "model": payload["model"],
+                    "payload": payload,
+                }),
+            );
+            return;
+        }
+
         Self::write_log(&format!("API_REQUEST: Sending request to {}", endpoint));
-        Self::write_log(&format!(
+        Self::write_log_at(LogLevel::Debug, &format!(
             "API_REQUEST: Full payload: {}",
             serde_json::to_string_pretty(payload).unwrap_or_default()
         ));
@@ -293,6 +517,22 @@ impl DebugLogger {
         error: Option<&str>,
         raw_response: Option<&str>,
     ) {
+        if Self::is_json_format() {
+            Self::write_entry(
+                if success { LogLevel::Info } else { LogLevel::Error },
+                "translation",
+                "Translation response",
+                json!({
+                    "success": success,
+                    "processed_text": processed_text,
+                    "processed_length": processed_text.map(str::len),
+                    "error": error,
+                    "raw_response": raw_response,
+                }),
+            );
+            return;
+        }
+
         if success {
             if let Some(text) = processed_text {
                 Self::write_log(&format!("TRANSLATION_RESPONSE: SUCCESS - '{}'", text));
@@ -302,7 +542,7 @@ impl DebugLogger {
                 ));
             }
         } else {
-            Self::write_log(&format!(
+            Self::write_log_at(LogLevel::Error, &format!(
                 "TRANSLATION_RESPONSE: ERROR - {}",
                 error.unwrap_or("Unknown error")
             ));
@@ -315,12 +555,27 @@ impl DebugLogger {
 
     /// Log text insertion
     pub fn log_text_insertion(text: &str, success: bool, error: Option<&str>) {
+        if Self::is_json_format() {
+            Self::write_entry(
+                if success { LogLevel::Info } else { LogLevel::Error },
+                "text_insertion",
+                "Inserting text",
+                json!({
+                    "text": text,
+                    "text_length": text.len(),
+                    "success": success,
+                    "error": error,
+                }),
+            );
+            return;
+        }
+
         Self::write_log(&format!("TEXT_INSERTION: Inserting text: '{}'", text));
 
         if success {
             Self::write_log("TEXT_INSERTION: SUCCESS");
         } else {
-            Self::write_log(&format!(
+            Self::write_log_at(LogLevel::Error, &format!(
                 "TEXT_INSERTION: ERROR - {}",
                 error.unwrap_or("Unknown error")
             ));
@@ -329,7 +584,12 @@ impl DebugLogger {
 
     /// Log pipeline errors
     pub fn log_pipeline_error(stage: &str, error: &str) {
-        Self::write_log(&format!(
+        if Self::is_json_format() {
+            Self::write_entry(LogLevel::Error, stage, error, Value::Null);
+            return;
+        }
+
+        Self::write_log_at(LogLevel::Error, &format!(
             "PIPELINE_ERROR: Stage '{}' failed: {}",
             stage, error
         ));
@@ -340,6 +600,21 @@ impl DebugLogger {
         Self::write_log(message);
     }
 
+    /// Log a recoverable problem
+    pub fn log_warn(message: &str) {
+        Self::write_log_at(LogLevel::Warn, message);
+    }
+
+    /// Log diagnostic detail that is noisy in normal operation
+    pub fn log_debug(message: &str) {
+        Self::write_log_at(LogLevel::Debug, message);
+    }
+
+    /// Log per-frame/per-buffer detail
+    pub fn log_trace(message: &str) {
+        Self::write_log_at(LogLevel::Trace, message);
+    }
+
     /// Get log file path
     fn get_log_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
         let data_dir = Self::get_portable_data_dir(app_handle)?;
@@ -400,6 +675,26 @@ impl DebugLogger {
         Ok(())
     }
 
+    /// Delete the log file, its rotated copies and all WAV dumps; returns (logs, dumps) removed
+    pub fn purge_files(app_handle: &AppHandle) -> Result<(usize, usize), String> {
+        let dumps = Self::clear_wav_dumps(app_handle)?;
+        let Ok(entries) = std::fs::read_dir(Self::dump_dir(app_handle)?) else {
+            return Ok((0, dumps));
+        };
+        let mut logs = 0;
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let is_log = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("talktome.log"));
+            if is_log {
+                std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                logs += 1;
+            }
+        }
+        Ok((logs, dumps))
+    }
+
     /// Check if debug logging is currently enabled
     pub fn is_debug_enabled() -> bool {
         if let Ok(enabled) = DEBUG_ENABLED.lock() {
@@ -409,3 +704,168 @@ impl DebugLogger {
         }
     }
 }
+
+/// One log line with timestamp. Secrets are masked in the message and field values before anything
+/// is serialized, so redaction can't cut into JSON escapes.
+fn format_line(json_format: bool, level: LogLevel, stage: &str, message: &str, fields: Value) -> String {
+    let message = redact_secrets(message);
+    if json_format {
+        json!({
+            "ts": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "level": level.as_str(),
+            "stage": stage,
+            "message": message,
+            "fields": redact_json(fields),
+        })
+        .to_string()
+    } else {
+        let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f UTC");
+        format!("[{}] {}", timestamp, message)
+    }
+}
+
+fn redact_json(value: Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(redact_secrets(&text)),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact_json).collect()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    // The value of a secret header needs no marker inside it to be masked
+                    let secret = ["api-key", "authorization", "proxy-authorization"]
+                        .iter()
+                        .any(|name| key.eq_ignore_ascii_case(name));
+                    let value = if secret && value.is_string() { Value::from(REDACTED) } else { redact_json(value) };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+const REDACTED: &str = "[REDACTED]";
+// OpenAI-style keys are long; shorter sk- words (e.g. "sk-learn") are left alone
+const MIN_SK_KEY_LEN: usize = 20;
+
+/// Mask bearer tokens, `sk-...` API keys and `api-key` header values
+fn redact_secrets(message: &str) -> String {
+    let redacted = redact_value_after(message, "Bearer", false, |c| c.is_whitespace());
+    let redacted = redact_value_after(&redacted, "api-key", true, |c| {
+        c.is_whitespace() || matches!(c, ':' | '=' | '"' | '\'')
+    });
+    redact_sk_keys(&redacted)
+}
+
+fn is_value_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '\'' | ',' | '}' | ')')
+}
+
+/// Replace the value that follows `marker` (after at least one separator char)
+fn redact_value_after(
+    text: &str,
+    marker: &str,
+    case_insensitive: bool,
+    is_separator: impl Fn(char) -> bool,
+) -> String {
+    // ASCII lowercasing keeps byte offsets identical to `text`
+    let haystack = if case_insensitive {
+        text.to_ascii_lowercase()
+    } else {
+        text.to_string()
+    };
+    let needle = if case_insensitive {
+        marker.to_ascii_lowercase()
+    } else {
+        marker.to_string()
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(found) = haystack[pos..].find(&needle) {
+        let marker_end = pos + found + needle.len();
+        let value_start = text[marker_end..]
+            .char_indices()
+            .find(|&(_, c)| !is_separator(c))
+            .map_or(text.len(), |(i, _)| marker_end + i);
+        let value_end = text[value_start..]
+            .char_indices()
+            .find(|&(_, c)| is_value_end(c))
+            .map_or(text.len(), |(i, _)| value_start + i);
+
+        out.push_str(&text[pos..value_start]);
+        if value_start > marker_end && value_end > value_start {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(&text[value_start..value_end]);
+        }
+        pos = value_end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+fn redact_sk_keys(text: &str) -> String {
+    let is_key_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(found) = text[pos..].find("sk-") {
+        let start = pos + found;
+        let at_boundary = !matches!(text[..start].chars().next_back(), Some(c) if is_key_char(c));
+        let end = text[start..]
+            .char_indices()
+            .find(|&(_, c)| !is_key_char(c))
+            .map_or(text.len(), |(i, _)| start + i);
+
+        out.push_str(&text[pos..start]);
+        if at_boundary && end - start >= MIN_SK_KEY_LEN {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(&text[start..end]);
+        }
+        pos = end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_bearer_and_sk_keys() {
+        let line = r#"headers: {"authorization": "Bearer abc.def-123", "x": "y"}"#;
+        assert_eq!(
+            redact_secrets(line),
+            r#"headers: {"authorization": "Bearer [REDACTED]", "x": "y"}"#
+        );
+        assert_eq!(
+            redact_secrets("key=sk-proj-ABCDEFGHIJKLMNOPQRSTUVWX end"),
+            "key=[REDACTED] end"
+        );
+        assert_eq!(redact_secrets("uses sk-learn and task-abc"), "uses sk-learn and task-abc");
+    }
+
+    #[test]
+    fn test_redacts_api_key_header() {
+        assert_eq!(
+            redact_secrets(r#"{"api-key": "0123456789abcdef"}"#),
+            r#"{"api-key": "[REDACTED]"}"#
+        );
+        assert_eq!(redact_secrets("Api-Key: secret42 next"), "Api-Key: [REDACTED] next");
+        assert_eq!(redact_secrets("no secrets here"), "no secrets here");
+    }
+
+    #[test]
+    fn test_json_line_stays_valid_after_redaction() {
+        let message = r#"request {"api-key": "abc\"def", "auth": "Bearer tok\"en"}"#;
+        let fields = json!({ "headers": { "api-key": "0123456789abcdef" }, "n": 3 });
+        let line = format_line(true, LogLevel::Info, "stt", message, fields);
+        let parsed: Value = serde_json::from_str(&line).expect("log line must stay valid JSON");
+        let logged = parsed["message"].as_str().unwrap();
+        assert!(!logged.contains("abc") && !logged.contains("tok"));
+        assert_eq!(parsed["fields"]["n"], 3);
+        assert_eq!(parsed["fields"]["headers"]["api-key"], REDACTED);
+    }
+}
@@ -0,0 +1,66 @@
+// Typed errors returned by Tauri commands.
+// Serialized as `{ kind, message }` so the frontend can branch on `kind` instead of string-matching.
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TalkToMeError {
+    #[error("{0}")]
+    InvalidApiKey(String),
+    #[error("{0}")]
+    MissingApiKey(String),
+    #[error("{0}")]
+    InvalidApiEndpoint(String),
+    #[error("{0}")]
+    NetworkError(String),
+    #[error("{0}")]
+    Timeout(String),
+    #[error("{0}")]
+    ApiError(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    AlreadyRecording(String),
+    #[error("{0}")]
+    AudioError(String),
+    #[error("{0}")]
+    MicPermissionDenied(String),
+    #[error("{0}")]
+    StorageError(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl TalkToMeError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TalkToMeError::InvalidApiKey(_) => "InvalidApiKey",
+            TalkToMeError::MissingApiKey(_) => "MissingApiKey",
+            TalkToMeError::InvalidApiEndpoint(_) => "InvalidApiEndpoint",
+            TalkToMeError::NetworkError(_) => "NetworkError",
+            TalkToMeError::Timeout(_) => "Timeout",
+            TalkToMeError::ApiError(_) => "ApiError",
+            TalkToMeError::InvalidInput(_) => "InvalidInput",
+            TalkToMeError::AlreadyRecording(_) => "AlreadyRecording",
+            TalkToMeError::AudioError(_) => "AudioError",
+            TalkToMeError::MicPermissionDenied(_) => "MicPermissionDenied",
+            TalkToMeError::StorageError(_) => "StorageError",
+            TalkToMeError::Internal(_) => "Internal",
+        }
+    }
+}
+
+// Existing helpers still return String errors; `?` on them lands here
+impl From<String> for TalkToMeError {
+    fn from(message: String) -> Self {
+        TalkToMeError::Internal(message)
+    }
+}
+
+impl Serialize for TalkToMeError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TalkToMeError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -5,12 +6,45 @@ use std::time::{Duration, Instant};
 pub enum RecordingState {
     Idle,
     Recording,
+    /// Capture stopped, transcription/insertion still running; presses are ignored
+    Processing,
+}
+
+/// How the hands-free hotkey drives recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyMode {
+    /// Press once to start, press again to stop
+    Toggle,
+    /// Record while the key is held, stop on release
+    PushToTalk,
+}
+
+impl HotkeyMode {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "push_to_talk" | "pushtotalk" | "ptt" => HotkeyMode::PushToTalk,
+            _ => HotkeyMode::Toggle,
+        }
+    }
+}
+
+/// What a push-to-talk key event should do to the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PttAction {
+    Start,
+    Stop,
+    /// Released within the tap threshold: keep recording until the next tap
+    Latch,
+    Ignore,
 }
 
 pub struct HotkeySM {
     state: Arc<Mutex<RecordingState>>,
     last_toggle_time: Arc<Mutex<Option<Instant>>>,
-    debounce_ms: u64,
+    debounce_ms: AtomicU64,
+    tap_threshold_ms: AtomicU64,
+    pressed_at: Mutex<Option<Instant>>,
+    latched: AtomicBool,
 }
 
 impl HotkeySM {
@@ -18,10 +52,31 @@ impl HotkeySM {
         Self {
             state: Arc::new(Mutex::new(RecordingState::Idle)),
             last_toggle_time: Arc::new(Mutex::new(None)),
-            debounce_ms,
+            debounce_ms: AtomicU64::new(debounce_ms),
+            tap_threshold_ms: AtomicU64::new(0),
+            pressed_at: Mutex::new(None),
+            latched: AtomicBool::new(false),
         }
     }
 
+    pub fn debounce_ms(&self) -> u64 {
+        self.debounce_ms.load(Ordering::Relaxed)
+    }
+
+    /// Change the debounce interval; takes effect on the next key press
+    pub fn set_debounce_ms(&self, debounce_ms: u64) {
+        self.debounce_ms.store(debounce_ms, Ordering::Relaxed);
+    }
+
+    pub fn tap_threshold_ms(&self) -> u64 {
+        self.tap_threshold_ms.load(Ordering::Relaxed)
+    }
+
+    /// Push-to-talk presses released within this many ms latch the recording on; 0 disables
+    pub fn set_tap_threshold_ms(&self, tap_threshold_ms: u64) {
+        self.tap_threshold_ms.store(tap_threshold_ms, Ordering::Relaxed);
+    }
+
     pub fn get_state(&self) -> Result<RecordingState, String> {
         self.state
             .lock()
@@ -34,18 +89,19 @@ impl HotkeySM {
         let now = Instant::now();
 
         if let Some(last_instant) = *last_time {
-            if now.duration_since(last_instant) < Duration::from_millis(self.debounce_ms) {
+            if now.duration_since(last_instant) < Duration::from_millis(self.debounce_ms()) {
                 return Ok(None);
             }
         }
 
-        *last_time = Some(now);
         let new_state = {
             let mut state = self.state.lock().map_err(|e| e.to_string())?;
             let new = match *state {
                 RecordingState::Idle => RecordingState::Recording,
-                RecordingState::Recording => RecordingState::Idle,
+                RecordingState::Recording => RecordingState::Processing,
+                RecordingState::Processing => return Ok(None),
             };
+            *last_time = Some(now);
             *state = new;
             new
         };
@@ -53,9 +109,96 @@ impl HotkeySM {
         Ok(Some(new_state))
     }
 
+    /// Push-to-talk press: debounced transition from Idle to Recording.
+    /// Returns false when already recording (key auto-repeat) or debounced.
+    pub fn try_start(&self) -> Result<bool, String> {
+        let mut last_time = self.last_toggle_time.lock().map_err(|e| e.to_string())?;
+        let now = Instant::now();
+
+        if let Some(last_instant) = *last_time {
+            if now.duration_since(last_instant) < Duration::from_millis(self.debounce_ms()) {
+                return Ok(false);
+            }
+        }
+
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        if *state != RecordingState::Idle {
+            return Ok(false);
+        }
+        *state = RecordingState::Recording;
+        *last_time = Some(now);
+        Ok(true)
+    }
+
+    /// Push-to-talk release: transition from Recording to Processing, never debounced
+    /// so a quick tap can't leave the recording running.
+    pub fn release(&self) -> Result<bool, String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        if *state != RecordingState::Recording {
+            return Ok(false);
+        }
+        *state = RecordingState::Processing;
+        Ok(true)
+    }
+
+    /// Push-to-talk press: starts a recording, or stops a latched one (not debounced, like a release)
+    pub fn ptt_press(&self) -> Result<PttAction, String> {
+        {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            if self.latched.load(Ordering::Relaxed) && *state == RecordingState::Recording {
+                self.latched.store(false, Ordering::Relaxed);
+                *state = RecordingState::Processing;
+                return Ok(PttAction::Stop);
+            }
+        }
+        if !self.try_start()? {
+            return Ok(PttAction::Ignore);
+        }
+        *self.pressed_at.lock().map_err(|e| e.to_string())? = Some(Instant::now());
+        Ok(PttAction::Start)
+    }
+
+    /// Push-to-talk release: stops a held recording, or latches it when the press was a quick tap
+    pub fn ptt_release(&self) -> Result<PttAction, String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        if *state != RecordingState::Recording || self.latched.load(Ordering::Relaxed) {
+            return Ok(PttAction::Ignore);
+        }
+        let held = self.pressed_at.lock().map_err(|e| e.to_string())?.take().map(|at| at.elapsed());
+        let threshold = Duration::from_millis(self.tap_threshold_ms());
+        if held.is_some_and(|held| held < threshold) {
+            self.latched.store(true, Ordering::Relaxed);
+            return Ok(PttAction::Latch);
+        }
+        *state = RecordingState::Processing;
+        Ok(PttAction::Stop)
+    }
+
+    /// Pipeline finished: Processing back to Idle so the next press can start a recording.
+    /// A newer session that is already Recording is left alone.
+    pub fn finish_processing(&self) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        if *state == RecordingState::Processing {
+            *state = RecordingState::Idle;
+            self.latched.store(false, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// The recording a press asked for never started (start_recording failed): Recording back to Idle
+    pub fn abandon_start(&self) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        if *state == RecordingState::Recording {
+            *state = RecordingState::Idle;
+            self.latched.store(false, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
     pub fn force_set_state(&self, state: RecordingState) -> Result<(), String> {
         let mut state_guard = self.state.lock().map_err(|e| e.to_string())?;
         *state_guard = state;
+        self.latched.store(false, Ordering::Relaxed);
         Ok(())
     }
 
@@ -84,11 +227,54 @@ mod tests {
     }
 
     #[test]
-    fn test_toggle_from_recording_to_idle() {
+    fn test_toggle_from_recording_to_processing() {
         let sm = HotkeySM::new(150);
         sm.try_toggle().unwrap();
+        sm.reset_debounce().unwrap();
         let new_state = sm.try_toggle().unwrap().unwrap();
-        assert_eq!(new_state, RecordingState::Idle);
+        assert_eq!(new_state, RecordingState::Processing);
+    }
+
+    #[test]
+    fn test_presses_ignored_while_processing() {
+        let sm = HotkeySM::new(0);
+        sm.force_set_state(RecordingState::Processing).unwrap();
+        assert_eq!(sm.try_toggle().unwrap(), None);
+        assert!(!sm.try_start().unwrap());
+        sm.finish_processing().unwrap();
+        assert_eq!(sm.try_toggle().unwrap(), Some(RecordingState::Recording));
+    }
+
+    #[test]
+    fn test_finish_processing_keeps_newer_recording() {
+        let sm = HotkeySM::new(0);
+        sm.force_set_state(RecordingState::Recording).unwrap();
+        sm.finish_processing().unwrap();
+        assert_eq!(sm.get_state().unwrap(), RecordingState::Recording);
+    }
+
+    #[test]
+    fn test_failed_start_returns_to_idle() {
+        let sm = HotkeySM::new(0);
+        assert_eq!(sm.try_toggle().unwrap(), Some(RecordingState::Recording));
+        // start_recording failed, so the press must not leave the hotkey stuck in Recording
+        sm.abandon_start().unwrap();
+        assert_eq!(sm.get_state().unwrap(), RecordingState::Idle);
+        assert_eq!(sm.try_toggle().unwrap(), Some(RecordingState::Recording));
+        // A pipeline still processing isn't affected
+        sm.force_set_state(RecordingState::Processing).unwrap();
+        sm.abandon_start().unwrap();
+        assert_eq!(sm.get_state().unwrap(), RecordingState::Processing);
+    }
+
+    #[test]
+    fn test_stop_with_nothing_recording_returns_to_idle() {
+        let sm = HotkeySM::new(0);
+        sm.force_set_state(RecordingState::Recording).unwrap();
+        // The press meant as "stop" moves to Processing; no pipeline runs to finish it
+        assert_eq!(sm.try_toggle().unwrap(), Some(RecordingState::Processing));
+        sm.finish_processing().unwrap();
+        assert_eq!(sm.try_toggle().unwrap(), Some(RecordingState::Recording));
     }
 
     #[test]
@@ -105,7 +291,7 @@ mod tests {
         sm.try_toggle().unwrap();
         std::thread::sleep(Duration::from_millis(60));
         let result = sm.try_toggle().unwrap().unwrap();
-        assert_eq!(result, RecordingState::Idle);
+        assert_eq!(result, RecordingState::Processing);
     }
 
     #[test]
@@ -115,6 +301,70 @@ mod tests {
         assert_eq!(sm.get_state().unwrap(), RecordingState::Recording);
     }
 
+    #[test]
+    fn test_push_to_talk_press_and_release() {
+        let sm = HotkeySM::new(150);
+        assert!(sm.try_start().unwrap());
+        assert_eq!(sm.get_state().unwrap(), RecordingState::Recording);
+        // Release right after press must not be debounced
+        assert!(sm.release().unwrap());
+        assert_eq!(sm.get_state().unwrap(), RecordingState::Processing);
+    }
+
+    #[test]
+    fn test_push_to_talk_ignores_repeat_press() {
+        let sm = HotkeySM::new(0);
+        assert!(sm.try_start().unwrap());
+        assert!(!sm.try_start().unwrap());
+        assert!(sm.release().unwrap());
+        assert!(!sm.release().unwrap());
+        sm.finish_processing().unwrap();
+        assert!(sm.try_start().unwrap());
+    }
+
+    #[test]
+    fn test_push_to_talk_tap_latches() {
+        let sm = HotkeySM::new(0);
+        sm.set_tap_threshold_ms(10000);
+        assert_eq!(sm.ptt_press().unwrap(), PttAction::Start);
+        assert_eq!(sm.ptt_release().unwrap(), PttAction::Latch);
+        assert_eq!(sm.get_state().unwrap(), RecordingState::Recording);
+        // The next tap stops; its release is ignored
+        assert_eq!(sm.ptt_press().unwrap(), PttAction::Stop);
+        assert_eq!(sm.ptt_release().unwrap(), PttAction::Ignore);
+        assert_eq!(sm.get_state().unwrap(), RecordingState::Processing);
+    }
+
+    #[test]
+    fn test_push_to_talk_hold_stops_on_release() {
+        let sm = HotkeySM::new(0);
+        sm.set_tap_threshold_ms(20);
+        assert_eq!(sm.ptt_press().unwrap(), PttAction::Start);
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(sm.ptt_release().unwrap(), PttAction::Stop);
+        sm.finish_processing().unwrap();
+        // Threshold 0: every release stops
+        sm.set_tap_threshold_ms(0);
+        assert_eq!(sm.ptt_press().unwrap(), PttAction::Start);
+        assert_eq!(sm.ptt_release().unwrap(), PttAction::Stop);
+    }
+
+    #[test]
+    fn test_hotkey_mode_from_setting() {
+        assert_eq!(HotkeyMode::from_setting("push_to_talk"), HotkeyMode::PushToTalk);
+        assert_eq!(HotkeyMode::from_setting("toggle"), HotkeyMode::Toggle);
+        assert_eq!(HotkeyMode::from_setting("unknown"), HotkeyMode::Toggle);
+    }
+
+    #[test]
+    fn test_set_debounce_ms() {
+        let sm = HotkeySM::new(10000);
+        sm.try_toggle().unwrap();
+        sm.set_debounce_ms(0);
+        assert_eq!(sm.debounce_ms(), 0);
+        assert_ne!(sm.try_toggle().unwrap(), None);
+    }
+
     #[test]
     fn test_reset_debounce() {
         let sm = HotkeySM::new(10000);
@@ -5,30 +5,68 @@ use tauri::{
 };
 use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut, ShortcutState, GlobalShortcutExt};
 use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 // Global last-audio-manager error for diagnostics (frontend can query this)
 static AUDIO_MANAGER_LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+// Audio manager liveness: cleared when its thread exits (panics included); the heartbeat (unix ms)
+// is refreshed on every loop iteration, at least every 250ms while idle
+static AUDIO_MANAGER_ALIVE: AtomicBool = AtomicBool::new(false);
+static AUDIO_MANAGER_HEARTBEAT_MS: AtomicU64 = AtomicU64::new(0);
+// Bumped on every spawn so a replaced thread exiting late doesn't mark its successor dead
+static AUDIO_MANAGER_GENERATION: AtomicU64 = AtomicU64::new(0);
+// A heartbeat older than this means the thread is stuck (a test clip blocks it for at most ~15s)
+const AUDIO_MANAGER_STALL_MS: u64 = 20_000;
+// Set while the offline queue is being flushed so the background task and flush_queue don't overlap
+static QUEUE_FLUSHING: AtomicBool = AtomicBool::new(false);
+// How often the background task retries the offline queue
+const QUEUE_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+// Recording pipelines and insertion workers still running; shutdown waits for them to finish
+static ACTIVE_PIPELINE_TASKS: AtomicUsize = AtomicUsize::new(0);
+// Set once an exit is under way so repeated quit requests don't run the cleanup twice
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+// Model lists fetched by list_models, keyed by flavor + endpoint
+static MODELS_CACHE: Mutex<Option<HashMap<String, (std::time::Instant, Vec<String>)>>> = Mutex::new(None);
+const MODELS_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+// Give the frontend time to register its listeners before the startup API key check emits
+const STARTUP_CHECK_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+// How long shutdown waits for the pipeline to unmute and flush queued insertions
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
 use std::sync::mpsc as std_mpsc;
 // no additional thread/state for AudioCapture; it's not Send
 mod settings;
 use settings::AppSettings;
 mod audio;
-use audio::AudioCapture;
+use audio::{AudioCapture, PreRollBuffer};
 mod stt;
-use stt::STTService;
+use stt::{EmptyReason, ResponseFormat, STTService, Transcription};
 mod translation;
-use translation::TranslationService;
+use translation::{ApiMode, CorrectionStyle, TranslationService};
 mod text_insertion;
-use text_insertion::TextInsertionService;
+use text_insertion::{InsertionMethod, InsertionOutcome, OutputMode, TextInsertionService};
 mod system_audio;
 use system_audio::SystemAudioControl;
 mod debug_logger;
-use debug_logger::DebugLogger;
+use debug_logger::{DebugLogger, LogLevel};
 mod storage;
-use storage::SettingsStore;
+use storage::{HistoryEntry, HistoryStore, MetricsStore, ProfileList, ProfileStore, SessionMetrics, SettingsStore};
 mod hotkey_fsm;
-use hotkey_fsm::HotkeySM;
+use hotkey_fsm::{HotkeyMode, HotkeySM, PttAction};
+mod api_flavor;
+use api_flavor::{api_key_required, custom_header_map, parse_model_ids, ApiFlavor};
+mod audio_encoding;
+use audio_encoding::UploadFormat;
+mod retry;
+mod error;
+use error::TalkToMeError;
+mod tray_status;
+mod offline_queue;
+use offline_queue::{OfflineQueue, QueuedRecording};
+mod text_format;
+mod permissions;
+use permissions::MicPermission;
 
 // Global state to track registered hotkeys and active recording
 type HotkeyRegistry = Mutex<HashMap<String, String>>;
@@ -40,6 +78,80 @@ type LastStopTime = Arc<Mutex<Option<std::time::Instant>>>;
 type LastHotkey = Arc<Mutex<Option<(String, std::time::Instant)>>>;
 // FSM for recording state with debouncing
 type HotkeySMState = Arc<HotkeySM>;
+// Set when the active recording is cancelled so the pipeline exits before transcription
+type CancelFlag = Arc<AtomicBool>;
+// Audio of the last single recording whose transcription failed, kept for retry_last_transcription
+type LastFailedRecording = Arc<Mutex<Option<FailedRecording>>>;
+// Final text of the last transcription, for reinsert_last
+type LastFinalText = Arc<Mutex<Option<String>>>;
+
+struct FailedRecording {
+    samples: Vec<f32>,
+    sample_rate: u32,
+    // Settings of the original recording so the retry behaves the same way
+    settings: AppSettings,
+    // Offline queue entry holding the same audio, removed once either copy is transcribed
+    queue_id: Option<String>,
+}
+
+// Totals for one recording session, sent with the pipeline's final recording-stopped event
+#[derive(Default)]
+struct SessionStats {
+    samples: usize,
+    // Seconds of audio captured (only the speech segments in chunked mode)
+    duration_secs: f32,
+    final_text: String,
+    translation_ran: bool,
+    stt_requests: u32,
+    stt_failures: u32,
+    stt_ms: u64,
+    // Set when correction/translation was attempted
+    translation_ms: Option<u64>,
+}
+
+impl SessionStats {
+    fn add_audio(&mut self, samples: usize, sample_rate: u32) {
+        self.samples += samples;
+        if sample_rate > 0 {
+            self.duration_secs += samples as f32 / sample_rate as f32;
+        }
+    }
+
+    fn add_stt(&mut self, started: std::time::Instant, ok: bool) {
+        self.stt_requests += 1;
+        if !ok {
+            self.stt_failures += 1;
+        }
+        self.stt_ms += started.elapsed().as_millis() as u64;
+    }
+
+    fn add_translation(&mut self, started: std::time::Instant) {
+        self.translation_ms = Some(started.elapsed().as_millis() as u64);
+    }
+
+    // Sessions that never reached STT (silence, cancel) aren't worth a metrics entry
+    fn metrics(&self, total: std::time::Duration) -> Option<SessionMetrics> {
+        (self.stt_requests > 0).then(|| SessionMetrics {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            capture_secs: self.duration_secs,
+            stt_latency_ms: self.stt_ms,
+            translation_latency_ms: self.translation_ms,
+            total_ms: total.as_millis() as u64,
+            words: self.final_text.split_whitespace().count(),
+            success: self.stt_failures == 0,
+        })
+    }
+
+    fn payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "durationSecs": self.duration_secs,
+            "sampleCount": self.samples,
+            "wordCount": self.final_text.split_whitespace().count(),
+            "charCount": self.final_text.chars().count(),
+            "translationRan": self.translation_ran
+        })
+    }
+}
 
 // Commands sent to the single-threaded audio manager which owns the non-Send AudioCapture
 enum AudioManagerCommand {
@@ -48,16 +160,277 @@ enum AudioManagerCommand {
     reply: std_mpsc::Sender<Result<std_mpsc::Receiver<crate::audio::AudioChunk>, String>>,
     // Whether frontend requested real-time chunking (VAD). If false, capture should operate in passthrough
     audio_chunking_enabled: bool,
+    // Run nnnoiseless over the final audio (otherwise it is only resampled)
+    noise_reduction_enabled: bool,
+    // Wet/dry blend of the denoised audio, 1.0 = fully denoised
+    denoise_strength: f32,
+    // Trim leading/trailing silence (and long pauses) from the final single-recording buffer
+    silence_trim: Option<crate::audio::SilenceTrim>,
+    mono_mixdown: crate::audio::MonoMixdown,
+    // Microphone, or the output device in loopback mode
+    capture_source: crate::audio::CaptureSource,
+    // Input device name as reported by cpal, or "default" for the host default
+    audio_device: String,
+    // Receives throttled input levels (0.0-1.0) for the frontend VU meter
+    level_tx: Option<std_mpsc::SyncSender<f32>>,
+    // Notified when the input device is lost or replaced mid-recording
+    device_tx: Option<std_mpsc::Sender<crate::audio::DeviceEvent>>,
+    // Milliseconds of audio kept from before the hotkey (0 disables the standby stream)
+    pre_roll_ms: u32,
+    },
+    ConfigurePreRoll {
+        audio_device: String,
+        pre_roll_ms: u32,
+        mono_mixdown: crate::audio::MonoMixdown,
+    },
+    // Record a short diagnostics clip; replies with the 16kHz mono samples
+    TestClip {
+        audio_device: String,
+        seconds: u32,
+        reply: std_mpsc::Sender<Result<Vec<f32>, String>>,
     },
     Stop {
         // optional reply to acknowledge stop
         reply: Option<std_mpsc::Sender<Result<(), String>>>,
     },
+    Cancel {
+        // optional reply to acknowledge cancellation
+        reply: Option<std_mpsc::Sender<Result<(), String>>>,
+    },
 }
 
 // Arc+Mutex wrapper so we can store the command sender in Tauri managed state
 type AudioManagerHandle = Arc<Mutex<std_mpsc::Sender<AudioManagerCommand>>>;
 
+// Standby pre-roll configuration (device, ms, downmix); None keeps the microphone closed between recordings
+type PreRollConfig = Option<(String, u32, crate::audio::MonoMixdown)>;
+
+// Open the standby pre-roll stream for the given configuration, if any
+fn arm_pre_roll(config: &PreRollConfig) -> Option<PreRollBuffer> {
+    let (audio_device, pre_roll_ms, mono_mixdown) = config.as_ref()?;
+    match PreRollBuffer::start(audio_device, *pre_roll_ms, *mono_mixdown) {
+        Ok(buffer) => Some(buffer),
+        Err(e) => {
+            DebugLogger::log_warn(&format!("Failed to start pre-roll buffer: {}", e));
+            None
+        }
+    }
+}
+
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// Held by the audio manager thread; dropping it (normal exit or unwinding panic) marks the manager dead
+struct AudioManagerLiveness {
+    generation: u64,
+}
+
+impl AudioManagerLiveness {
+    fn enter(generation: u64) -> Self {
+        AUDIO_MANAGER_HEARTBEAT_MS.store(unix_millis(), Ordering::SeqCst);
+        AUDIO_MANAGER_ALIVE.store(true, Ordering::SeqCst);
+        AudioManagerLiveness { generation }
+    }
+}
+
+impl Drop for AudioManagerLiveness {
+    fn drop(&mut self) {
+        if AUDIO_MANAGER_GENERATION.load(Ordering::SeqCst) != self.generation {
+            return;
+        }
+        AUDIO_MANAGER_ALIVE.store(false, Ordering::SeqCst);
+        if std::thread::panicking() {
+            let msg = "Audio manager thread panicked; restart it to record again".to_string();
+            DebugLogger::log_pipeline_error("audio_manager", &msg);
+            if let Ok(mut last_err) = AUDIO_MANAGER_LAST_ERROR.lock() {
+                *last_err = Some(msg);
+            }
+        }
+    }
+}
+
+// Health snapshot for get_audio_manager_health; "healthy" means running with a recent heartbeat
+fn audio_manager_health() -> serde_json::Value {
+    let alive = AUDIO_MANAGER_ALIVE.load(Ordering::SeqCst);
+    let heartbeat_age_ms = unix_millis().saturating_sub(AUDIO_MANAGER_HEARTBEAT_MS.load(Ordering::SeqCst));
+    serde_json::json!({
+        "alive": alive,
+        "healthy": alive && heartbeat_age_ms < AUDIO_MANAGER_STALL_MS,
+        "heartbeat_age_ms": heartbeat_age_ms,
+        "generation": AUDIO_MANAGER_GENERATION.load(Ordering::SeqCst),
+        "last_error": AUDIO_MANAGER_LAST_ERROR.lock().ok().and_then(|err| err.clone()),
+    })
+}
+
+// Appended to audio manager errors so the UI can point at restart_audio_manager
+fn audio_manager_hint() -> &'static str {
+    if audio_manager_health()["healthy"].as_bool().unwrap_or(false) {
+        ""
+    } else {
+        " (the audio manager is not responding; restart it)"
+    }
+}
+
+// Spawn a dedicated single-thread audio manager to own the non-Send AudioCapture; returns its command sender.
+// Called once at startup and again by restart_audio_manager.
+fn spawn_audio_manager() -> std_mpsc::Sender<AudioManagerCommand> {
+    let (cmd_tx, cmd_rx) = std_mpsc::channel::<AudioManagerCommand>();
+    let generation = AUDIO_MANAGER_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    // Spawn thread that owns AudioCapture and responds to commands
+    std::thread::spawn(move || {
+        DebugLogger::log_info("Audio manager thread starting");
+        let _liveness = AudioManagerLiveness::enter(generation);
+        // The audio capture instance is owned here on this single thread
+        let mut audio_capture_opt: Option<AudioCapture> = None;
+        // Standby stream feeding the pre-roll, open only between recordings
+        let mut pre_roll_config: PreRollConfig = None;
+        let mut pre_roll: Option<PreRollBuffer> = None;
+        loop {
+            AUDIO_MANAGER_HEARTBEAT_MS.store(unix_millis(), Ordering::SeqCst);
+            // Wake up periodically to notice lost input devices while recording
+            let cmd = match cmd_rx.recv_timeout(std::time::Duration::from_millis(250)) {
+                Ok(cmd) => cmd,
+                Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                    if let Some(cap) = audio_capture_opt.as_mut() {
+                        cap.check_device();
+                        if !cap.is_active() {
+                            // Device loss stopped the capture; free the slot for the next Start
+                            DebugLogger::log_info("Audio manager releasing capture stopped by device loss");
+                            audio_capture_opt = None;
+                            pre_roll = arm_pre_roll(&pre_roll_config);
+                        }
+                    }
+                    continue;
+                }
+                Err(std_mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+            match cmd {
+                AudioManagerCommand::Start { reply, audio_chunking_enabled, noise_reduction_enabled, denoise_strength, silence_trim, mono_mixdown, capture_source, audio_device, level_tx, device_tx, pre_roll_ms } => {
+                    DebugLogger::log_info("Audio manager received Start command");
+                    // If already started, return error
+                    if audio_capture_opt.is_some() {
+                        DebugLogger::log_info("Audio manager received duplicate Start - capture already running");
+                        let err_msg = "Audio capture already started; call stop_recording() before starting a new capture".to_string();
+                        // store for diagnostics
+                        if let Ok(mut last_err) = AUDIO_MANAGER_LAST_ERROR.lock() {
+                            *last_err = Some(err_msg.clone());
+                        }
+                        let _ = reply.send(Err(err_msg));
+                        continue;
+                    }
+                    // Create and start capture (only once)
+                    let mut capture = AudioCapture::new();
+                    // A loopback-only recording never uses the microphone pre-roll, so don't keep it open
+                    let new_pre_roll_config: PreRollConfig = (pre_roll_ms > 0 && capture_source != crate::audio::CaptureSource::SystemLoopback)
+                        .then(|| (audio_device.clone(), pre_roll_ms, mono_mixdown));
+                    match capture.start_capture(capture_source, &audio_device, audio_chunking_enabled, noise_reduction_enabled, denoise_strength, silence_trim, mono_mixdown, level_tx, device_tx) {
+                        Ok(rx) => {
+                            // Prepend what the standby stream heard, then close it for the duration of the recording
+                            if let Some(buffer) = pre_roll.take() {
+                                if new_pre_roll_config.is_some() && pre_roll_config == new_pre_roll_config {
+                                    let (samples, rate) = buffer.take();
+                                    capture.prepend_audio(samples, rate);
+                                }
+                            }
+                            pre_roll_config = new_pre_roll_config;
+                            audio_capture_opt = Some(capture);
+                            DebugLogger::log_info("Audio manager successfully started capture and returned receiver");
+                            let _ = reply.send(Ok(rx));
+                        }
+                        Err(e) => {
+                            let msg = format!("Failed to start capture in manager: {}", e);
+                            DebugLogger::log_pipeline_error("audio_manager", &msg);
+                            let _ = reply.send(Err(msg));
+                        }
+                    }
+                }
+                AudioManagerCommand::Stop { reply } => {
+                    DebugLogger::log_info("Audio manager received Stop command");
+                    if let Some(mut cap) = audio_capture_opt.take() {
+                        DebugLogger::log_info("Audio manager is stopping active capture (cap was Some)");
+                        if let Err(e) = cap.stop_recording() {
+                            DebugLogger::log_pipeline_error("audio_manager", &format!("Error stopping capture: {}", e));
+                        } else {
+                            DebugLogger::log_info("Audio manager stop_recording() returned Ok");
+                        }
+                    } else {
+                        DebugLogger::log_info("Audio manager Stop called but no active capture was present (cap was None)");
+                        if let Ok(mut last_err) = AUDIO_MANAGER_LAST_ERROR.lock() {
+                            *last_err = Some("Stop called but no active capture present".to_string());
+                        }
+                    }
+                    pre_roll = arm_pre_roll(&pre_roll_config);
+                    if let Some(r) = reply {
+                        let _ = r.send(Ok(()));
+                    }
+                }
+                AudioManagerCommand::Cancel { reply } => {
+                    DebugLogger::log_info("Audio manager received Cancel command");
+                    let result = if let Some(mut cap) = audio_capture_opt.take() {
+                        cap.cancel_recording().map_err(|e| {
+                            let msg = format!("Error cancelling capture: {}", e);
+                            DebugLogger::log_pipeline_error("audio_manager", &msg);
+                            msg
+                        })
+                    } else {
+                        DebugLogger::log_info("Audio manager Cancel called but no active capture was present");
+                        Ok(())
+                    };
+                    pre_roll = arm_pre_roll(&pre_roll_config);
+                    if let Some(r) = reply {
+                        let _ = r.send(result);
+                    }
+                }
+                AudioManagerCommand::ConfigurePreRoll { audio_device, pre_roll_ms, mono_mixdown } => {
+                    let new_pre_roll_config: PreRollConfig = (pre_roll_ms > 0).then_some((audio_device, pre_roll_ms, mono_mixdown));
+                    // Sent on every settings save; keep an unchanged standby stream and what it has buffered
+                    if new_pre_roll_config == pre_roll_config && (pre_roll.is_some() || audio_capture_opt.is_some()) {
+                        continue;
+                    }
+                    DebugLogger::log_info(&format!("Audio manager configuring pre-roll: {:?}", new_pre_roll_config));
+                    pre_roll_config = new_pre_roll_config;
+                    // Close the old standby stream first so the device isn't opened twice
+                    pre_roll = None;
+                    if audio_capture_opt.is_none() {
+                        pre_roll = arm_pre_roll(&pre_roll_config);
+                    }
+                }
+                AudioManagerCommand::TestClip { audio_device, seconds, reply } => {
+                    DebugLogger::log_info(&format!("Audio manager recording {}s test clip on '{}'", seconds, audio_device));
+                    if audio_capture_opt.is_some() {
+                        let _ = reply.send(Err("Cannot record a test clip while a recording is in progress".to_string()));
+                        continue;
+                    }
+                    // Blocks this thread for the clip length; the clip is capped to a few seconds
+                    drop(pre_roll.take());
+                    let mut capture = AudioCapture::new();
+                    let result = match capture.start_capture(crate::audio::CaptureSource::Microphone, &audio_device, false, false, 1.0, None, crate::audio::MonoMixdown::Average, None, None) {
+                        Ok(rx) => {
+                            std::thread::sleep(std::time::Duration::from_secs(seconds as u64));
+                            match capture.stop_recording() {
+                                Ok(()) => rx
+                                    .recv_timeout(std::time::Duration::from_secs(5))
+                                    .map(|chunk| chunk.data)
+                                    .map_err(|e| format!("No audio received from the test capture: {}", e)),
+                                Err(e) => Err(format!("Failed to stop test capture: {}", e)),
+                            }
+                        }
+                        Err(e) => Err(format!("Failed to start test capture: {}", e)),
+                    };
+                    pre_roll = arm_pre_roll(&pre_roll_config);
+                    let _ = reply.send(result);
+                }
+            }
+        }
+        DebugLogger::log_info("Audio manager thread exiting");
+    });
+    cmd_tx
+}
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -246,6 +619,37 @@ fn clear_audio_manager_last_error() {
     }
 }
 
+/// Whether the audio manager thread is running and responsive
+#[tauri::command]
+fn get_audio_manager_health() -> serde_json::Value {
+    audio_manager_health()
+}
+
+/// Replace the audio manager thread, e.g. after it panicked or stopped responding.
+/// A recording in progress is cancelled; a stuck old thread exits once its channel disconnects.
+#[tauri::command]
+async fn restart_audio_manager(app: AppHandle) -> Result<serde_json::Value, String> {
+    DebugLogger::log_info("Restarting audio manager thread");
+    let cancel_app = app.clone();
+    tokio::task::spawn_blocking(move || cancel_active_recording(&cancel_app))
+        .await
+        .map_err(|e| e.to_string())?;
+    {
+        let handle = app.state::<AudioManagerHandle>();
+        let mut sender = handle.lock().map_err(|e| e.to_string())?;
+        *sender = spawn_audio_manager();
+    }
+    let fsm = app.state::<HotkeySMState>();
+    fsm.force_set_state(hotkey_fsm::RecordingState::Idle)?;
+    fsm.reset_debounce()?;
+    if let Ok(mut err) = AUDIO_MANAGER_LAST_ERROR.lock() {
+        *err = None;
+    }
+    // Re-arm the standby pre-roll stream the old thread owned
+    sync_pre_roll(&app, &SettingsStore::load(&app).unwrap_or_default());
+    Ok(audio_manager_health())
+}
+
 /// Test hotkey parsing (for debugging)
 #[tauri::command]
 fn test_hotkey_parsing(hotkey: String) -> Result<String, String> {
@@ -259,15 +663,78 @@ fn test_hotkey_parsing(hotkey: String) -> Result<String, String> {
     }
 }
 
+// Hands-free toggle path shared by the hotkey and the tray menu: stop if recording,
+// otherwise show the confirmation window
+fn toggle_recording_from_hotkey(app_handle: &AppHandle, source: &str, ts_ms: u128) {
+    // Check if we are currently recording
+    let fsm_state = app_handle
+        .try_state::<HotkeySMState>()
+        .map(|fsm| fsm.get_state().unwrap_or(hotkey_fsm::RecordingState::Idle))
+        .unwrap_or(hotkey_fsm::RecordingState::Idle);
+    if fsm_state == hotkey_fsm::RecordingState::Processing {
+        // Starting now would race the previous session's cleanup
+        DebugLogger::log_info(&format!("HOTKEY_IGNORED: action={} while processing previous recording", source));
+        return;
+    }
+    let is_recording = fsm_state == hotkey_fsm::RecordingState::Recording;
+
+    if is_recording {
+        // Already recording: Stop immediately (standard behavior)
+        if let Some(fsm) = app_handle.try_state::<HotkeySMState>() {
+            match fsm.try_toggle() {
+                Ok(Some(new_state)) => {
+                    DebugLogger::log_info(&format!(
+                        "HOTKEY_FSM_TOGGLE: action={}, new_state={:?}, ts_ms={}",
+                        source, new_state, ts_ms
+                    ));
+                    let _ = app_handle.emit("toggle-recording-from-hotkey", ());
+                }
+                Ok(None) => {
+                    DebugLogger::log_info(&format!("HOTKEY_FSM_DEBOUNCED: action={} (stop)", source));
+                }
+                Err(e) => {
+                    DebugLogger::log_pipeline_error("hotkey_fsm", &format!("FSM error: {}", e));
+                }
+            }
+        } else {
+            let _ = app_handle.emit("toggle-recording-from-hotkey", ());
+        }
+    } else {
+        // Not recording: Show confirmation window instead of starting immediately
+        DebugLogger::log_info(&format!("HOTKEY_TRIGGER: Showing confirmation window (action={})", source));
+        if let Some(window) = app_handle.get_webview_window("confirmation") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+}
+
+// Persistent hotkey fields; the optional ones may be left empty to disable them
+const HOTKEY_SETTING_FIELDS: [&str; 5] = ["hands_free_hotkey", "start_recording_hotkey", "stop_recording_hotkey", "cancel_hotkey", "reinsert_hotkey"];
+
+// Reject a hotkey string at save time with the same error registration would hit later
+fn validate_hotkey_setting(field: &str, hotkey: &str) -> Result<(), String> {
+    if hotkey.is_empty() && field != "hands_free_hotkey" {
+        return Ok(());
+    }
+    parse_hotkey(hotkey).map(|_| ())
+}
+
 // Command to register hotkeys
 #[tauri::command]
 async fn register_hotkeys(
     app: AppHandle,
     hotkeys: std::collections::HashMap<String, String>,
+    hotkey_mode: Option<String>,
     registry: State<'_, HotkeyRegistry>,
 ) -> Result<(), String> {
     let global_shortcut = app.global_shortcut();
-    DebugLogger::log_info(&format!("register_hotkeys called, hotkeys_count={}", hotkeys.len()));
+    // The frontend re-registers without a mode, so fall back to the saved one instead of toggle
+    let hotkey_mode = match hotkey_mode {
+        Some(mode) => HotkeyMode::from_setting(&mode),
+        None => HotkeyMode::from_setting(&SettingsStore::load(&app).unwrap_or_default().hotkey_mode),
+    };
+    DebugLogger::log_info(&format!("register_hotkeys called, hotkeys_count={}, mode={:?}", hotkeys.len(), hotkey_mode));
     
     // Log each hotkey being registered
     for (action, hotkey_str) in &hotkeys {
@@ -312,49 +779,131 @@ async fn register_hotkeys(
                 // Normalize action names to support both camelCase and snake_case
                 let normalized = match action_clone.as_str() {
                     "handsFree" | "hands_free" => "hands_free",
+                    "startRecording" | "start_recording" => "start_recording",
+                    "stopRecording" | "stop_recording" => "stop_recording",
+                    "cancel" | "cancelRecording" | "cancel_recording" => "cancel",
+                    "reinsertLast" | "reinsert_last" => "reinsert_last",
                     other => other,
                 };
 
                 match (normalized, ev.state) {
-                    // Hands-free: Only process key press (ignore release)
-                    ("hands_free", ShortcutState::Pressed) => {
-                        // Check if we are currently recording
-                        let is_recording = if let Some(fsm) = app_handle.try_state::<HotkeySMState>() {
-                            fsm.get_state().unwrap_or(hotkey_fsm::RecordingState::Idle) == hotkey_fsm::RecordingState::Recording
-                        } else {
-                            false
-                        };
-
-                        if is_recording {
-                            // Already recording: Stop immediately (standard behavior)
-                            if let Some(fsm) = app_handle.try_state::<HotkeySMState>() {
-                                match fsm.try_toggle() {
-                                    Ok(Some(new_state)) => {
-                                        DebugLogger::log_info(&format!(
-                                            "HOTKEY_FSM_TOGGLE: action=hands_free, new_state={:?}, ts_ms={}",
-                                            new_state, ts_ms
-                                        ));
-                                        let _ = app_for_emit.emit("toggle-recording-from-hotkey", ());
-                                    }
-                                    Ok(None) => {
-                                        DebugLogger::log_info("HOTKEY_FSM_DEBOUNCED: action=hands_free (stop)");
-                                    }
-                                    Err(e) => {
-                                        DebugLogger::log_pipeline_error("hotkey_fsm", &format!("FSM error: {}", e));
-                                    }
+                    // Push-to-talk: start on press, stop on release (no confirmation dialog).
+                    // A quick tap latches the recording on until the next tap.
+                    ("hands_free", ShortcutState::Pressed) if hotkey_mode == HotkeyMode::PushToTalk => {
+                        if let Some(fsm) = app_handle.try_state::<HotkeySMState>() {
+                            match fsm.ptt_press() {
+                                Ok(PttAction::Start) => {
+                                    DebugLogger::log_info(&format!("HOTKEY_PTT_PRESS: starting recording, ts_ms={}", ts_ms));
+                                    let _ = app_for_emit.emit("toggle-recording-from-hotkey", ());
+                                }
+                                Ok(PttAction::Stop) => {
+                                    DebugLogger::log_info(&format!("HOTKEY_PTT_PRESS: stopping latched recording, ts_ms={}", ts_ms));
+                                    let _ = app_for_emit.emit("toggle-recording-from-hotkey", ());
+                                }
+                                Ok(_) => {
+                                    DebugLogger::log_info("HOTKEY_PTT_PRESS: ignored (already recording, key repeat or debounced)");
+                                }
+                                Err(e) => {
+                                    DebugLogger::log_pipeline_error("hotkey_fsm", &format!("FSM error: {}", e));
                                 }
-                            } else {
-                                let _ = app_for_emit.emit("toggle-recording-from-hotkey", ());
                             }
-                        } else {
-                            // Not recording: Show confirmation window instead of starting immediately
-                            DebugLogger::log_info("HOTKEY_TRIGGER: Showing confirmation window");
-                            if let Some(window) = app_handle.get_webview_window("confirmation") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
+                        }
+                    }
+                    ("hands_free", ShortcutState::Released) if hotkey_mode == HotkeyMode::PushToTalk => {
+                        if let Some(fsm) = app_handle.try_state::<HotkeySMState>() {
+                            match fsm.ptt_release() {
+                                Ok(PttAction::Stop) => {
+                                    DebugLogger::log_info(&format!("HOTKEY_PTT_RELEASE: stopping recording, ts_ms={}", ts_ms));
+                                    let _ = app_for_emit.emit("toggle-recording-from-hotkey", ());
+                                }
+                                Ok(PttAction::Latch) => {
+                                    DebugLogger::log_info(&format!("HOTKEY_PTT_RELEASE: tap, recording latched on, ts_ms={}", ts_ms));
+                                    let _ = app_for_emit.emit("recording-latched", ());
+                                }
+                                Ok(_) => {
+                                    DebugLogger::log_info("HOTKEY_PTT_RELEASE: ignored (not recording or latched)");
+                                }
+                                Err(e) => {
+                                    DebugLogger::log_pipeline_error("hotkey_fsm", &format!("FSM error: {}", e));
+                                }
                             }
                         }
                     }
+                    // Dedicated start key: same FSM transition as a push-to-talk press, no confirmation dialog
+                    ("start_recording", ShortcutState::Pressed) => {
+                        if let Some(fsm) = app_handle.try_state::<HotkeySMState>() {
+                            match fsm.try_start() {
+                                Ok(true) => {
+                                    DebugLogger::log_info(&format!("HOTKEY_START: starting recording, ts_ms={}", ts_ms));
+                                    let _ = app_for_emit.emit("toggle-recording-from-hotkey", ());
+                                }
+                                Ok(false) => {
+                                    DebugLogger::log_info("HOTKEY_START: ignored (already recording or debounced)");
+                                }
+                                Err(e) => {
+                                    DebugLogger::log_pipeline_error("hotkey_fsm", &format!("FSM error: {}", e));
+                                }
+                            }
+                        }
+                    }
+                    // Dedicated stop key: no-op unless recording
+                    ("stop_recording", ShortcutState::Pressed) => {
+                        if let Some(fsm) = app_handle.try_state::<HotkeySMState>() {
+                            match fsm.release() {
+                                Ok(true) => {
+                                    DebugLogger::log_info(&format!("HOTKEY_STOP: stopping recording, ts_ms={}", ts_ms));
+                                    let _ = app_for_emit.emit("toggle-recording-from-hotkey", ());
+                                }
+                                Ok(false) => {
+                                    DebugLogger::log_info("HOTKEY_STOP: ignored (not recording)");
+                                }
+                                Err(e) => {
+                                    DebugLogger::log_pipeline_error("hotkey_fsm", &format!("FSM error: {}", e));
+                                }
+                            }
+                        }
+                    }
+                    // Cancel key: abort without transcribing, only while recording
+                    ("cancel", ShortcutState::Pressed) => {
+                        let is_recording = app_handle
+                            .try_state::<HotkeySMState>()
+                            .and_then(|fsm| fsm.get_state().ok())
+                            == Some(hotkey_fsm::RecordingState::Recording);
+                        if !is_recording {
+                            DebugLogger::log_info("HOTKEY_CANCEL: ignored (not recording)");
+                            return;
+                        }
+                        DebugLogger::log_info(&format!("HOTKEY_CANCEL: cancelling recording, ts_ms={}", ts_ms));
+                        let app = app_for_emit.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let result = cancel_recording(
+                                app.clone(),
+                                app.state::<RecordingState>(),
+                                app.state::<AudioStopSender>(),
+                                app.state::<AudioManagerHandle>(),
+                                app.state::<HotkeySMState>(),
+                                app.state::<CancelFlag>(),
+                            )
+                            .await;
+                            if let Err(e) = result {
+                                DebugLogger::log_pipeline_error("hotkey_cancel", &e);
+                            }
+                        });
+                    }
+                    // Re-insert key: paste the last final text into whatever window has focus now
+                    ("reinsert_last", ShortcutState::Pressed) => {
+                        DebugLogger::log_info(&format!("HOTKEY_REINSERT: inserting last text again, ts_ms={}", ts_ms));
+                        let app = app_for_emit.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = reinsert_last(app.clone(), app.state::<LastFinalText>()).await {
+                                DebugLogger::log_pipeline_error("hotkey_reinsert", &e);
+                            }
+                        });
+                    }
+                    // Hands-free toggle: Only process key press (ignore release)
+                    ("hands_free", ShortcutState::Pressed) => {
+                        toggle_recording_from_hotkey(app_handle, "hands_free", ts_ms);
+                    }
                     _ => {
                         let state = match ev.state {
                             ShortcutState::Pressed => "pressed",
@@ -384,6 +933,54 @@ async fn register_hotkeys(
     Ok(())
 }
 
+/// Which desktop notifications are shown (`notifications` setting)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotificationLevel {
+    All,
+    /// Failures only; routine start/stop/completed toasts are suppressed
+    ErrorsOnly,
+    Off,
+}
+
+impl NotificationLevel {
+    fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "errors_only" | "errors" => NotificationLevel::ErrorsOnly,
+            "off" | "none" => NotificationLevel::Off,
+            _ => NotificationLevel::All,
+        }
+    }
+
+    fn allows(self, is_error: bool) -> bool {
+        match self {
+            NotificationLevel::All => true,
+            NotificationLevel::ErrorsOnly => is_error,
+            NotificationLevel::Off => false,
+        }
+    }
+}
+
+// Show a desktop notification unless the `notifications` setting suppresses it
+fn notify(app: &AppHandle, level: &str, is_error: bool, title: &str, body: &str) -> Result<(), String> {
+    if !NotificationLevel::from_setting(level).allows(is_error) {
+        DebugLogger::log_info(&format!("Notification '{}' suppressed (notifications={})", title, level));
+        return Ok(());
+    }
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| e.to_string())
+}
+
+// Notification level for the frontend-triggered notifications, which get no settings passed in
+fn stored_notification_level(app: &AppHandle) -> String {
+    SettingsStore::load(app)
+        .map(|settings| settings.notifications)
+        .unwrap_or_else(|_| AppSettings::default().notifications)
+}
+
 // Command to show recording started notification
 #[tauri::command]
 async fn show_recording_started_notification(
@@ -401,12 +998,7 @@ async fn show_recording_started_notification(
     
     DebugLogger::log_info("Showing recording started notification");
     
-    app.notification()
-        .builder()
-        .title("Recording Started")
-        .body("🎤 Listening for speech...")
-        .show()
-        .map_err(|e| e.to_string())?;
+    notify(&app, &stored_notification_level(&app), false, "Recording Started", "🎤 Listening for speech...")?;
         
     Ok(())
 }
@@ -419,63 +1011,493 @@ async fn show_recording_stopped_notification(
 ) -> Result<(), String> {
     DebugLogger::log_info("Showing recording stopped notification");
 
-    app.notification()
-        .builder()
-        .title("Recording Stopped")
-        .body("⏳ Processing audio...")
-        .show()
-        .map_err(|e| e.to_string())?;
+    notify(&app, &stored_notification_level(&app), false, "Recording Stopped", "⏳ Processing audio...")?;
+
+    Ok(())
+}
+
+// Command to confirm recording from dialog
+#[tauri::command]
+async fn confirm_recording(
+    app: AppHandle,
+    fsm: State<'_, HotkeySMState>,
+) -> Result<(), String> {
+    DebugLogger::log_info("CONFIRM_RECORDING: User confirmed recording start");
+    
+    // Hide confirmation window
+    if let Some(window) = app.get_webview_window("confirmation") {
+        let _ = window.hide();
+    }
+
+    if fsm.get_state()? == hotkey_fsm::RecordingState::Processing {
+        DebugLogger::log_info("CONFIRM_RECORDING: ignored, previous recording is still processing");
+        return Err("Previous recording is still being processed".to_string());
+    }
+
+    // Toggle FSM to Recording
+    match fsm.try_toggle() {
+        Ok(Some(new_state)) => {
+            DebugLogger::log_info(&format!("CONFIRM_RECORDING: FSM toggled to {:?}", new_state));
+            // Emit event to start recording
+            let _ = app.emit("toggle-recording-from-hotkey", ());
+        }
+        Ok(None) => {
+            DebugLogger::log_info("CONFIRM_RECORDING: FSM debounced (unexpected)");
+            // Force emit anyway since user explicitly confirmed
+            let _ = app.emit("toggle-recording-from-hotkey", ());
+        }
+        Err(e) => {
+            DebugLogger::log_pipeline_error("confirm_recording", &format!("FSM error: {}", e));
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
+
+// Command to cancel recording: dismisses the confirmation dialog and, if a recording is
+// active, aborts it and discards the audio without transcribing or inserting anything
+#[tauri::command]
+async fn cancel_recording(
+    app: AppHandle,
+    recording_state: State<'_, RecordingState>,
+    audio_stop_sender: State<'_, AudioStopSender>,
+    audio_manager: State<'_, AudioManagerHandle>,
+    fsm: State<'_, HotkeySMState>,
+    cancel_flag: State<'_, CancelFlag>,
+) -> Result<(), String> {
+    DebugLogger::log_info("CANCEL_RECORDING: User cancelled recording");
+    
+    // Hide confirmation window
+    if let Some(window) = app.get_webview_window("confirmation") {
+        let _ = window.hide();
+    }
+
+    // Same cooldown as stop_recording so rapid repeated requests are dropped
+    let cooldown_ms = 100u128;
+    if let Ok(lst) = app.state::<LastStopTime>().inner().lock() {
+        if let Some(prev) = *lst {
+            let elapsed = prev.elapsed().as_millis();
+            if elapsed < cooldown_ms {
+                DebugLogger::log_info(&format!("cancel_recording ignored due to cooldown ({}ms since last stop)", elapsed));
+                return Ok(());
+            }
+        }
+    }
+    {
+        let state = recording_state.inner().lock().map_err(|e| e.to_string())?;
+        if !*state {
+            DebugLogger::log_info("CANCEL_RECORDING: No active recording, only the confirmation dialog was dismissed");
+            return Ok(());
+        }
+    }
+
+    // Flag the pipeline first so it bails out before transcription once it wakes up
+    cancel_flag.store(true, Ordering::SeqCst);
+
+    // Tell the audio manager to discard the captured audio
+    if let Ok(sender) = audio_manager.lock() {
+        let (ack_tx, ack_rx) = std_mpsc::channel();
+        let _ = sender.send(AudioManagerCommand::Cancel { reply: Some(ack_tx) });
+        match ack_rx.recv_timeout(std::time::Duration::from_secs(2)) {
+            Ok(Ok(_)) => DebugLogger::log_info("Audio manager acknowledged cancel"),
+            Ok(Err(e)) => DebugLogger::log_pipeline_error("audio_manager", &format!("Cancel error: {}", e)),
+            Err(_) => DebugLogger::log_info("No ack from audio manager on cancel (continuing)")
+        }
+    }
+
+    {
+        let mut state = recording_state.inner().lock().map_err(|e| e.to_string())?;
+        *state = false;
+        DebugLogger::log_info("RECORDING_STATE_CHANGE: Set to false in cancel_recording command");
+    }
+
+    fsm.force_set_state(hotkey_fsm::RecordingState::Idle)
+        .unwrap_or_else(|e| DebugLogger::log_warn(&format!("Failed to set FSM to Idle: {}", e)));
+
+    // Wake up the processing task so it can exit
+    {
+        let mut audio_stop = audio_stop_sender.inner().lock().map_err(|e| e.to_string())?;
+        if let Some(sender) = audio_stop.take() {
+            let _ = sender.send(());
+            DebugLogger::log_info("Stop signal sent to audio processing task (cancel)");
+        }
+    }
+    if let Ok(mut lst) = app.state::<LastStopTime>().inner().lock() {
+        *lst = Some(std::time::Instant::now());
+    }
+
+    let _ = app.emit("recording-cancelled", ());
+    DebugLogger::log_info("Recording cancelled successfully");
+    Ok(())
+}
+
+// Puts the hotkey FSM back to Idle when start_recording returns before capture is running, so a failed
+// start (e.g. no microphone permission yet) doesn't leave the hotkey stuck in Recording
+struct FailedStartGuard(Option<HotkeySMState>);
+
+impl FailedStartGuard {
+    fn disarm(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for FailedStartGuard {
+    fn drop(&mut self) {
+        if let Some(fsm) = self.0.take() {
+            if let Err(e) = fsm.abandon_start() {
+                DebugLogger::log_warn(&format!("Failed to reset FSM after a failed start: {}", e));
+            }
+        }
+    }
+}
+
+// Counts a task in ACTIVE_PIPELINE_TASKS for as long as it is alive (also on early return/panic)
+struct PipelineTaskGuard;
+
+impl PipelineTaskGuard {
+    fn new() -> Self {
+        ACTIVE_PIPELINE_TASKS.fetch_add(1, Ordering::SeqCst);
+        PipelineTaskGuard
+    }
+}
+
+impl Drop for PipelineTaskGuard {
+    fn drop(&mut self) {
+        ACTIVE_PIPELINE_TASKS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Stop a recording in progress without transcribing it; false when nothing was recording.
+// Blocks for up to two seconds waiting for the audio manager to acknowledge.
+fn cancel_active_recording(app: &AppHandle) -> bool {
+    let recording = app.state::<RecordingState>().lock().map(|state| *state).unwrap_or(false);
+    if !recording {
+        return false;
+    }
+    app.state::<CancelFlag>().store(true, Ordering::SeqCst);
+    if let Ok(sender) = app.state::<AudioManagerHandle>().lock() {
+        let (ack_tx, ack_rx) = std_mpsc::channel();
+        let _ = sender.send(AudioManagerCommand::Cancel { reply: Some(ack_tx) });
+        let _ = ack_rx.recv_timeout(std::time::Duration::from_secs(2));
+    }
+    if let Ok(mut state) = app.state::<RecordingState>().lock() {
+        *state = false;
+    }
+    if let Ok(mut audio_stop) = app.state::<AudioStopSender>().lock() {
+        if let Some(sender) = audio_stop.take() {
+            let _ = sender.send(());
+        }
+    }
+    true
+}
+
+// Exit without leaving the system muted: an active recording is cancelled (its audio is discarded),
+// then we wait for the pipeline to restore the volume and the insertion worker to drain its queue.
+// Shared by quit_app and the tray Quit item.
+fn shutdown(app: &AppHandle) {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    DebugLogger::log_info("SHUTDOWN: starting graceful shutdown");
+    if cancel_active_recording(app) {
+        DebugLogger::log_info("SHUTDOWN: cancelled active recording");
+    }
+
+    let deadline = std::time::Instant::now() + SHUTDOWN_GRACE;
+    while ACTIVE_PIPELINE_TASKS.load(Ordering::SeqCst) > 0 && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let remaining = ACTIVE_PIPELINE_TASKS.load(Ordering::SeqCst);
+    if remaining > 0 {
+        DebugLogger::log_warn(&format!("SHUTDOWN: exiting with {} pipeline task(s) still running", remaining));
+    } else {
+        DebugLogger::log_info("SHUTDOWN: pipeline idle, exiting");
+    }
+    app.exit(0);
+}
+
+// Single-recording sessions longer than this are transcribed in segments to show progress
+const PARTIAL_SEGMENT_SECS: usize = 30;
+
+fn stt_service_for(settings: &AppSettings, api_key: String) -> STTService {
+    STTService::new(
+        settings.api_endpoint.clone(),
+        api_key,
+        settings.effective_stt_model().to_string(),
+        settings.spoken_language.clone(),
+        settings.stt_prompt.clone(),
+        ApiFlavor::from_setting(&settings.api_flavor),
+        UploadFormat::from_setting(&settings.upload_format),
+        ResponseFormat::from_setting(&settings.response_format),
+        settings.request_timeout_secs as u64,
+        settings.max_retries,
+        settings.max_upload_mb,
+        settings.normalize_audio,
+        settings.stt_temperature,
+        settings.silence_threshold,
+        settings.min_duration_secs,
+        custom_header_map(&settings.custom_headers, settings.allow_auth_headers),
+        settings.local_only,
+    )
+}
+
+fn translation_service_for(settings: &AppSettings, api_key: String) -> TranslationService {
+    TranslationService::new(
+        settings.api_endpoint.clone(),
+        api_key,
+        settings.effective_translation_model().to_string(),
+        ApiFlavor::from_setting(&settings.api_flavor),
+        settings.request_timeout_secs as u64,
+        settings.max_retries,
+        settings.translation_prompt_template.clone(),
+        settings.translation_temperature,
+        settings.translation_max_tokens,
+        CorrectionStyle::from_setting(&settings.correction_style),
+        custom_header_map(&settings.custom_headers, settings.allow_auth_headers),
+        ApiMode::from_setting(&settings.api_mode),
+        settings.local_only,
+    )
+}
+
+// Transcribe a finished single recording, emitting the growing raw text after each segment.
+// The returned transcription carries the aggregated text only (segments were already emitted).
+async fn transcribe_with_progress(
+    app: &AppHandle,
+    stt_service: &STTService,
+    samples: &[f32],
+    sample_rate: u32,
+    settings: &AppSettings,
+) -> Result<Transcription, String> {
+    let segment_len = sample_rate as usize * PARTIAL_SEGMENT_SECS;
+    // A short tail is folded into the previous segment rather than sent on its own
+    let min_tail = segment_len / 4;
+    if samples.len() <= segment_len + min_tail {
+        let transcription = stt_service.transcribe_chunk_detailed(samples.to_vec(), sample_rate, Some("stt_single")).await?;
+        emit_transcription_segments(app, &transcription);
+        return Ok(transcription);
+    }
+
+    // Audio shared by neighbouring segments so words on a boundary aren't cut in half
+    let overlap = sample_rate as usize * settings.chunk_overlap_ms as usize / 1000;
+    let mut agg_text = String::new();
+    let mut skipped = None;
+    let mut language = None;
+    let mut start = 0;
+    let mut segment = 1;
+    loop {
+        let mut end = (start + segment_len).min(samples.len());
+        if samples.len() - end < min_tail {
+            end = samples.len();
+        }
+        DebugLogger::log_info(&format!("Single recording: transcribing segment {} ({:.1}s - {:.1}s)",
+            segment, start as f32 / sample_rate as f32, end as f32 / sample_rate as f32));
+        let mut transcription = stt_service
+            .transcribe_chunk_detailed(samples[start..end].to_vec(), sample_rate, Some("stt_single"))
+            .await?;
+        // Timestamps come back relative to the segment; report them against the whole recording
+        transcription.offset_by(start as f64 / sample_rate as f64);
+        emit_transcription_segments(app, &transcription);
+        skipped = skipped.or(transcription.skipped);
+        language = language.or(transcription.language);
+        let text = transcription.text.trim();
+        if !text.is_empty() {
+            text_format::append_dedup(&mut agg_text, text, settings.dedup_window_tokens as usize);
+            let _ = app.emit("transcribed-text", serde_json::json!({
+                "raw": agg_text,
+                "final": "" // Partial result; the final pass runs once all segments are in
+            }));
+        }
+        if end == samples.len() {
+            break;
+        }
+        start = end - overlap;
+        segment += 1;
+    }
+    Ok(Transcription { text: agg_text, skipped, language, ..Default::default() })
+}
+
+// Report the language the STT service detected (`detected-language`) and pick the source language
+// for the correction/translation prompt: the detected one when `spoken_language` is "auto"
+fn resolve_source_language(app: &AppHandle, settings: &AppSettings, detected: Option<&str>) -> String {
+    if let Some(language) = detected {
+        let code = translation::detected_language_code(language);
+        let _ = app.emit("detected-language", serde_json::json!({ "language": language, "code": code }));
+        if settings.spoken_language == "auto" && settings.use_detected_language {
+            if let Some(code) = code {
+                DebugLogger::log_info(&format!("Using detected language '{}' ({}) as the source language", language, code));
+                return code.to_string();
+            }
+        }
+    }
+    settings.spoken_language.clone()
+}
+
+// A stop that came less than min_recording_ms after the start (a stray hotkey tap) is treated as a
+// cancel: the cancel flag makes the pipeline discard the audio without calling the API.
+fn discard_if_too_short(app: &AppHandle, started: std::time::Instant, min_recording_ms: u32, cancel_flag: &AtomicBool) {
+    if min_recording_ms == 0 || cancel_flag.load(Ordering::SeqCst) {
+        return;
+    }
+    // Measure up to the stop request rather than to now, which includes the drain window
+    let stopped = app
+        .state::<LastStopTime>()
+        .lock()
+        .ok()
+        .and_then(|last| *last)
+        .filter(|stopped| *stopped >= started)
+        .unwrap_or_else(std::time::Instant::now);
+    let recorded_ms = stopped.duration_since(started).as_millis() as u64;
+    if recorded_ms >= min_recording_ms as u64 {
+        return;
+    }
+    DebugLogger::log_info(&format!(
+        "Recording too short ({}ms < {}ms) - discarding as a cancel",
+        recorded_ms, min_recording_ms
+    ));
+    cancel_flag.store(true, Ordering::SeqCst);
+    let _ = app.emit("recording-too-short", serde_json::json!({
+        "duration_ms": recorded_ms,
+        "min_recording_ms": min_recording_ms
+    }));
+}
+
+// Stages reported by the consolidated pipeline-status event. The older recording-started,
+// processing-audio, processing-status, transcribed-text and processing-error events are still sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PipelineStage {
+    Capturing,
+    Transcribing,
+    Translating,
+    Inserting,
+    // Always the last event of a session, including after an error or a cancel
+    Done,
+    Error,
+}
+
+impl PipelineStage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PipelineStage::Capturing => "capturing",
+            PipelineStage::Transcribing => "transcribing",
+            PipelineStage::Translating => "translating",
+            PipelineStage::Inserting => "inserting",
+            PipelineStage::Done => "done",
+            PipelineStage::Error => "error",
+        }
+    }
+}
+
+fn emit_pipeline_status(app: &AppHandle, stage: PipelineStage, detail: Option<&str>) {
+    let _ = app.emit("pipeline-status", serde_json::json!({
+        "stage": stage.as_str(),
+        "detail": detail
+    }));
+}
+
+// Unlike the plain-string processing-error payloads, this one tells the UI to offer a Retry button
+// that calls retry_last_transcription
+fn emit_retryable_error(app: &AppHandle, settings: &AppSettings, message: &str) {
+    let _ = app.emit("processing-error", serde_json::json!({
+        "message": message,
+        "retryable": true
+    }));
+    emit_pipeline_status(app, PipelineStage::Error, Some(message));
+    let _ = notify(app, &settings.notifications, true, "Transcription failed", message);
+}
+
+// `spoken_punctuation`: spoken "comma", "new line", ... become symbols on the raw transcription,
+// so they don't depend on the correction step
+// (takes the two fields so the stored PersistentSettings can use it too)
+fn apply_spoken_punctuation(enabled: bool, words: &[text_format::SpokenPunctuation], raw: String) -> String {
+    if enabled {
+        text_format::apply_spoken_punctuation(&raw, words)
+    } else {
+        raw
+    }
+}
+
+// Local clean-up of the final text: capitalization/punctuation (`local_formatting`) for text the
+// correction step didn't edit, then the user's `text_replacements` rules
+fn finalize_text(settings: &AppSettings, text: String, corrected: bool) -> String {
+    let text = if settings.local_formatting && !corrected {
+        text_format::format_locally(&text)
+    } else {
+        text
+    };
+    if settings.text_replacements.is_empty() {
+        text
+    } else {
+        text_format::apply_replacements(&text, &settings.text_replacements)
+    }
+}
 
-    Ok(())
+// One-shot `recording-timeout-warning` once the recording passes the warning point
+fn emit_timeout_warning_once(
+    app: &AppHandle,
+    elapsed: std::time::Duration,
+    warning_at: Option<std::time::Duration>,
+    max_duration: std::time::Duration,
+    sent: &mut bool,
+) {
+    if *sent || !warning_at.is_some_and(|at| elapsed >= at) {
+        return;
+    }
+    *sent = true;
+    let seconds_remaining = max_duration.saturating_sub(elapsed).as_secs();
+    DebugLogger::log_info(&format!("EMIT: recording-timeout-warning ({}s remaining)", seconds_remaining));
+    let _ = app.emit("recording-timeout-warning", serde_json::json!({ "seconds_remaining": seconds_remaining }));
 }
 
-// Command to confirm recording from dialog
-#[tauri::command]
-async fn confirm_recording(
-    app: AppHandle,
-    fsm: State<'_, HotkeySMState>,
-) -> Result<(), String> {
-    DebugLogger::log_info("CONFIRM_RECORDING: User confirmed recording start");
-    
-    // Hide confirmation window
-    if let Some(window) = app.get_webview_window("confirmation") {
-        let _ = window.hide();
+// Tell the frontend a recording produced no text (vs. a hang or error) so it can suggest checking the mic
+fn emit_transcription_empty(app: &AppHandle, reason: EmptyReason) {
+    DebugLogger::log_info(&format!("EMIT: transcription-empty (reason: {})", reason.as_str()));
+    let _ = app.emit("transcription-empty", serde_json::json!({ "reason": reason.as_str() }));
+}
+
+// Forward verbose_json timestamps to the frontend (no-op for plain json responses)
+fn emit_transcription_segments(app: &AppHandle, transcription: &Transcription) {
+    if transcription.segments.is_empty() && transcription.words.is_empty() {
+        return;
     }
+    let _ = app.emit("transcription-segments", serde_json::json!({
+        "text": transcription.text,
+        "segments": transcription.segments,
+        "words": transcription.words,
+    }));
+}
 
-    // Toggle FSM to Recording
-    match fsm.try_toggle() {
-        Ok(Some(new_state)) => {
-            DebugLogger::log_info(&format!("CONFIRM_RECORDING: FSM toggled to {:?}", new_state));
-            // Emit event to start recording
-            let _ = app.emit("toggle-recording-from-hotkey", ());
-        }
-        Ok(None) => {
-            DebugLogger::log_info("CONFIRM_RECORDING: FSM debounced (unexpected)");
-            // Force emit anyway since user explicitly confirmed
-            let _ = app.emit("toggle-recording-from-hotkey", ());
-        }
-        Err(e) => {
-            DebugLogger::log_pipeline_error("confirm_recording", &format!("FSM error: {}", e));
-            return Err(e);
-        }
+// Persist a finished transcription when history is enabled (before insertion, so a failed paste can be recovered)
+fn record_history(app: &AppHandle, settings: &AppSettings, raw_text: &str, processed_text: &str) {
+    if !settings.history_enabled {
+        return;
     }
+    append_history(app, settings, raw_text, processed_text);
+}
 
-    Ok(())
+fn append_history(app: &AppHandle, settings: &AppSettings, raw_text: &str, processed_text: &str) {
+    let entry = HistoryEntry {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        raw_text: raw_text.to_string(),
+        processed_text: processed_text.to_string(),
+        source_language: settings.spoken_language.clone(),
+        target_language: settings.translation_language.clone(),
+    };
+    if let Err(e) = HistoryStore::append(app, entry) {
+        DebugLogger::log_pipeline_error("history", &format!("Failed to save transcription history: {}", e));
+    }
 }
 
-// Command to cancel recording from dialog
-#[tauri::command]
-async fn cancel_recording(
-    app: AppHandle,
-) -> Result<(), String> {
-    DebugLogger::log_info("CANCEL_RECORDING: User cancelled recording start");
-    
-    // Hide confirmation window
-    if let Some(window) = app.get_webview_window("confirmation") {
-        let _ = window.hide();
+// Keep the final text for reinsert_last; empty results don't replace it
+fn remember_final_text(app: &AppHandle, text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    if let Some(last) = app.try_state::<LastFinalText>() {
+        if let Ok(mut last) = last.lock() {
+            *last = Some(text.to_string());
+        }
     }
-    
-    Ok(())
 }
 
 // Command to start recording
@@ -486,6 +1508,7 @@ async fn start_recording(
     audio_stop_sender: State<'_, AudioStopSender>,
     audio_manager: State<'_, AudioManagerHandle>,
     fsm: State<'_, HotkeySMState>,
+    cancel_flag: State<'_, CancelFlag>,
 
     spoken_language: String,
     translation_language: String,
@@ -497,16 +1520,76 @@ async fn start_recording(
     text_insertion_enabled: bool,
     audio_chunking_enabled: bool,
     max_recording_time_minutes: u32,
-    debug_logging: bool
-) -> Result<(), String> {
+    debug_logging: bool,
+    audio_device: Option<String>,
+    restore_clipboard: Option<bool>,
+    insertion_method: Option<String>,
+    auto_mute_mode: Option<String>,
+    duck_level: Option<u32>,
+    api_flavor: Option<String>,
+    upload_format: Option<String>,
+    response_format: Option<String>,
+    stt_prompt: Option<String>,
+    request_timeout_secs: Option<u32>,
+    max_retries: Option<u32>,
+    translation_prompt_template: Option<String>,
+    history_enabled: Option<bool>,
+    output_mode: Option<String>,
+    max_log_mb: Option<u32>,
+    log_format: Option<String>,
+    log_level: Option<String>,
+    noise_reduction_enabled: Option<bool>,
+    pre_roll_ms: Option<u32>,
+    max_upload_mb: Option<u32>,
+    trim_silence: Option<bool>,
+    max_silence_gap_ms: Option<u32>,
+    normalize_audio: Option<bool>,
+    mono_mixdown: Option<String>,
+    stt_temperature: Option<f32>,
+    translation_temperature: Option<f32>,
+    translation_max_tokens: Option<u32>,
+    require_api_key: Option<bool>,
+    correction_style: Option<String>,
+    offline_queue_enabled: Option<bool>,
+    model_overrides: Option<HashMap<String, crate::settings::ModelOverride>>,
+    local_formatting: Option<bool>,
+    paste_delay_ms: Option<u32>,
+    powershell_fallback: Option<bool>,
+    timeout_warning_secs: Option<u32>,
+    silence_threshold: Option<f32>,
+    min_duration_secs: Option<f32>,
+    custom_headers: Option<HashMap<String, String>>,
+    allow_auth_headers: Option<bool>,
+    preserve_clipboard: Option<bool>,
+    notifications: Option<String>,
+    capture_source: Option<String>,
+    use_detected_language: Option<bool>,
+    text_replacements: Option<Vec<text_format::TextReplacement>>,
+    wav_dump_retention: Option<u32>,
+    max_concurrent_requests: Option<u32>,
+    min_recording_ms: Option<u32>,
+    api_mode: Option<String>,
+    local_only: Option<bool>,
+    denoise_strength: Option<f32>,
+    spoken_punctuation: Option<bool>,
+    spoken_punctuation_words: Option<Vec<text_format::SpokenPunctuation>>,
+    insert_prefix: Option<String>,
+    insert_suffix: Option<String>,
+    dedup_window_tokens: Option<u32>,
+    chunk_overlap_ms: Option<u32>
+) -> Result<(), TalkToMeError> {
     // Check if already recording
     {
         let state = recording_state.inner().lock().map_err(|e| e.to_string())?;
         if *state {
             DebugLogger::log_info("start_recording called but already recording - rejecting duplicate start");
-            return Err("Already recording".to_string());
+            return Err(TalkToMeError::AlreadyRecording("Already recording".to_string()));
         }
     }
+    let mut failed_start_guard = FailedStartGuard(Some(fsm.inner().clone()));
+
+    // Anything the frontend didn't send comes from the saved (active profile) settings
+    let stored = SettingsStore::load(&app).unwrap_or_default();
 
     // Get API key (use default AppSettings instance for the method)
     DebugLogger::log_info("=== PIPELINE START: start_recording() called ===");
@@ -514,29 +1597,49 @@ async fn start_recording(
         spoken_language, translation_language, api_endpoint, stt_model, auto_mute, translation_enabled, text_insertion_enabled, audio_chunking_enabled, debug_logging));
     
     // Update debug logging state to match the frontend preference
+    let max_log_mb = max_log_mb.unwrap_or(stored.max_log_mb);
+    let wav_dump_retention = wav_dump_retention.unwrap_or(stored.wav_dump_retention);
+    let log_format = log_format.unwrap_or(stored.log_format);
+    let log_level = log_level.unwrap_or(stored.log_level);
+    DebugLogger::set_max_log_mb(max_log_mb);
+    DebugLogger::set_wav_dump_retention(wav_dump_retention);
+    DebugLogger::set_log_format(&log_format);
+    DebugLogger::set_min_level(LogLevel::from_setting(&log_level));
     DebugLogger::init_with_state(&app, debug_logging)?;
     DebugLogger::log_info(&format!("Debug logging state updated to: {}", debug_logging));
     
-    let settings_for_api = AppSettings::default();
-    let api_key = settings_for_api.get_api_key(&app).map_err(|e| {
-        let error_msg = format!("Failed to get API key: {}", e);
-        DebugLogger::log_pipeline_error("settings", &error_msg);
-        error_msg
+    let require_api_key = require_api_key.unwrap_or(stored.require_api_key);
+    let api_key = resolve_api_key(&app, &api_endpoint, require_api_key).map_err(|e| {
+        DebugLogger::log_pipeline_error("settings", &e.to_string());
+        e
     })?;
     DebugLogger::log_info(&format!("API key obtained, length: {} chars", api_key.len()));
+
+    // Fail fast instead of recording audio that can never be transcribed
+    validate_api_credentials(&api_endpoint, &api_key, require_api_key).map_err(|e| {
+        DebugLogger::log_pipeline_error("settings", &e.to_string());
+        e
+    })?;
+
+    let audio_device = audio_device.unwrap_or(stored.audio_device);
+    DebugLogger::log_info(&format!("Requested audio input device: {}", audio_device));
     
     // Create a settings struct for the processing pipeline
     let settings = AppSettings {
         spoken_language,
         translation_language,
-        audio_device: "default".to_string(), // Not used in recording
-        theme: "auto".to_string(), // Not used in recording
+        audio_device: audio_device.clone(),
+        theme: stored.theme, // Not used in recording
         auto_save: true, // Not used in recording
         api_endpoint,
         stt_model,
         translation_model: translation_model.clone(),
         hotkeys: crate::settings::Hotkeys {
             hands_free: "".to_string(), // Not used in recording
+            start_recording: None,
+            stop_recording: None,
+            cancel: None,
+            reinsert_last: None,
         },
         auto_mute,
         translation_enabled,
@@ -544,17 +1647,93 @@ async fn start_recording(
         text_insertion_enabled,
         audio_chunking_enabled,
         max_recording_time_minutes,
+        hotkey_mode: stored.hotkey_mode, // Not used in recording
+        hotkey_debounce_ms: stored.hotkey_debounce_ms, // Not used in recording
+        tap_threshold_ms: stored.tap_threshold_ms, // Not used in recording
+        restore_clipboard: restore_clipboard.unwrap_or(stored.restore_clipboard),
+        insertion_method: insertion_method.unwrap_or(stored.insertion_method),
+        // Older frontends only send the auto_mute flag; the stored mode says how to apply it
+        auto_mute_mode: auto_mute_mode.unwrap_or_else(|| match stored.auto_mute_mode.as_str() {
+            _ if !auto_mute => "off".to_string(),
+            "off" => "mute".to_string(),
+            mode => mode.to_string(),
+        }),
+        duck_level: duck_level.unwrap_or(stored.duck_level).min(100),
+        api_flavor: api_flavor.unwrap_or(stored.api_flavor),
+        upload_format: upload_format.unwrap_or(stored.upload_format),
+        response_format: response_format.unwrap_or(stored.response_format),
+        stt_prompt: stt_prompt.unwrap_or(stored.stt_prompt),
+        request_timeout_secs: request_timeout_secs.unwrap_or(stored.request_timeout_secs).clamp(5, 300),
+        max_retries: max_retries.unwrap_or(stored.max_retries).min(10),
+        translation_prompt_template: translation_prompt_template.unwrap_or(stored.translation_prompt_template),
+        history_enabled: history_enabled.unwrap_or(stored.history_enabled),
+        output_mode: output_mode.unwrap_or(stored.output_mode),
+        max_log_mb,
+        log_format,
+        log_level,
+        noise_reduction_enabled: noise_reduction_enabled.unwrap_or(stored.noise_reduction_enabled),
+        pre_roll_ms: pre_roll_ms.unwrap_or(stored.pre_roll_ms).min(2000),
+        max_upload_mb: max_upload_mb.unwrap_or(stored.max_upload_mb).clamp(1, 500),
+        trim_silence: trim_silence.unwrap_or(stored.trim_silence),
+        max_silence_gap_ms: max_silence_gap_ms.unwrap_or(stored.max_silence_gap_ms).min(10_000),
+        normalize_audio: normalize_audio.unwrap_or(stored.normalize_audio),
+        mono_mixdown: mono_mixdown.unwrap_or(stored.mono_mixdown),
+        stt_temperature: stt_temperature.unwrap_or(stored.stt_temperature).clamp(0.0, 1.0),
+        translation_temperature: translation_temperature.unwrap_or(stored.translation_temperature).clamp(0.0, 2.0),
+        translation_max_tokens: translation_max_tokens.unwrap_or(stored.translation_max_tokens).clamp(1, 32_000),
+        require_api_key,
+        correction_style: correction_style.unwrap_or(stored.correction_style),
+        offline_queue_enabled: offline_queue_enabled.unwrap_or(stored.offline_queue_enabled),
+        model_overrides: model_overrides.unwrap_or(stored.model_overrides),
+        local_formatting: local_formatting.unwrap_or(stored.local_formatting),
+        paste_delay_ms: paste_delay_ms.unwrap_or(stored.paste_delay_ms).min(2000),
+        powershell_fallback: powershell_fallback.unwrap_or(stored.powershell_fallback),
+        timeout_warning_secs: timeout_warning_secs.unwrap_or(stored.timeout_warning_secs),
+        silence_threshold: silence_threshold.unwrap_or(stored.silence_threshold).clamp(0.0, 1.0),
+        min_duration_secs: min_duration_secs.unwrap_or(stored.min_duration_secs).clamp(0.0, 10.0),
+        validate_api_key_on_startup: stored.validate_api_key_on_startup, // Startup-only
+        custom_headers: custom_headers.unwrap_or(stored.custom_headers),
+        allow_auth_headers: allow_auth_headers.unwrap_or(stored.allow_auth_headers),
+        preserve_clipboard: preserve_clipboard.unwrap_or(stored.preserve_clipboard),
+        notifications: notifications.unwrap_or(stored.notifications),
+        capture_source: capture_source.unwrap_or(stored.capture_source),
+        use_detected_language: use_detected_language.unwrap_or(stored.use_detected_language),
+        text_replacements: text_replacements.unwrap_or(stored.text_replacements),
+        wav_dump_retention,
+        max_concurrent_requests: max_concurrent_requests.unwrap_or(stored.max_concurrent_requests).clamp(1, 8),
+        min_recording_ms: min_recording_ms.unwrap_or(stored.min_recording_ms).min(5000),
+        api_mode: api_mode.unwrap_or(stored.api_mode),
+        local_only: crate::api_flavor::effective_local_only(local_only, stored.local_only),
+        denoise_strength: denoise_strength.unwrap_or(stored.denoise_strength).clamp(0.0, 1.0),
+        spoken_punctuation: spoken_punctuation.unwrap_or(stored.spoken_punctuation),
+        spoken_punctuation_words: spoken_punctuation_words.unwrap_or(stored.spoken_punctuation_words),
+        insert_prefix: insert_prefix.unwrap_or(stored.insert_prefix),
+        insert_suffix: insert_suffix.unwrap_or(stored.insert_suffix),
+        dedup_window_tokens: dedup_window_tokens.unwrap_or(stored.dedup_window_tokens).min(50),
+        chunk_overlap_ms: chunk_overlap_ms.unwrap_or(stored.chunk_overlap_ms).min(5000),
     };
+    retry::set_max_concurrent_requests(settings.max_concurrent_requests);
+
+    // Loopback capture doesn't go through the microphone permission
+    if crate::audio::CaptureSource::from_setting(&settings.capture_source) != crate::audio::CaptureSource::SystemLoopback {
+        check_mic_permission(&app)?;
+    }
     
+    // Clear any cancellation left over from the previous session
+    cancel_flag.store(false, Ordering::SeqCst);
+
     // Request the audio manager (single-thread owner) to start capture and return the receiver
     DebugLogger::log_info("Requesting audio manager to start capture");
     let (reply_tx, reply_rx) = std_mpsc::channel();
+    // Bounded so a slow frontend can never back up the audio callback; stale levels are dropped
+    let (level_tx, level_rx) = std_mpsc::sync_channel::<f32>(8);
+    let (device_tx, device_rx) = std_mpsc::channel::<crate::audio::DeviceEvent>();
     {
         let sender = audio_manager.lock().map_err(|e| e.to_string())?;
-        sender.send(AudioManagerCommand::Start { reply: reply_tx, audio_chunking_enabled }).map_err(|e| {
-            let msg = format!("Failed to send start command to audio manager: {}", e);
+        sender.send(AudioManagerCommand::Start { reply: reply_tx, audio_chunking_enabled, noise_reduction_enabled: settings.noise_reduction_enabled, denoise_strength: settings.denoise_strength, silence_trim: settings.trim_silence.then_some(crate::audio::SilenceTrim { max_gap_ms: settings.max_silence_gap_ms }), mono_mixdown: crate::audio::MonoMixdown::from_setting(&settings.mono_mixdown), capture_source: crate::audio::CaptureSource::from_setting(&settings.capture_source), audio_device, level_tx: Some(level_tx), device_tx: Some(device_tx), pre_roll_ms: settings.pre_roll_ms }).map_err(|e| {
+            let msg = format!("Failed to send start command to audio manager: {}{}", e, audio_manager_hint());
             DebugLogger::log_pipeline_error("audio_manager", &msg);
-            msg
+            TalkToMeError::AudioError(msg)
         })?;
     }
     // Wait for manager to reply with the audio receiver
@@ -562,20 +1741,50 @@ async fn start_recording(
     Ok(Ok(rx)) => rx,
     Ok(Err(e)) => {
             DebugLogger::log_pipeline_error("audio_manager", &e);
-            return Err(e);
+            return Err(TalkToMeError::AudioError(e));
         }
         Err(e) => {
-            let msg = format!("Timed out waiting for audio manager start reply: {}", e);
+            let msg = format!("Timed out waiting for audio manager start reply: {}{}", e, audio_manager_hint());
             DebugLogger::log_pipeline_error("audio_manager", &msg);
-            return Err(msg);
+            return Err(TalkToMeError::AudioError(msg));
         }
     };
     DebugLogger::log_info("Audio capture started successfully (owned by audio manager thread)");
+
+    // Forward device loss/rebind notifications; ends when the capture is dropped
+    let app_for_devices = app.clone();
+    std::thread::spawn(move || {
+        for event in device_rx.iter() {
+            match event {
+                crate::audio::DeviceEvent::Rebound(device) => {
+                    let _ = app_for_devices.emit("device-changed", serde_json::json!({ "device": device }));
+                }
+                crate::audio::DeviceEvent::Lost(reason) => {
+                    // Distinct from a normal stop: the audio captured so far is still transcribed
+                    let _ = app_for_devices.emit("processing-error", format!("Microphone disconnected - recording stopped early: {}", reason));
+                }
+            }
+        }
+    });
+
+    // Forward microphone levels to the frontend; ends when the capture stream is dropped
+    let app_for_levels = app.clone();
+    std::thread::spawn(move || {
+        for level in level_rx.iter() {
+            let _ = app_for_levels.emit("audio-level", level);
+        }
+        DebugLogger::log_info("Audio level forwarder exiting (capture stopped)");
+    });
     
     // Track recording start time for timeout monitoring
     let recording_start_time = std::time::Instant::now();
     let max_recording_duration = std::time::Duration::from_secs((max_recording_time_minutes as u64) * 60);
     DebugLogger::log_info(&format!("Recording timeout set to {} minutes", max_recording_time_minutes));
+    // No warning when it's disabled or the whole limit is shorter than the warning period
+    let timeout_warning_at = (settings.timeout_warning_secs > 0)
+        .then(|| max_recording_duration.checked_sub(std::time::Duration::from_secs(settings.timeout_warning_secs as u64)))
+        .flatten()
+        .filter(|at| !at.is_zero());
     
     // Set recording state to true
     {
@@ -583,22 +1792,20 @@ async fn start_recording(
         *state = true;
         DebugLogger::log_info("RECORDING_STATE_CHANGE: Set to true in start_recording (recording started)");
     }
+    failed_start_guard.disarm();
 
     // Update FSM to Recording state
     fsm.force_set_state(hotkey_fsm::RecordingState::Recording)
-        .unwrap_or_else(|e| DebugLogger::log_info(&format!("Failed to set FSM to Recording: {}", e)));
+        .unwrap_or_else(|e| DebugLogger::log_warn(&format!("Failed to set FSM to Recording: {}", e)));
 
     // Show "Recording Started" notification
     DebugLogger::log_info("Showing recording started notification");
-    let _ = app.notification()
-        .builder()
-        .title("Recording Started")
-        .body("🎤 Listening for speech...")
-        .show();
+    let _ = notify(&app, &settings.notifications, false, "Recording Started", "🎤 Listening for speech...");
 
     // Emit recording-started event to frontend to ensure state synchronization
     DebugLogger::log_info("Emitting recording-started event to frontend");
     let _ = app.emit("recording-started", ());
+    emit_pipeline_status(&app, PipelineStage::Capturing, None);
 
     // Create stop channel for proper audio cleanup
     let (stop_tx, stop_rx) = std::sync::mpsc::channel();
@@ -614,26 +1821,28 @@ async fn start_recording(
     
     // Create services with API key
     DebugLogger::log_info("Creating STT service");
-    let stt_service = STTService::new(
-        settings.api_endpoint.clone(),
-        api_key.clone(),
-        settings.stt_model.clone(),
-        settings.spoken_language.clone(),
-    );
-    DebugLogger::log_info(&format!("STT service created with endpoint: {} and model: {}", settings.api_endpoint, settings.stt_model));
+    let stt_service = stt_service_for(&settings, api_key.clone());
+    DebugLogger::log_info(&format!("STT service created with endpoint: {} and model: {}", settings.api_endpoint, settings.effective_stt_model()));
     
     let translation_service = if settings.translation_enabled && settings.translation_language != "none" {
         DebugLogger::log_info("Creating translation service (translation enabled)");
-        Some(TranslationService::new(settings.api_endpoint.clone(), api_key, settings.translation_model.clone()))
+        Some(translation_service_for(&settings, api_key))
     } else {
         // Always create translation service for text correction
         DebugLogger::log_info("Creating translation service (text correction only)");
-        Some(TranslationService::new(settings.api_endpoint.clone(), api_key, settings.translation_model.clone()))
+        Some(translation_service_for(&settings, api_key))
     };
     DebugLogger::log_info("Translation service created");
     
     DebugLogger::log_info("Creating text insertion service");
-    let text_insertion_service = std::sync::Arc::new(TextInsertionService::new());
+    let text_insertion_service = std::sync::Arc::new(TextInsertionService::new(
+        settings.restore_clipboard,
+        settings.preserve_clipboard,
+        InsertionMethod::from_setting(&settings.insertion_method),
+        OutputMode::from_setting(&settings.output_mode),
+        settings.paste_delay_ms,
+        settings.powershell_fallback,
+    ));
     DebugLogger::log_info("Text insertion service created");
     // Create a non-blocking background worker for text insertion so the audio
     // pipeline never blocks on platform typing utilities (PowerShell/xdotool/etc.).
@@ -645,44 +1854,85 @@ async fn start_recording(
     // using spawn_blocking so it doesn't block the Tokio runtime.
     let text_insertion_service_for_worker = text_insertion_service.clone();
     let insertion_ctrl_tx_for_worker = insertion_ctrl_tx.clone();
+    let app_for_worker = app.clone();
+    let notifications_for_worker = settings.notifications.clone();
+    let worker_guard = PipelineTaskGuard::new();
     tokio::spawn(async move {
+        let _worker_guard = worker_guard;
         DebugLogger::log_info("TEXT_INSERTION_WORKER: started");
         while let Some(text) = text_insertion_rx.recv().await {
             DebugLogger::log_info(&format!("TEXT_INSERTION_WORKER: received text (len={}) to insert", text.len()));
             // Signal insertion start
             let _ = insertion_ctrl_tx_for_worker.send(true);
+            emit_pipeline_status(&app_for_worker, PipelineStage::Inserting, None);
             
             let svc = text_insertion_service_for_worker.clone();
             let t = text.clone();
             // Run the platform Command in a blocking thread pool
             let res = tokio::task::spawn_blocking(move || svc.insert_text(&t)).await;
             match res {
-                Ok(Ok(())) => DebugLogger::log_info("TEXT_INSERTION_WORKER: insertion succeeded"),
-                Ok(Err(e)) => DebugLogger::log_pipeline_error("text_insertion_worker", &format!("insertion error: {}", e)),
+                Ok(Ok(InsertionOutcome::CopiedWithoutAccessibility)) => {
+                    report_accessibility_needed(&app_for_worker);
+                    let _ = notify(&app_for_worker, &notifications_for_worker, true, "Text not inserted", ACCESSIBILITY_NEEDED_MESSAGE);
+                    emit_pipeline_status(&app_for_worker, PipelineStage::Error, Some(ACCESSIBILITY_NEEDED_MESSAGE));
+                }
+                Ok(Ok(outcome)) => {
+                    DebugLogger::log_info("TEXT_INSERTION_WORKER: insertion succeeded");
+                    if outcome == InsertionOutcome::TypedToPreserveClipboard {
+                        let _ = notify(&app_for_worker, &notifications_for_worker, false, "TalkToMe", "Clipboard kept — text was typed instead of pasted");
+                    }
+                    if text_insertion_service_for_worker.output_mode() == OutputMode::ClipboardOnly {
+                        let paste_hint = if cfg!(target_os = "macos") { "Cmd+V" } else { "Ctrl+V" };
+                        let _ = notify(&app_for_worker, &notifications_for_worker, false, "TalkToMe", &format!("Text copied — press {}", paste_hint));
+                    }
+                }
+                Ok(Err(e)) => {
+                    DebugLogger::log_pipeline_error("text_insertion_worker", &format!("insertion error: {}", e));
+                    let _ = notify(&app_for_worker, &notifications_for_worker, true, "Text insertion failed", &e);
+                    emit_pipeline_status(&app_for_worker, PipelineStage::Error, Some(&e));
+                }
                 Err(e) => DebugLogger::log_pipeline_error("text_insertion_worker", &format!("spawn_blocking failed: {}", e)),
             }
             // Signal insertion complete
             let _ = insertion_ctrl_tx_for_worker.send(false);
         }
         DebugLogger::log_info("TEXT_INSERTION_WORKER: exiting (sender closed)");
+        // The pipeline drops its sender when it finishes, so this follows every other stage
+        emit_pipeline_status(&app_for_worker, PipelineStage::Done, None);
     });
     
     // Clone values for the async task
     let app_clone = app.clone();
     let recording_state_clone = recording_state.inner().clone();
-    let auto_mute = settings.auto_mute;
+    let cancel_flag_clone = cancel_flag.inner().clone();
+    let auto_mute_mode = settings.auto_mute_mode.clone();
+    let duck_level = settings.duck_level;
     
     // Spawn task to process audio chunks and monitor stop signal
+    let pipeline_guard = PipelineTaskGuard::new();
     tokio::spawn(async move {
+        let _pipeline_guard = pipeline_guard;
         // Create system audio control inside the task for auto-mute if enabled
-        DebugLogger::log_info(&format!("Auto-mute setting: {}", auto_mute));
-        let audio_control = if auto_mute {
+        DebugLogger::log_info(&format!("Auto-mute mode: {} (duck level {}%)", auto_mute_mode, duck_level));
+        let auto_mute_requested = auto_mute_mode == "mute" || auto_mute_mode == "duck";
+        let audio_control = if auto_mute_requested && !SystemAudioControl::is_supported() {
+            // No backend here: leave system audio alone rather than report an error on every recording
+            DebugLogger::log_info("Auto-mute is not supported on this system, leaving system audio unchanged");
+            None
+        } else if auto_mute_requested {
             DebugLogger::log_info("Attempting to create system audio control for auto-mute");
             match SystemAudioControl::new() {
                 Ok(control) => {
                     DebugLogger::log_info("System audio control created successfully");
-                    // Mute system audio
-                    if let Err(e) = control.mute_system_audio() {
+                    if auto_mute_mode == "duck" {
+                        // Lower system volume instead of muting it
+                        if let Err(e) = control.duck_system_audio(duck_level as f32 / 100.0) {
+                            let error_msg = format!("Failed to duck system audio: {}", e);
+                            DebugLogger::log_pipeline_error("system_audio", &error_msg);
+                        } else {
+                            DebugLogger::log_info("System audio ducked successfully");
+                        }
+                    } else if let Err(e) = control.mute_system_audio() {
                         let error_msg = format!("Failed to mute system audio: {}", e);
                         eprintln!("{}", error_msg);
                         DebugLogger::log_pipeline_error("system_audio", &error_msg);
@@ -714,6 +1964,9 @@ async fn start_recording(
         
         DebugLogger::log_info("About to enter audio processing pipeline");
         DebugLogger::log_info(&format!("Audio chunking mode: {}", if settings.audio_chunking_enabled { "ENABLED (real-time chunks)" } else { "DISABLED (single recording)" }));
+        // Only counts as translated when the text was actually sent for translation (not just correction)
+        let translating = settings.translation_enabled && settings.translation_language != "none";
+        let mut stats = SessionStats::default();
         
         if settings.audio_chunking_enabled {
             // === CHUNKED MODE: Real-time processing ===
@@ -725,51 +1978,67 @@ async fn start_recording(
             // Aggregation state: accumulate text until recording stops
             use std::time::Duration;
             let mut agg_text = String::new();
+            // First language the STT service reported, used for the final prompt
+            let mut detected_language: Option<String> = None;
+            // Reported if the session ends without text; silent chunks never reach STT at all
+            let mut empty_reason = Some(EmptyReason::TooQuiet);
 
-            fn append_dedup(agg: &mut String, next: &str) {
-                // Token-aware suffix/prefix dedup: use last up to 12 chars as heuristic
-                let take = agg.chars().rev().take(12).collect::<String>();
-                let tail: String = take.chars().rev().collect();
-                if !tail.is_empty() && next.starts_with(&tail) {
-                    agg.push_str(&next[tail.len()..]);
-                } else {
-                    if !agg.is_empty() { agg.push(' '); }
-                    agg.push_str(next);
-                }
-            }
+            // Once a stop is requested we keep receiving for a short while so the trailing
+            // speech segment flushed by the capture thread still gets transcribed
+            let mut drain_deadline: Option<std::time::Instant> = None;
+            let drain_window = Duration::from_millis(2000);
+            let mut timeout_warning_sent = false;
 
             // Process audio chunks with timeout to detect stop/idle
             loop {
                 use std::sync::mpsc::RecvTimeoutError;
+
+            if cancel_flag_clone.load(Ordering::SeqCst) {
+                DebugLogger::log_info("STOP_REASON: Recording cancelled, discarding remaining chunks");
+                break;
+            }
             
             // Check stop signal first
-            match stop_rx.try_recv() {
-                Ok(_) => {
-                    DebugLogger::log_info("STOP_REASON: Stop signal received manually, breaking processing loop");
-                    break;
-                }
-                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
-                    DebugLogger::log_info("STOP_REASON: Stop signal channel disconnected (audio system failure), breaking processing loop");
-                    break;
-                }
-                Err(std::sync::mpsc::TryRecvError::Empty) => {
-                    // No stop signal, continue processing
+            if drain_deadline.is_none() {
+                match stop_rx.try_recv() {
+                    Ok(_) => {
+                        DebugLogger::log_info("STOP_REASON: Stop signal received manually, draining remaining chunks");
+                        drain_deadline = Some(std::time::Instant::now() + drain_window);
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                        DebugLogger::log_info("STOP_REASON: Stop signal channel disconnected (audio system failure), draining remaining chunks");
+                        drain_deadline = Some(std::time::Instant::now() + drain_window);
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Empty) => {
+                        // No stop signal, continue processing
+                    }
                 }
             }
             
             let audio_chunk = match audio_rx.recv_timeout(Duration::from_millis(200)) {
                 Ok(chunk) => chunk,
                 Err(RecvTimeoutError::Timeout) => {
+                    if let Some(deadline) = drain_deadline {
+                        if std::time::Instant::now() >= deadline {
+                            DebugLogger::log_info("DRAIN_PHASE: Drain window elapsed, breaking processing loop");
+                            break;
+                        }
+                        continue;
+                    }
+
                     // Periodically check for stop
                     let stop = {
                         let state = recording_state_clone.lock().unwrap();
                         !*state
                     };
                     if stop {
-                        DebugLogger::log_info("STOP_REASON: Recording state set to false (timeout check), breaking processing loop");
-                        break;
+                        DebugLogger::log_info("STOP_REASON: Recording state set to false (timeout check), draining remaining chunks");
+                        drain_deadline = Some(std::time::Instant::now() + drain_window);
+                        continue;
                     }
                     
+                    emit_timeout_warning_once(&app, recording_start_time.elapsed(), timeout_warning_at, max_recording_duration, &mut timeout_warning_sent);
+
                     // Check if recording has exceeded max time limit
                     if recording_start_time.elapsed() >= max_recording_duration {
                         DebugLogger::log_info(&format!("STOP_REASON: Recording exceeded maximum time limit of {} minutes", max_recording_time_minutes));
@@ -783,28 +2052,24 @@ async fn start_recording(
                         // Emit timeout notification to frontend
                         let _ = app.emit("recording-timeout", ());
                         
-                        break;
+                        drain_deadline = Some(std::time::Instant::now() + drain_window);
+                        continue;
                     }
                     
                     // Continue waiting for more audio
                     continue;
                 }
                 Err(RecvTimeoutError::Disconnected) => {
-                    DebugLogger::log_info("STOP_REASON: Audio channel disconnected (audio device/system failure), breaking processing loop");
+                    if drain_deadline.is_some() {
+                        DebugLogger::log_info("DRAIN_PHASE: Audio channel closed after final segment, breaking processing loop");
+                    } else {
+                        DebugLogger::log_info("STOP_REASON: Audio channel disconnected (audio device/system failure), breaking processing loop");
+                    }
                     break;
                 }
             };
             DebugLogger::log_info("=== NEW AUDIO CHUNK RECEIVED ===");
             
-            // Check if recording has been stopped
-            {
-                let state = recording_state_clone.lock().unwrap();
-                if !*state {
-                    DebugLogger::log_info("STOP_REASON: Recording state set to false (chunk processing check), breaking audio processing loop");
-                    break;
-                }
-            }
-            
             // Handle all audio chunks the same way in simplified mode
             // No special handling for silence chunks needed
             
@@ -812,6 +2077,7 @@ async fn start_recording(
             let max_amplitude = audio_chunk.data.iter().map(|&x| x.abs()).fold(0.0, f32::max);
             let has_activity = audio_chunk.has_audio_activity();
             DebugLogger::log_audio_chunk(audio_chunk.data.len(), audio_chunk.sample_rate, has_activity, max_amplitude);
+            stats.add_audio(audio_chunk.data.len(), audio_chunk.sample_rate);
 
             // Skip empty or silent chunks
             if audio_chunk.is_empty() || !has_activity {
@@ -821,14 +2087,27 @@ async fn start_recording(
 
             // Emit status to frontend
             let _ = app.emit("processing-audio", true);
+            emit_pipeline_status(&app, PipelineStage::Transcribing, None);
 
             // Transcribe audio chunk
             DebugLogger::log_info("=== STARTING STT TRANSCRIPTION ===");
-            match stt_service.transcribe_chunk(audio_chunk.data, audio_chunk.sample_rate, None).await {
+            let stt_started = std::time::Instant::now();
+            let chunk_start_secs = audio_chunk.start_secs;
+            let result = stt_service.transcribe_chunk_detailed(audio_chunk.data, audio_chunk.sample_rate, None).await;
+            stats.add_stt(stt_started, result.is_ok());
+            match result.map(|mut t| {
+                t.offset_by(chunk_start_secs);
+                emit_transcription_segments(&app, &t);
+                if let Some(reason) = t.empty_reason() {
+                    empty_reason = Some(reason);
+                }
+                detected_language = detected_language.take().or(t.language);
+                apply_spoken_punctuation(settings.spoken_punctuation, &settings.spoken_punctuation_words, t.text)
+            }) {
                 Ok(transcribed_text) => {
                     DebugLogger::log_transcription_response(true, Some(&transcribed_text), None);
                     if !transcribed_text.trim().is_empty() {
-                        append_dedup(&mut agg_text, &transcribed_text);
+                        text_format::append_dedup(&mut agg_text, &transcribed_text, settings.dedup_window_tokens as usize);
                         DebugLogger::log_info(&format!("Aggregated text length now: {}", agg_text.len()));
                         
                         // Store transcribed text but don't insert yet - wait for user to stop recording
@@ -847,6 +2126,9 @@ async fn start_recording(
                     DebugLogger::log_pipeline_error("transcription", &e);
                     let _ = app.emit("processing-error", format!("Transcription error: {}", e));
                     let _ = app.emit("processing-audio", false);
+                    emit_pipeline_status(&app, PipelineStage::Error, Some(&e));
+                    // The error was already reported; don't also claim there was no speech
+                    empty_reason = None;
                 }
             }
     }
@@ -868,6 +2150,15 @@ async fn start_recording(
                 } else {
                     DebugLogger::log_info("System audio unmuted successfully during cleanup");
                 }
+            } else if audio_control.is_ducked() {
+                DebugLogger::log_info("Attempting to restore system audio volume during cleanup");
+                if let Err(e) = audio_control.restore_system_audio() {
+                    let error_msg = format!("Failed to restore system audio volume during cleanup: {}", e);
+                    eprintln!("{}", error_msg);
+                    DebugLogger::log_pipeline_error("system_audio_cleanup", &error_msg);
+                } else {
+                    DebugLogger::log_info("System audio volume restored successfully during cleanup");
+                }
             } else {
                 DebugLogger::log_info("System audio was not muted, no cleanup needed");
             }
@@ -875,36 +2166,52 @@ async fn start_recording(
             DebugLogger::log_info("No system audio control to clean up");
         }
         
+        discard_if_too_short(&app, recording_start_time, settings.min_recording_ms, &cancel_flag_clone);
+
         // Final flush - process and insert text when recording stops
-        if !agg_text.trim().is_empty() {
+        if cancel_flag_clone.load(Ordering::SeqCst) {
+            DebugLogger::log_info("Recording cancelled - skipping final processing and insertion");
+        } else if !agg_text.trim().is_empty() {
             let raw_text = agg_text.clone();
             DebugLogger::log_info("TEXT_INSERTION: processing final text after recording stopped");
-            let final_text = if let Some(ref translation_service) = translation_service {
-                match translation_service.process_text(
+            let source_language = resolve_source_language(&app, &settings, detected_language.as_deref());
+            let (final_text, corrected) = if let Some(ref translation_service) = translation_service {
+                emit_pipeline_status(&app, PipelineStage::Translating, None);
+                let translation_started = std::time::Instant::now();
+                let result = translation_service.process_text(
                     &agg_text,
-                    &settings.spoken_language,
+                    &source_language,
                     &settings.translation_language,
                     settings.translation_enabled
-                ).await {
+                ).await;
+                stats.add_translation(translation_started);
+                match result {
                     Ok(processed_text) => {
                         DebugLogger::log_translation_response(true, Some(&processed_text), None, None);
-                        processed_text
+                        stats.translation_ran = translating;
+                        (processed_text, translation_service.corrects_text())
                     },
                     Err(e) => {
                         DebugLogger::log_translation_response(false, None, Some(&e), None);
                         DebugLogger::log_pipeline_error("translation", &e);
                         let _ = app.emit("processing-error", format!("Translation Error - Using fallback: {}", e));
-                        agg_text.clone()
+                        (agg_text.clone(), false)
                     }
                 }
             } else {
-                agg_text.clone()
+                (agg_text.clone(), false)
             };
+            let final_text = finalize_text(&settings, final_text, corrected);
             
+            record_history(&app, &settings, &raw_text, &final_text);
+            remember_final_text(&app, &final_text);
+            stats.final_text = final_text.clone();
+
             // Now insert the text since recording has stopped
             DebugLogger::log_info("TEXT_INSERTION: queueing text for insertion (recording stopped)");
             if settings.text_insertion_enabled {
-                if let Err(e) = text_insertion_tx.send(final_text.clone()) {
+                let insertion = text_format::wrap_for_insertion(&final_text, &settings.insert_prefix, &settings.insert_suffix);
+                if let Err(e) = text_insertion_tx.send(insertion) {
                     DebugLogger::log_pipeline_error("text_insertion", &format!("failed to queue text (final flush): {}", e));
                 } else {
                     DebugLogger::log_text_insertion(&final_text, true, None);
@@ -919,6 +2226,8 @@ async fn start_recording(
                 "raw": raw_text,
                 "final": final_text
             }));
+        } else if let Some(reason) = empty_reason {
+            emit_transcription_empty(&app, reason);
         }
 
         } else {
@@ -932,16 +2241,31 @@ async fn start_recording(
             let app_single = app.clone();
             let stop_rx_single = stop_rx;
             let recording_state_single = recording_state_clone.clone();
-            let stt_service_single = stt_service;
+            let upload_app = app.clone();
+            let last_upload_percent = AtomicU64::new(u64::MAX);
+            let stt_service_single = stt_service.with_upload_progress(move |sent, total| {
+                // One event per percent; a retry starts over from 0
+                let percent = (sent * 100).checked_div(total).unwrap_or(100);
+                if last_upload_percent.swap(percent, Ordering::Relaxed) != percent {
+                    let _ = upload_app.emit("upload-progress", serde_json::json!({
+                        "sent": sent,
+                        "total": total,
+                        "percent": percent
+                    }));
+                }
+            });
             let translation_service_single = translation_service;
             let settings_single = settings.clone();
             let text_insertion_tx_single = text_insertion_tx.clone();
+            let cancel_flag_single = cancel_flag_clone.clone();
+            let stats_single = &mut stats;
             
             // Run single recording session inline and await completion so the outer pipeline
             // does not proceed to cleanup while the single-recording task is still active.
             (async move {
                 let mut all_audio_data: Vec<f32> = Vec::new();
                 let mut sample_rate = 48000; // Default sample rate, will be updated from first chunk
+                let mut timeout_warning_sent = false;
                 
                 // Collect all audio data until recording stops
                 loop {
@@ -1030,6 +2354,8 @@ async fn start_recording(
                                 break;
                             }
                             
+                            emit_timeout_warning_once(&app_single, recording_start_time.elapsed(), timeout_warning_at, max_recording_duration, &mut timeout_warning_sent);
+
                             // Check if recording has exceeded max time limit
                             if recording_start_time.elapsed() >= max_recording_duration {
                                 DebugLogger::log_info(&format!("STOP_REASON: Single recording exceeded maximum time limit of {} minutes", max_recording_time_minutes));
@@ -1073,16 +2399,33 @@ async fn start_recording(
                     }
                 }
                 
+                discard_if_too_short(&app_single, recording_start_time, settings_single.min_recording_ms, &cancel_flag_single);
+
                 // Process the complete audio recording
-                if !all_audio_data.is_empty() {
+                if cancel_flag_single.load(Ordering::SeqCst) {
+                    DebugLogger::log_info("Single recording session cancelled - discarding audio without transcribing");
+                } else if !all_audio_data.is_empty() {
                     DebugLogger::log_info(&format!("Single recording complete: {} samples ({:.1}s) at {}Hz", 
                         all_audio_data.len(), all_audio_data.len() as f32 / sample_rate as f32, sample_rate));
+                    stats_single.add_audio(all_audio_data.len(), sample_rate);
                     
                     // Convert to WAV format and send to STT service
                     DebugLogger::log_info("Sending complete recording to STT service...");
+                    let _ = app_single.emit("processing-audio", true);
+                    emit_pipeline_status(&app_single, PipelineStage::Transcribing, None);
                     
-                    match stt_service_single.transcribe_chunk(all_audio_data, sample_rate, Some("stt_single")).await {
+                    let stt_started = std::time::Instant::now();
+                    let stt_result = transcribe_with_progress(&app_single, &stt_service_single, &all_audio_data, sample_rate, &settings_single).await;
+                    stats_single.add_stt(stt_started, stt_result.is_ok());
+                    match stt_result {
                             Ok(transcription) => {
+                                // A fresh recording made it through; the old failure is no longer worth retrying
+                                if let Ok(mut last_failed) = app_single.state::<LastFailedRecording>().lock() {
+                                    *last_failed = None;
+                                }
+                                let empty_reason = transcription.empty_reason();
+                                let detected_language = transcription.language;
+                                let transcription = apply_spoken_punctuation(settings_single.spoken_punctuation, &settings_single.spoken_punctuation_words, transcription.text);
                                 DebugLogger::log_info(&format!("STT complete transcription: '{}'", transcription));
                         // IMMEDIATELY emit raw transcription to frontend (don't wait for translation)
                                 let _ = app_single.emit("transcribed-text", serde_json::json!({
@@ -1103,6 +2446,9 @@ async fn start_recording(
                                     
                                     // Clear processing status
                                     let _ = app_single.emit("processing-status", serde_json::json!({"status": ""}));
+                                    if let Some(reason) = empty_reason {
+                                        emit_transcription_empty(&app_single, reason);
+                                    }
                                     
                                     // Skip text insertion
                                     DebugLogger::log_info("TEXT_INSERTION: skipped (empty transcription)");
@@ -1111,15 +2457,22 @@ async fn start_recording(
                                     let _ = app_single.emit("processing-status", serde_json::json!({"status": "translating"}));
 
                                     // Now do translation/correction in background and emit update when done
+                                    let source_language = resolve_source_language(&app_single, &settings_single, detected_language.as_deref());
                                     let final_text = if let Some(ref translation_service) = translation_service_single {
-                                        match translation_service.process_text(
+                                        emit_pipeline_status(&app_single, PipelineStage::Translating, None);
+                                        let translation_started = std::time::Instant::now();
+                                        let result = translation_service.process_text(
                                             &transcription,
-                                            &settings_single.spoken_language,
+                                            &source_language,
                                             &settings_single.translation_language,
                                             settings_single.translation_enabled
-                                        ).await {
+                                        ).await;
+                                        stats_single.add_translation(translation_started);
+                                        match result {
                                             Ok(processed_text) => {
                                                 DebugLogger::log_translation_response(true, Some(&processed_text), None, None);
+                                                stats_single.translation_ran = translating;
+                                                let processed_text = finalize_text(&settings_single, processed_text, translation_service.corrects_text());
 
                                                 // EMIT FINAL PROCESSED TEXT
                                                 let _ = app_single.emit("transcribed-text", serde_json::json!({
@@ -1136,33 +2489,40 @@ async fn start_recording(
                                                 let _ = app_single.emit("processing-error", format!("Translation Error - Using fallback: {}", e));
 
                                                 // FALLBACK: Use raw transcription as final (don't leave empty)
+                                                let fallback = finalize_text(&settings_single, transcription.clone(), false);
                                                 let _ = app_single.emit("transcribed-text", serde_json::json!({
                                                     "raw": transcription,
-                                                    "final": transcription // Use raw as fallback
+                                                    "final": fallback // Use raw as fallback
                                                 }));
                                                 DebugLogger::log_info("EMIT: Sent raw transcription as fallback final text");
 
-                                                transcription.clone()
+                                                fallback
                                             }
                                         }
                                     } else {
                                         // No translation service - just send raw transcription as final
+                                        let final_text = finalize_text(&settings_single, transcription.clone(), false);
                                         let _ = app_single.emit("transcribed-text", serde_json::json!({
                                             "raw": transcription,
-                                            "final": transcription
+                                            "final": final_text
                                         }));
                                         DebugLogger::log_info("EMIT: Sent raw transcription as final (no translation service)");
 
-                                        transcription.clone()
+                                        final_text
                                     };
 
                                     // CLEAR PROCESSING STATUS after completion
                                     let _ = app_single.emit("processing-status", serde_json::json!({"status": ""}));
+
+                                    record_history(&app_single, &settings_single, &transcription, &final_text);
+                                    remember_final_text(&app_single, &final_text);
+                                    stats_single.final_text = final_text.clone();
                                     
                                     // In single recording mode, the recording has already stopped, so insert text
                                     if settings_single.text_insertion_enabled {
                                         DebugLogger::log_info("TEXT_INSERTION: queueing complete transcription for insertion (single mode - recording already stopped)");
-                                        if let Err(e) = text_insertion_tx_single.send(final_text.clone()) {
+                                        let insertion = text_format::wrap_for_insertion(&final_text, &settings_single.insert_prefix, &settings_single.insert_suffix);
+                                        if let Err(e) = text_insertion_tx_single.send(insertion) {
                                             DebugLogger::log_pipeline_error("text_insertion", &format!("failed to queue complete transcription: {}", e));
                                         } else {
                                             DebugLogger::log_text_insertion(&final_text, true, None);
@@ -1177,7 +2537,34 @@ async fn start_recording(
                             },
                             Err(e) => {
                                 DebugLogger::log_pipeline_error("stt", &format!("STT processing failed: {}", e));
-                                let _ = app_single.emit("processing-error", format!("STT Error: {}", e));
+                                let queue_id = if settings_single.offline_queue_enabled {
+                                    match OfflineQueue::enqueue(&app_single, &all_audio_data, sample_rate, &settings_single, &e) {
+                                        Ok(entry) => {
+                                            DebugLogger::log_info(&format!("Queued failed recording {} for retry", entry.id));
+                                            Some(entry.id)
+                                        }
+                                        Err(queue_err) => {
+                                            DebugLogger::log_pipeline_error("offline_queue", &queue_err);
+                                            None
+                                        }
+                                    }
+                                } else {
+                                    None
+                                };
+                                let message = if queue_id.is_some() {
+                                    format!("STT Error: {} (saved to the offline queue)", e)
+                                } else {
+                                    format!("STT Error: {}", e)
+                                };
+                                if let Ok(mut last_failed) = app_single.state::<LastFailedRecording>().lock() {
+                                    *last_failed = Some(FailedRecording {
+                                        samples: all_audio_data,
+                                        sample_rate,
+                                        settings: settings_single.clone(),
+                                        queue_id,
+                                    });
+                                }
+                                emit_retryable_error(&app_single, &settings_single, &message);
                             }
                         }
                 } else {
@@ -1193,17 +2580,28 @@ async fn start_recording(
             DebugLogger::log_info("RECORDING_STATE_CHANGE: Set to false in pipeline cleanup (natural termination)");
             DebugLogger::log_info("Recording state set to false");
         }
+        if let Err(e) = app.state::<HotkeySMState>().finish_processing() {
+            DebugLogger::log_warn(&format!("Failed to set FSM to Idle after processing: {}", e));
+        }
         // Show completion notification when processing ends
-        DebugLogger::log_info("Showing processing completed notification");
-        let _ = app.notification()
-            .builder()
-            .title("Processing completed")
-            .body("✏️ Text copied to clipboard")
-            .show();
+        if cancel_flag_clone.load(Ordering::SeqCst) {
+            DebugLogger::log_info("Skipping processing completed notification (recording cancelled)");
+        } else {
+            DebugLogger::log_info("Showing processing completed notification");
+            let _ = notify(&app, &settings.notifications, false, "Processing completed", "✏️ Text copied to clipboard");
+        }
+
+        if !cancel_flag_clone.load(Ordering::SeqCst) {
+            if let Some(metrics) = stats.metrics(recording_start_time.elapsed()) {
+                if let Err(e) = MetricsStore::record(&app, metrics) {
+                    DebugLogger::log_pipeline_error("metrics", &e);
+                }
+            }
+        }
 
         // Emit recording-stopped event AFTER transcription has been shown to frontend
         DebugLogger::log_info("Emitting recording-stopped event to frontend");
-        let _ = app.emit("recording-stopped", {});
+        let _ = app.emit("recording-stopped", stats.payload());
             
         DebugLogger::log_info("=== PIPELINE CLEANUP COMPLETE ===");
     });
@@ -1223,7 +2621,7 @@ fn stop_recording(
     audio_stop_sender: State<'_, AudioStopSender>,
     audio_manager: State<'_, AudioManagerHandle>,
     fsm: State<'_, HotkeySMState>
-) -> Result<(), String> {
+) -> Result<(), TalkToMeError> {
     // Dump last hotkey info for correlation
     if let Ok(last) = app.state::<LastHotkey>().inner().lock() {
         if let Some((action, when)) = &*last {
@@ -1258,6 +2656,11 @@ fn stop_recording(
         let state = recording_state.inner().lock().map_err(|e| e.to_string())?;
         if !*state {
             DebugLogger::log_info("stop_recording called but recording_state already false - ignoring duplicate stop");
+            // A stop press with no recording and no pipeline behind it would otherwise leave the FSM in Processing
+            if ACTIVE_PIPELINE_TASKS.load(Ordering::SeqCst) == 0 {
+                fsm.finish_processing()
+                    .unwrap_or_else(|e| DebugLogger::log_warn(&format!("Failed to set FSM to Idle: {}", e)));
+            }
             return Ok(());
         }
     }
@@ -1282,9 +2685,9 @@ fn stop_recording(
         DebugLogger::log_info("Recording state set to false in stop_recording");
     }
 
-    // Update FSM to Idle state
-    fsm.force_set_state(hotkey_fsm::RecordingState::Idle)
-        .unwrap_or_else(|e| DebugLogger::log_info(&format!("Failed to set FSM to Idle: {}", e)));
+    // Capture is over but the pipeline is still transcribing; it moves the FSM to Idle when done
+    fsm.force_set_state(hotkey_fsm::RecordingState::Processing)
+        .unwrap_or_else(|e| DebugLogger::log_warn(&format!("Failed to set FSM to Processing: {}", e)));
 
     // Send stop signal to audio processing task
     {
@@ -1308,25 +2711,34 @@ fn stop_recording(
     Ok(())
 }
 
+// The `local_only` value a command should honour: the saved setting, or the caller's (e.g. an unsaved
+// settings form) when that is stricter. An unreadable settings file counts as local-only.
+fn local_only_for(app: &AppHandle, requested: Option<bool>) -> bool {
+    let stored = SettingsStore::load(app).map(|s| s.local_only).unwrap_or(true);
+    crate::api_flavor::effective_local_only(requested, stored)
+}
+
 // Command to test API connectivity
 #[tauri::command]
-async fn test_stt_api(endpoint: String, api_key: String) -> Result<bool, String> {
+async fn test_stt_api(app: AppHandle, endpoint: String, api_key: String, api_flavor: Option<String>, stt_model: Option<String>, require_api_key: Option<bool>, local_only: Option<bool>) -> Result<bool, TalkToMeError> {
     if endpoint.is_empty() {
-        return Err("API endpoint cannot be empty".to_string());
+        return Err(TalkToMeError::InvalidInput("API endpoint cannot be empty".to_string()));
     }
+    crate::api_flavor::check_local_only(&endpoint, local_only_for(&app, local_only))
+        .map_err(TalkToMeError::InvalidApiEndpoint)?;
     
-    if api_key.is_empty() {
-        return Err("API key cannot be empty".to_string());
+    if api_key.is_empty() && api_key_required(&endpoint, require_api_key.unwrap_or(true)) {
+        return Err(TalkToMeError::MissingApiKey("API key cannot be empty".to_string()));
     }
 
     let client = reqwest::Client::new();
+    let api_flavor = ApiFlavor::from_setting(api_flavor.as_deref().unwrap_or("openai"));
     
     // Try to test the models endpoint first (common for OpenAI-compatible APIs)
-    let models_url = format!("{}/models", endpoint);
+    let models_url = api_flavor.models_url(&endpoint);
     
-    match client
-        .get(&models_url)
-        .header("Authorization", format!("Bearer {}", api_key))
+    match api_flavor
+        .authorize(client.get(&models_url), &api_key)
         .timeout(std::time::Duration::from_secs(10))
         .send()
         .await
@@ -1335,13 +2747,13 @@ async fn test_stt_api(endpoint: String, api_key: String) -> Result<bool, String>
             if response.status().is_success() {
                 Ok(true)
             } else if response.status() == 401 {
-                Err("Unauthorized: Invalid API key".to_string())
+                Err(TalkToMeError::InvalidApiKey("Unauthorized: Invalid API key".to_string()))
             } else if response.status() == 404 {
                 // Models endpoint might not exist, try a simple health check or audio transcription endpoint
-                let transcription_url = format!("{}/audio/transcriptions", endpoint);
-                match client
-                    .head(&transcription_url)
-                    .header("Authorization", format!("Bearer {}", api_key))
+                let stt_model = stt_model.unwrap_or_else(|| AppSettings::default().stt_model);
+                let transcription_url = api_flavor.endpoint_url(&endpoint, &stt_model, "audio/transcriptions");
+                match api_flavor
+                    .authorize(client.head(&transcription_url), &api_key)
                     .timeout(std::time::Duration::from_secs(10))
                     .send()
                     .await
@@ -1351,27 +2763,276 @@ async fn test_stt_api(endpoint: String, api_key: String) -> Result<bool, String>
                             // 400/422 is expected for HEAD request without proper audio data
                             Ok(true)
                         } else if resp.status() == 401 {
-                            Err("Unauthorized: Invalid API key".to_string())
+                            Err(TalkToMeError::InvalidApiKey("Unauthorized: Invalid API key".to_string()))
                         } else {
-                            Err(format!("API returned status code: {}", resp.status()))
+                            Err(TalkToMeError::ApiError(format!("API returned status code: {}", resp.status())))
                         }
                     }
-                    Err(e) => Err(format!("Network error: {}", e))
+                    Err(e) => Err(TalkToMeError::NetworkError(format!("Network error: {}", e)))
                 }
             } else {
-                Err(format!("API returned status code: {}", response.status()))
+                Err(TalkToMeError::ApiError(format!("API returned status code: {}", response.status())))
             }
         }
         Err(e) => {
             if e.is_timeout() {
-                Err("Request timed out. Check your internet connection and API endpoint.".to_string())
+                Err(TalkToMeError::Timeout("Request timed out. Check your internet connection and API endpoint.".to_string()))
             } else if e.is_connect() {
-                Err("Cannot connect to API endpoint. Check the URL and your internet connection.".to_string())
+                Err(TalkToMeError::NetworkError("Cannot connect to API endpoint. Check the URL and your internet connection.".to_string()))
+            } else {
+                Err(TalkToMeError::NetworkError(format!("Network error: {}", e)))
+            }
+        }
+    }
+}
+
+// Model IDs offered by the endpoint, for the stt_model/translation_model dropdowns. Cached for a few
+// minutes per endpoint (`refresh` bypasses the cache). Servers without a usable /models route yield
+// an empty list; auth and network failures are still errors.
+#[tauri::command]
+async fn list_models(
+    app: AppHandle,
+    endpoint: String,
+    api_key: Option<String>,
+    api_flavor: Option<String>,
+    require_api_key: Option<bool>,
+    refresh: Option<bool>,
+    local_only: Option<bool>,
+) -> Result<Vec<String>, TalkToMeError> {
+    let endpoint = endpoint.trim().to_string();
+    if endpoint.is_empty() {
+        return Err(TalkToMeError::InvalidInput("API endpoint cannot be empty".to_string()));
+    }
+    crate::api_flavor::check_local_only(&endpoint, local_only_for(&app, local_only))
+        .map_err(TalkToMeError::InvalidApiEndpoint)?;
+    let api_flavor = ApiFlavor::from_setting(api_flavor.as_deref().unwrap_or("openai"));
+    let cache_key = format!("{:?}|{}", api_flavor, endpoint.trim_end_matches('/'));
+    if !refresh.unwrap_or(false) {
+        if let Some((fetched_at, models)) = MODELS_CACHE
+            .lock()
+            .ok()
+            .and_then(|cache| cache.as_ref().and_then(|c| c.get(&cache_key).cloned()))
+        {
+            if fetched_at.elapsed() < MODELS_CACHE_TTL {
+                return Ok(models);
+            }
+        }
+    }
+
+    let api_key = match api_key.filter(|key| !key.trim().is_empty()) {
+        Some(key) => key,
+        None => resolve_api_key(&app, &endpoint, require_api_key.unwrap_or(true))?,
+    };
+    let response = api_flavor
+        .authorize(reqwest::Client::new().get(api_flavor.models_url(&endpoint)), &api_key)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                TalkToMeError::Timeout("Request timed out while listing models".to_string())
             } else {
-                Err(format!("Network error: {}", e))
+                TalkToMeError::NetworkError(format!("Network error: {}", e))
+            }
+        })?;
+    let status = response.status();
+    if status == 401 || status == 403 {
+        return Err(TalkToMeError::InvalidApiKey("Unauthorized: Invalid API key".to_string()));
+    }
+    let models = if status.is_success() {
+        // A body that isn't a model list also means the route isn't supported
+        response
+            .json::<serde_json::Value>()
+            .await
+            .map(|body| parse_model_ids(&body))
+            .unwrap_or_default()
+    } else if status == 404 || status == 405 || status == 501 {
+        DebugLogger::log_info(&format!("list_models: {} answered {}, returning no models", endpoint, status));
+        Vec::new()
+    } else {
+        return Err(TalkToMeError::ApiError(format!("API returned status code: {}", status)));
+    };
+    DebugLogger::log_info(&format!("list_models: {} model(s) from {}", models.len(), endpoint));
+
+    if let Ok(mut cache) = MODELS_CACHE.lock() {
+        cache
+            .get_or_insert_with(HashMap::new)
+            .insert(cache_key, (std::time::Instant::now(), models.clone()));
+    }
+    Ok(models)
+}
+
+// Update the in-memory settings used by translate_text (only the fields present in `changes`), then
+// check the resulting endpoint with test_stt_api. The changes are kept even when the check fails,
+// so the settings screen can show "not connected" for what was just entered.
+#[tauri::command]
+async fn apply_runtime_settings(
+    app: AppHandle,
+    app_state: State<'_, Mutex<AppSettings>>,
+    changes: serde_json::Value,
+) -> Result<bool, TalkToMeError> {
+    let changes = changes
+        .as_object()
+        .ok_or_else(|| TalkToMeError::InvalidInput("Settings changes must be an object".to_string()))?;
+    let settings = {
+        let mut current = app_state.lock().map_err(|e| e.to_string())?;
+        let mut value = serde_json::to_value(&*current).map_err(|e| e.to_string())?;
+        let fields = value.as_object_mut().ok_or_else(|| "Settings did not serialize to an object".to_string())?;
+        for (key, change) in changes {
+            if !fields.contains_key(key) {
+                return Err(TalkToMeError::InvalidInput(format!("Unknown setting: {}", key)));
+            }
+            fields.insert(key.clone(), change.clone());
+        }
+        *current = serde_json::from_value(value)
+            .map_err(|e| TalkToMeError::InvalidInput(format!("Invalid settings: {}", e)))?;
+        current.clone()
+    };
+    DebugLogger::log_info(&format!(
+        "apply_runtime_settings: updated {:?}, endpoint={}",
+        changes.keys().collect::<Vec<_>>(),
+        settings.api_endpoint
+    ));
+
+    let api_key = resolve_api_key(&app, &settings.api_endpoint, settings.require_api_key)?;
+    test_stt_api(
+        app.clone(),
+        settings.api_endpoint.clone(),
+        api_key,
+        Some(settings.api_flavor.clone()),
+        Some(settings.effective_stt_model().to_string()),
+        Some(settings.require_api_key),
+        Some(settings.local_only),
+    )
+    .await
+}
+
+// Copy saved settings into the in-memory AppSettings used by translate_text. Fields only the store
+// has (theme, hotkeys, ...) are skipped, and in-memory-only fields keep their current value.
+fn sync_runtime_settings(app: &AppHandle, saved: &storage::PersistentSettings) -> Result<(), String> {
+    let state = app.state::<Mutex<AppSettings>>();
+    let mut current = state.lock().map_err(|e| e.to_string())?;
+    *current = overlay_saved_settings(&current, saved)?;
+    // The hotkey FSM keeps its own copy of the tap threshold; nothing in the frontend pushes it
+    if let Some(fsm) = app.try_state::<HotkeySMState>() {
+        fsm.set_tap_threshold_ms(saved.tap_threshold_ms.min(1000) as u64);
+    }
+    sync_pre_roll(app, saved);
+    Ok(())
+}
+
+// Arm or close the standby pre-roll stream to match the saved settings. It holds the microphone
+// open, so it only runs while pre-roll is on and the microphone is actually recorded.
+fn sync_pre_roll(app: &AppHandle, saved: &storage::PersistentSettings) {
+    let loopback_only = crate::audio::CaptureSource::from_setting(&saved.capture_source) == crate::audio::CaptureSource::SystemLoopback;
+    let pre_roll_ms = if loopback_only { 0 } else { saved.pre_roll_ms.min(2000) };
+    if let Some(handle) = app.try_state::<AudioManagerHandle>() {
+        if let Ok(sender) = handle.lock() {
+            let _ = sender.send(AudioManagerCommand::ConfigurePreRoll {
+                audio_device: saved.audio_device.clone(),
+                pre_roll_ms,
+                mono_mixdown: crate::audio::MonoMixdown::from_setting(&saved.mono_mixdown),
+            });
+        }
+    }
+}
+
+// `base` with every field it shares with the saved settings taken from `saved`
+fn overlay_saved_settings(base: &AppSettings, saved: &storage::PersistentSettings) -> Result<AppSettings, String> {
+    let mut value = serde_json::to_value(base).map_err(|e| e.to_string())?;
+    let saved = serde_json::to_value(saved).map_err(|e| e.to_string())?;
+    if let (Some(fields), Some(saved)) = (value.as_object_mut(), saved.as_object()) {
+        for (key, field) in saved {
+            if let Some(slot) = fields.get_mut(key) {
+                *slot = field.clone();
             }
         }
     }
+    serde_json::from_value(value).map_err(|e| format!("Saved settings don't fit the runtime settings: {}", e))
+}
+
+// First-run guidance: emit `missing-api-key` (and show the window) when the endpoint needs a key and
+// none is stored. With validate_api_key_on_startup, a stored key the endpoint rejects emits `api-key-invalid`.
+async fn check_api_key_on_startup(app: AppHandle) {
+    tokio::time::sleep(STARTUP_CHECK_DELAY).await;
+    let settings = match SettingsStore::load(&app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            DebugLogger::log_warn(&format!("STARTUP_CHECK: could not load settings: {}", e));
+            return;
+        }
+    };
+    let show_window = || {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    };
+
+    if !AppSettings::default().has_api_key(&app) {
+        if api_key_required(&settings.api_endpoint, settings.require_api_key) {
+            DebugLogger::log_info("STARTUP_CHECK: no API key stored, prompting the user");
+            show_window();
+            let _ = app.emit("missing-api-key", serde_json::json!({ "endpoint": settings.api_endpoint }));
+        }
+        return;
+    }
+    if !settings.validate_api_key_on_startup {
+        return;
+    }
+
+    let api_key = match resolve_api_key(&app, &settings.api_endpoint, settings.require_api_key) {
+        Ok(key) => key,
+        Err(_) => return,
+    };
+    let stt_model = crate::settings::resolve_stt_model(&settings.model_overrides, &settings.spoken_language, &settings.stt_model);
+    match test_stt_api(
+        app.clone(),
+        settings.api_endpoint.clone(),
+        api_key,
+        Some(settings.api_flavor.clone()),
+        Some(stt_model.to_string()),
+        Some(settings.require_api_key),
+        Some(settings.local_only),
+    )
+    .await
+    {
+        Ok(_) => DebugLogger::log_info("STARTUP_CHECK: stored API key accepted"),
+        Err(TalkToMeError::InvalidApiKey(message)) => {
+            DebugLogger::log_warn(&format!("STARTUP_CHECK: stored API key rejected: {}", message));
+            show_window();
+            let _ = app.emit("api-key-invalid", serde_json::json!({ "message": message }));
+        }
+        // Offline or endpoint trouble says nothing about the key itself
+        Err(e) => DebugLogger::log_info(&format!("STARTUP_CHECK: could not validate API key: {}", e)),
+    }
+}
+
+// The stored key, or an empty one (no auth header is sent) when the endpoint doesn't need a key
+fn resolve_api_key(app: &AppHandle, api_endpoint: &str, require_api_key: bool) -> Result<String, TalkToMeError> {
+    match AppSettings::default().get_api_key(app) {
+        Ok(key) => Ok(key),
+        Err(_) if !api_key_required(api_endpoint, require_api_key) => {
+            DebugLogger::log_info("No API key stored; continuing without authentication (local endpoint or require_api_key=false)");
+            Ok(String::new())
+        }
+        Err(e) => Err(TalkToMeError::MissingApiKey(format!("Failed to get API key: {}", e))),
+    }
+}
+
+// Offline pre-flight check of the STT credentials: endpoint format and key presence
+fn validate_api_credentials(api_endpoint: &str, api_key: &str, require_api_key: bool) -> Result<(), TalkToMeError> {
+    let endpoint = api_endpoint.trim();
+    if endpoint.is_empty() {
+        return Err(TalkToMeError::InvalidApiEndpoint("API endpoint cannot be empty".to_string()));
+    }
+    if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+        return Err(TalkToMeError::InvalidApiEndpoint(format!("API endpoint must start with http:// or https:// (got '{}')", endpoint)));
+    }
+    if api_key.trim().is_empty() && api_key_required(endpoint, require_api_key) {
+        return Err(TalkToMeError::InvalidApiKey("API key cannot be empty".to_string()));
+    }
+    Ok(())
 }
 
 // Command to validate settings
@@ -1390,15 +3051,64 @@ async fn validate_settings(settings: serde_json::Value) -> Result<serde_json::Va
         errors.push("API endpoint is required".to_string());
     }
 
-    // Validate API key
-    if let Some(api_key) = settings["apiKey"].as_str() {
-        if api_key.is_empty() {
-            errors.push("API key cannot be empty".to_string());
-        } else if api_key.len() < 10 {
-            errors.push("API key seems too short".to_string());
+    // Validate API key (optional for local servers or when requireApiKey is false)
+    let key_required = api_key_required(
+        settings["apiEndpoint"].as_str().unwrap_or(""),
+        settings["requireApiKey"].as_bool().unwrap_or(true),
+    );
+    match settings["apiKey"].as_str() {
+        Some(api_key) if !api_key.is_empty() => {
+            if api_key.len() < 10 {
+                errors.push("API key seems too short".to_string());
+            }
+        }
+        Some(_) if key_required => errors.push("API key cannot be empty".to_string()),
+        None if key_required => errors.push("API key is required".to_string()),
+        _ => {}
+    }
+
+    // Validate network tuning (optional fields)
+    if let Some(timeout) = settings["requestTimeoutSecs"].as_u64() {
+        if !(5..=300).contains(&timeout) {
+            errors.push("Request timeout must be between 5 and 300 seconds".to_string());
+        }
+    }
+    if let Some(debounce) = settings["hotkeyDebounceMs"].as_u64() {
+        if debounce > 1000 {
+            errors.push("Hotkey debounce must be between 0 and 1000 ms".to_string());
+        }
+    }
+    if let Some(retries) = settings["maxRetries"].as_u64() {
+        if retries > 10 {
+            errors.push("Max retries must be between 0 and 10".to_string());
+        }
+    }
+
+    // Validate sampling parameters (optional fields)
+    if let Some(temperature) = settings["sttTemperature"].as_f64() {
+        if !(0.0..=1.0).contains(&temperature) {
+            errors.push("STT temperature must be between 0 and 1".to_string());
+        }
+    }
+    if let Some(temperature) = settings["translationTemperature"].as_f64() {
+        if !(0.0..=2.0).contains(&temperature) {
+            errors.push("Translation temperature must be between 0 and 2".to_string());
+        }
+    }
+    if let Some(max_tokens) = settings["translationMaxTokens"].as_u64() {
+        if !(1..=32_000).contains(&max_tokens) {
+            errors.push("Translation max tokens must be between 1 and 32000".to_string());
+        }
+    }
+
+    // Validate custom translation prompt template (empty = built-in prompts)
+    if let Some(template) = settings["translationPromptTemplate"].as_str() {
+        if !template.trim().is_empty() && !template.contains("{text}") {
+            errors.push(format!(
+                "Translation prompt template must contain {{text}} (available placeholders: {})",
+                translation::PROMPT_TEMPLATE_PLACEHOLDERS.join(", ")
+            ));
         }
-    } else {
-        errors.push("API key is required".to_string());
     }
 
     // Validate hotkeys
@@ -1440,11 +3150,30 @@ fn toggle_window(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+// A second launch brings the running instance forward instead of starting another one.
+// toggle_window shows a hidden window; an already visible one only needs focus (toggling would hide it).
+fn focus_existing_instance(app: &AppHandle) {
+    let visible = app
+        .get_webview_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    if visible {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+    } else if let Err(e) = toggle_window(app.clone()) {
+        DebugLogger::log_warn(&format!("Failed to show window for second instance: {}", e));
+    }
+}
+
 // Command to quit the application
 #[tauri::command]
-fn quit_app(app: tauri::AppHandle) -> Result<(), String> {
-    app.exit(0);
-    Ok(())
+async fn quit_app(app: tauri::AppHandle) -> Result<(), String> {
+    // Off the async runtime: shutdown blocks while the pipeline winds down
+    tokio::task::spawn_blocking(move || shutdown(&app))
+        .await
+        .map_err(|e| format!("Shutdown task failed: {}", e))
 }
 
 // Removed update_spoken_language - now using localStorage-only approach
@@ -1454,13 +3183,30 @@ fn quit_app(app: tauri::AppHandle) -> Result<(), String> {
 // Removed update_audio_device - now using localStorage-only approach
 
 #[tauri::command]
-async fn store_api_key(app: AppHandle, api_key: String) -> Result<(), String> {
+async fn store_api_key(app: AppHandle, api_key: String) -> Result<(), TalkToMeError> {
     DebugLogger::log_info(&format!("store_api_key called with key length: {}", api_key.len()));
-    AppSettings::default().store_api_key(&app, api_key)?;
+    if api_key.trim().is_empty() {
+        return Err(TalkToMeError::InvalidApiKey("API key cannot be empty".to_string()));
+    }
+    save_api_key(&app, api_key).map_err(TalkToMeError::StorageError)?;
     DebugLogger::log_info("API key stored successfully in backend");
     Ok(())
 }
 
+// Store the key, warning the frontend (keyring-unavailable) when it could only be kept for this session
+fn save_api_key(app: &AppHandle, api_key: String) -> Result<(), String> {
+    if let settings::ApiKeyStorage::SessionOnly(reason) = AppSettings::default().store_api_key(app, api_key)? {
+        let message = format!(
+            "The system keyring is unavailable ({}). The API key will only be kept until TalkToMe exits.",
+            reason
+        );
+        DebugLogger::log_warn(&message);
+        let _ = app.emit("keyring-unavailable", serde_json::json!({ "message": message }));
+        let _ = notify(app, &stored_notification_level(app), true, "TalkToMe - API key not saved", &message);
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn get_api_key(app: AppHandle) -> Result<String, String> {
     AppSettings::default().get_api_key(&app)
@@ -1519,7 +3265,7 @@ async fn test_audio_capture() -> Result<String, String> {
     let device = host.default_input_device()
         .ok_or("No input device available")?;
     
-    let config = device.default_input_config()
+    let config = AudioCapture::input_config(&device)
         .map_err(|e| format!("Failed to get input config: {}", e))?;
     
     Ok(format!(
@@ -1531,6 +3277,66 @@ async fn test_audio_capture() -> Result<String, String> {
     ))
 }
 
+// Record a few seconds from the microphone and report whether any signal actually arrived
+#[tauri::command]
+async fn record_test_clip(
+    app: AppHandle,
+    audio_manager: State<'_, AudioManagerHandle>,
+    seconds: u32,
+    audio_device: Option<String>,
+    save_wav: Option<bool>,
+) -> Result<serde_json::Value, TalkToMeError> {
+    if !(1..=10).contains(&seconds) {
+        return Err(TalkToMeError::InvalidInput("Test clip length must be between 1 and 10 seconds".to_string()));
+    }
+    let (reply_tx, reply_rx) = std_mpsc::channel();
+    {
+        let sender = audio_manager.lock().map_err(|e| e.to_string())?;
+        sender
+            .send(AudioManagerCommand::TestClip {
+                audio_device: audio_device.unwrap_or_else(|| "default".to_string()),
+                seconds,
+                reply: reply_tx,
+            })
+            .map_err(|e| TalkToMeError::AudioError(format!("Failed to send test clip command to audio manager: {}", e)))?;
+    }
+    let samples = reply_rx
+        .recv_timeout(std::time::Duration::from_secs(seconds as u64 + 10))
+        .map_err(|e| TalkToMeError::AudioError(format!("Timed out waiting for test clip: {}", e)))?
+        .map_err(TalkToMeError::AudioError)?;
+
+    let levels = crate::audio::measure_levels(&samples);
+    DebugLogger::log_info(&format!(
+        "MIC_TEST: {} samples, peak {:.4}, rms {:.4}, {} non-silent",
+        samples.len(), levels.peak, levels.rms, levels.non_silent_samples
+    ));
+
+    // Overwritten on every test so diagnostics never pile up next to the logs
+    let wav_path = if save_wav.unwrap_or(false) {
+        let log_path = DebugLogger::get_log_file_path(&app)?;
+        let dir = std::path::Path::new(&log_path)
+            .parent()
+            .ok_or_else(|| TalkToMeError::StorageError("Log directory not available".to_string()))?;
+        let path = dir.join("mic_test.wav");
+        std::fs::write(&path, crate::audio::encode_wav_bytes(&samples, 16000))
+            .map_err(|e| TalkToMeError::StorageError(format!("Failed to save test clip: {}", e)))?;
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    Ok(serde_json::json!({
+        "durationSecs": samples.len() as f32 / 16000.0,
+        "sampleRate": 16000,
+        "totalSamples": samples.len(),
+        "peak": levels.peak,
+        "rms": levels.rms,
+        "nonSilentSamples": levels.non_silent_samples,
+        "silent": levels.non_silent_samples == 0,
+        "wavPath": wav_path
+    }))
+}
+
 #[tauri::command]
 async fn get_recording_status(recording_state: State<'_, RecordingState>) -> Result<bool, String> {
     let state = recording_state.inner().lock().map_err(|e| e.to_string())?;
@@ -1552,6 +3358,85 @@ async fn get_log_file_path(app: AppHandle) -> Result<String, String> {
     DebugLogger::get_log_file_path(&app)
 }
 
+// Delete the debug WAV dumps; returns how many were removed
+#[tauri::command]
+async fn clear_wav_dumps(app: AppHandle) -> Result<usize, String> {
+    let removed = DebugLogger::clear_wav_dumps(&app)?;
+    DebugLogger::log_info(&format!("Cleared {} WAV dump(s)", removed));
+    Ok(removed)
+}
+
+// Show the folder holding the log file and WAV dumps in the system file manager
+#[tauri::command]
+async fn open_dump_folder(app: AppHandle) -> Result<(), String> {
+    let dir = DebugLogger::dump_dir(&app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", dir.display(), e))
+}
+
+// Without microphone access macOS hands the app silent buffers instead of an error, so check up front
+fn check_mic_permission(app: &AppHandle) -> Result<(), TalkToMeError> {
+    let status = permissions::mic_permission();
+    let message = match status {
+        MicPermission::Granted => return Ok(()),
+        MicPermission::NotDetermined => {
+            permissions::request_mic_permission();
+            "Allow microphone access in the system prompt, then start recording again"
+        }
+        MicPermission::Denied => "Microphone access is denied. Enable TalkToMe in System Settings > Privacy & Security > Microphone",
+        MicPermission::Restricted => "Microphone access is restricted by a device policy",
+    };
+    DebugLogger::log_info(&format!("Microphone permission {}; not starting capture", status.as_str()));
+    let _ = app.emit("mic-permission-needed", serde_json::json!({
+        "status": status,
+        "message": message,
+    }));
+    Err(TalkToMeError::MicPermissionDenied(message.to_string()))
+}
+
+const ACCESSIBILITY_NEEDED_MESSAGE: &str = "Text was copied but not inserted. Allow TalkToMe in System Settings > Privacy & Security > Accessibility";
+
+// Insertion fell back to the clipboard because keystrokes would have been dropped
+fn report_accessibility_needed(app: &AppHandle) {
+    DebugLogger::log_pipeline_error("text_insertion", "Accessibility permission missing; text left on the clipboard");
+    let _ = app.emit("accessibility-permission-needed", serde_json::json!({
+        "message": ACCESSIBILITY_NEEDED_MESSAGE,
+    }));
+}
+
+#[tauri::command]
+async fn get_mic_permission() -> Result<MicPermission, String> {
+    Ok(permissions::mic_permission())
+}
+
+// Jump to the Microphone privacy pane so the user can grant access after a denial
+#[tauri::command]
+async fn open_mic_privacy_settings(app: AppHandle) -> Result<(), String> {
+    if !cfg!(target_os = "macos") {
+        return Err("Microphone privacy settings are only available on macOS".to_string());
+    }
+    app.opener()
+        .open_url(permissions::MIC_PRIVACY_SETTINGS_URL, None::<&str>)
+        .map_err(|e| format!("Failed to open privacy settings: {}", e))
+}
+
+#[tauri::command]
+async fn get_accessibility_permission() -> Result<bool, String> {
+    Ok(permissions::accessibility_trusted())
+}
+
+#[tauri::command]
+async fn open_accessibility_settings(app: AppHandle) -> Result<(), String> {
+    if !cfg!(target_os = "macos") {
+        return Err("Accessibility settings are only available on macOS".to_string());
+    }
+    app.opener()
+        .open_url(permissions::ACCESSIBILITY_PRIVACY_SETTINGS_URL, None::<&str>)
+        .map_err(|e| format!("Failed to open privacy settings: {}", e))
+}
+
 #[tauri::command]
 async fn get_data_directory_info(app: AppHandle) -> Result<serde_json::Value, String> {
     use serde_json::json;
@@ -1587,6 +3472,50 @@ async fn get_data_directory_info(app: AppHandle) -> Result<serde_json::Value, St
     }))
 }
 
+// What works on this platform, so the UI can hide or disable controls that would be no-ops
+#[derive(serde::Serialize)]
+struct Capabilities {
+    platform: &'static str,
+    // auto_mute_mode "mute"/"duck" has an effect
+    system_mute_supported: bool,
+    // capture_source "system_loopback"/"both" can be used
+    loopback_supported: bool,
+    // Insertion backend the current settings would use (see check_text_insertion_tools for details)
+    insertion_backend: &'static str,
+    noise_reduction: bool,
+    keyring_available: bool,
+}
+
+#[tauri::command]
+async fn get_capabilities(app: AppHandle) -> Result<Capabilities, String> {
+    let settings = SettingsStore::load(&app)?;
+    let insertion_backend = TextInsertionService::new(
+        settings.restore_clipboard,
+        settings.preserve_clipboard,
+        InsertionMethod::from_setting(&settings.insertion_method),
+        OutputMode::from_setting(&settings.output_mode),
+        settings.paste_delay_ms,
+        settings.powershell_fallback,
+    )
+    .check_tools()
+    .backend;
+    // The keyring and mute probes may spawn processes or talk to D-Bus
+    let (system_mute_supported, keyring_available) = tokio::task::spawn_blocking(|| {
+        (SystemAudioControl::is_supported(), AppSettings::default().keyring_available())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(Capabilities {
+        platform: std::env::consts::OS,
+        system_mute_supported,
+        loopback_supported: crate::audio::CaptureSource::loopback_supported(),
+        insertion_backend,
+        // nnnoiseless is built in on every platform
+        noise_reduction: true,
+        keyring_available,
+    })
+}
+
 // Command used by the frontend to annotate backend logs with frontend-originated events
 #[tauri::command]
 async fn frontend_log(tag: String, payload: Option<serde_json::Value>) -> Result<(), String> {
@@ -1595,11 +3524,76 @@ async fn frontend_log(tag: String, payload: Option<serde_json::Value>) -> Result
     Ok(())
 }
 
+// Report installed insertion tools and the backend the current settings would use
+#[tauri::command]
+async fn check_text_insertion_tools(app: AppHandle) -> Result<text_insertion::InsertionToolReport, String> {
+    let settings = SettingsStore::load(&app)?;
+    let service = TextInsertionService::new(
+        settings.restore_clipboard,
+        settings.preserve_clipboard,
+        InsertionMethod::from_setting(&settings.insertion_method),
+        OutputMode::from_setting(&settings.output_mode),
+        settings.paste_delay_ms,
+        settings.powershell_fallback,
+    );
+    let report = service.check_tools();
+    DebugLogger::log_info(&format!(
+        "check_text_insertion_tools: backend={}, fallback={:?}, missing={:?}",
+        report.backend, report.fallback, report.missing
+    ));
+    Ok(report)
+}
+
+// Insert the last final text again, e.g. when the first paste landed in the wrong window
+#[tauri::command]
+async fn reinsert_last(app: AppHandle, last_text: State<'_, LastFinalText>) -> Result<(), String> {
+    let text = last_text
+        .lock()
+        .map_err(|e| format!("Failed to lock last text: {}", e))?
+        .clone()
+        .ok_or_else(|| "No transcription to insert yet".to_string())?;
+    let settings = SettingsStore::load(&app)?;
+    let service = TextInsertionService::new(
+        settings.restore_clipboard,
+        settings.preserve_clipboard,
+        InsertionMethod::from_setting(&settings.insertion_method),
+        OutputMode::from_setting(&settings.output_mode),
+        settings.paste_delay_ms,
+        settings.powershell_fallback,
+    );
+    DebugLogger::log_info(&format!("reinsert_last: inserting {} chars again", text.len()));
+    let t = text_format::wrap_for_insertion(&text, &settings.insert_prefix, &settings.insert_suffix);
+    match tokio::task::spawn_blocking(move || service.insert_text(&t)).await {
+        Ok(Ok(InsertionOutcome::CopiedWithoutAccessibility)) => {
+            report_accessibility_needed(&app);
+            DebugLogger::log_text_insertion(&text, false, Some(ACCESSIBILITY_NEEDED_MESSAGE));
+            Err(ACCESSIBILITY_NEEDED_MESSAGE.to_string())
+        }
+        Ok(Ok(_)) => {
+            DebugLogger::log_text_insertion(&text, true, None);
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            DebugLogger::log_text_insertion(&text, false, Some(&e));
+            Err(e)
+        }
+        Err(e) => Err(format!("Insertion task failed: {}", e)),
+    }
+}
+
 // Test command for text insertion debugging
 #[tauri::command]
 async fn test_text_insertion(test_text: String) -> Result<(), String> {
     DebugLogger::log_info(&format!("TEST_TEXT_INSERTION: called with text='{}'", test_text));
-    let text_insertion_service = TextInsertionService::new();
+    let defaults = AppSettings::default();
+    let text_insertion_service = TextInsertionService::new(
+        defaults.restore_clipboard,
+        defaults.preserve_clipboard,
+        InsertionMethod::from_setting(&defaults.insertion_method),
+        OutputMode::from_setting(&defaults.output_mode),
+        defaults.paste_delay_ms,
+        defaults.powershell_fallback,
+    );
     text_insertion_service.test_insert(&test_text)
 }
 
@@ -1615,24 +3609,37 @@ async fn translate_text(
     DebugLogger::log_info(&format!("translate_text called: '{}' from {} to {}", text, source_lang, target_lang));
     
     // Get current settings and clone necessary values to avoid holding the lock across await
-    let (api_endpoint, translation_model) = {
+    let (api_endpoint, translation_model, api_flavor, request_timeout_secs, max_retries, prompt_template, temperature, max_tokens, require_api_key, correction_style, custom_headers, api_mode, local_only) = {
         let settings = app_state.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
-        (settings.api_endpoint.clone(), settings.translation_model.clone())
+        (settings.api_endpoint.clone(), settings.translation_model.clone(), ApiFlavor::from_setting(&settings.api_flavor),
+            settings.request_timeout_secs as u64, settings.max_retries, settings.translation_prompt_template.clone(),
+            settings.translation_temperature, settings.translation_max_tokens, settings.require_api_key,
+            CorrectionStyle::from_setting(&settings.correction_style),
+            custom_header_map(&settings.custom_headers, settings.allow_auth_headers),
+            ApiMode::from_setting(&settings.api_mode), settings.local_only)
     };
     
     // Get API key using the same method as start_recording
-    let settings_for_api = AppSettings::default();
-    let api_key = settings_for_api.get_api_key(&app).map_err(|e| {
-        let error_msg = format!("Failed to get API key: {}", e);
-        DebugLogger::log_info(&format!("No API key available for translation: {}", error_msg));
-        error_msg
+    let api_key = resolve_api_key(&app, &api_endpoint, require_api_key).map_err(|e| {
+        DebugLogger::log_info(&format!("No API key available for translation: {}", e));
+        e.to_string()
     })?;
     
     // Create translation service
     let translation_service = TranslationService::new(
         api_endpoint,
         api_key,
-        translation_model
+        translation_model,
+        api_flavor,
+        request_timeout_secs,
+        max_retries,
+        prompt_template,
+        temperature,
+        max_tokens,
+        correction_style,
+        custom_headers,
+        api_mode,
+        local_only_for(&app, Some(local_only))
     );
     
     // Perform translation
@@ -1648,6 +3655,272 @@ async fn translate_text(
     }
 }
 
+// Transcribe one queued recording. The result goes to history (even when history is off, since the
+// app it was dictated into has long lost focus) and the entry is removed; failures bump its attempt count.
+async fn process_queued_recording(app: &AppHandle, mut entry: QueuedRecording) -> Result<(), String> {
+    let (samples, sample_rate) = OfflineQueue::load_audio(app, &entry)?;
+    let settings = entry.settings.clone();
+    let api_key = resolve_api_key(app, &settings.api_endpoint, settings.require_api_key).map_err(|e| e.to_string())?;
+    let raw = match stt_service_for(&settings, api_key.clone())
+        .transcribe_chunk(samples, sample_rate, Some("stt_queue"))
+        .await
+    {
+        Ok(raw) => apply_spoken_punctuation(settings.spoken_punctuation, &settings.spoken_punctuation_words, raw),
+        Err(e) => {
+            entry.attempts += 1;
+            entry.last_error = e.clone();
+            if let Err(update_err) = OfflineQueue::update(app, &entry) {
+                DebugLogger::log_pipeline_error("offline_queue", &update_err);
+            }
+            return Err(e);
+        }
+    };
+
+    let processed = if raw.trim().is_empty() {
+        raw.clone()
+    } else {
+        let translation_service = translation_service_for(&settings, api_key);
+        match translation_service
+            .process_text(&raw, &settings.spoken_language, &settings.translation_language, settings.translation_enabled)
+            .await
+        {
+            Ok(processed) => finalize_text(&settings, processed, translation_service.corrects_text()),
+            Err(e) => {
+                DebugLogger::log_pipeline_error("translation", &e);
+                finalize_text(&settings, raw.clone(), false)
+            }
+        }
+    };
+    if !raw.trim().is_empty() {
+        append_history(app, &settings, &raw, &processed);
+    }
+    let _ = app.emit("queued-transcription-completed", serde_json::json!({
+        "id": entry.id,
+        "raw": raw,
+        "final": processed
+    }));
+    OfflineQueue::remove(app, &entry.id)?;
+
+    // The in-memory retry copy of the same recording is no longer needed
+    if let Ok(mut last_failed) = app.state::<LastFailedRecording>().lock() {
+        if last_failed.as_ref().is_some_and(|f| f.queue_id.as_deref() == Some(entry.id.as_str())) {
+            *last_failed = None;
+        }
+    }
+    Ok(())
+}
+
+// Retry queued recordings oldest first. Stops at the first failure, which usually means we're still
+// offline. Automatic runs skip entries that already failed `MAX_AUTO_ATTEMPTS` times.
+// Returns (transcribed, still pending).
+async fn flush_offline_queue(app: &AppHandle, automatic: bool) -> Result<(usize, usize), String> {
+    if QUEUE_FLUSHING.swap(true, Ordering::SeqCst) {
+        return Err("Offline queue is already being processed".to_string());
+    }
+    let mut transcribed = 0;
+    let mut result = Ok(());
+    for entry in OfflineQueue::list(app).unwrap_or_default() {
+        if automatic && entry.attempts >= offline_queue::MAX_AUTO_ATTEMPTS {
+            continue;
+        }
+        let id = entry.id.clone();
+        match process_queued_recording(app, entry).await {
+            Ok(()) => {
+                DebugLogger::log_info(&format!("OFFLINE_QUEUE: transcribed {}", id));
+                transcribed += 1;
+            }
+            Err(e) => {
+                DebugLogger::log_warn(&format!("OFFLINE_QUEUE: {} still failing: {}", id, e));
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    QUEUE_FLUSHING.store(false, Ordering::SeqCst);
+    let pending = OfflineQueue::list(app)?.len();
+    // A manual flush reports the failure; the background task just tries again later
+    match result {
+        Err(e) if !automatic && transcribed == 0 => Err(e),
+        _ => Ok((transcribed, pending)),
+    }
+}
+
+#[tauri::command]
+async fn get_pending_queue(app: AppHandle) -> Result<Vec<QueuedRecording>, String> {
+    OfflineQueue::list(&app)
+}
+
+#[tauri::command]
+async fn flush_queue(app: AppHandle) -> Result<serde_json::Value, String> {
+    let (transcribed, pending) = flush_offline_queue(&app, false).await?;
+    Ok(serde_json::json!({
+        "transcribed": transcribed,
+        "pending": pending
+    }))
+}
+
+// Re-run STT (and correction/translation + insertion) on the audio of the last failed single recording
+#[tauri::command]
+async fn retry_last_transcription(
+    app: AppHandle,
+    last_failed: State<'_, LastFailedRecording>,
+    recording_state: State<'_, RecordingState>,
+) -> Result<serde_json::Value, TalkToMeError> {
+    if *recording_state.lock().map_err(|e| e.to_string())? {
+        return Err(TalkToMeError::AlreadyRecording("Stop the current recording before retrying".to_string()));
+    }
+    // Copy out so the lock isn't held across the request; the buffer stays stored until a retry succeeds
+    let (samples, sample_rate, settings, queue_id) = {
+        let guard = last_failed.lock().map_err(|e| e.to_string())?;
+        let failed = guard
+            .as_ref()
+            .ok_or_else(|| TalkToMeError::InvalidInput("No failed transcription to retry".to_string()))?;
+        (failed.samples.clone(), failed.sample_rate, failed.settings.clone(), failed.queue_id.clone())
+    };
+    DebugLogger::log_info(&format!(
+        "retry_last_transcription: {} samples ({:.1}s)",
+        samples.len(), samples.len() as f32 / sample_rate as f32
+    ));
+
+    let api_key = resolve_api_key(&app, &settings.api_endpoint, settings.require_api_key)?;
+    let _ = app.emit("processing-audio", true);
+    emit_pipeline_status(&app, PipelineStage::Transcribing, None);
+    let result = stt_service_for(&settings, api_key.clone())
+        .transcribe_chunk(samples, sample_rate, Some("stt_retry"))
+        .await;
+    let _ = app.emit("processing-audio", false);
+    let raw = match result {
+        Ok(raw) => apply_spoken_punctuation(settings.spoken_punctuation, &settings.spoken_punctuation_words, raw),
+        Err(e) => {
+            DebugLogger::log_pipeline_error("stt", &format!("Retry failed: {}", e));
+            emit_retryable_error(&app, &settings, &format!("STT Error: {}", e));
+            emit_pipeline_status(&app, PipelineStage::Done, None);
+            return Err(TalkToMeError::ApiError(e));
+        }
+    };
+    if let Ok(mut last_failed) = last_failed.lock() {
+        *last_failed = None;
+    }
+    if let Some(id) = queue_id {
+        if let Err(e) = OfflineQueue::remove(&app, &id) {
+            DebugLogger::log_pipeline_error("offline_queue", &e);
+        }
+    }
+    if raw.trim().is_empty() {
+        emit_transcription_empty(&app, EmptyReason::NoSpeech);
+        emit_pipeline_status(&app, PipelineStage::Done, None);
+        return Ok(serde_json::json!({ "raw": "", "final": "" }));
+    }
+
+    let translation_service = translation_service_for(&settings, api_key);
+    emit_pipeline_status(&app, PipelineStage::Translating, None);
+    let processed = match translation_service
+        .process_text(&raw, &settings.spoken_language, &settings.translation_language, settings.translation_enabled)
+        .await
+    {
+        Ok(processed) => finalize_text(&settings, processed, translation_service.corrects_text()),
+        Err(e) => {
+            DebugLogger::log_pipeline_error("translation", &e);
+            let _ = app.emit("processing-error", format!("Translation Error - Using fallback: {}", e));
+            finalize_text(&settings, raw.clone(), false)
+        }
+    };
+    let _ = app.emit("transcribed-text", serde_json::json!({
+        "raw": raw,
+        "final": processed
+    }));
+    record_history(&app, &settings, &raw, &processed);
+    remember_final_text(&app, &processed);
+
+    if settings.text_insertion_enabled {
+        let service = TextInsertionService::new(
+            settings.restore_clipboard,
+            settings.preserve_clipboard,
+            InsertionMethod::from_setting(&settings.insertion_method),
+            OutputMode::from_setting(&settings.output_mode),
+            settings.paste_delay_ms,
+            settings.powershell_fallback,
+        );
+        let text = text_format::wrap_for_insertion(&processed, &settings.insert_prefix, &settings.insert_suffix);
+        emit_pipeline_status(&app, PipelineStage::Inserting, None);
+        match tokio::task::spawn_blocking(move || service.insert_text(&text)).await {
+            Ok(Ok(InsertionOutcome::CopiedWithoutAccessibility)) => {
+                report_accessibility_needed(&app);
+                DebugLogger::log_text_insertion(&processed, false, Some(ACCESSIBILITY_NEEDED_MESSAGE));
+                emit_pipeline_status(&app, PipelineStage::Error, Some(ACCESSIBILITY_NEEDED_MESSAGE));
+            }
+            Ok(Ok(_)) => DebugLogger::log_text_insertion(&processed, true, None),
+            Ok(Err(e)) => {
+                DebugLogger::log_text_insertion(&processed, false, Some(&e));
+                emit_pipeline_status(&app, PipelineStage::Error, Some(&e));
+            }
+            Err(e) => DebugLogger::log_pipeline_error("text_insertion", &format!("Insertion task failed: {}", e)),
+        }
+    }
+    emit_pipeline_status(&app, PipelineStage::Done, None);
+
+    Ok(serde_json::json!({
+        "raw": raw,
+        "final": processed
+    }))
+}
+
+// Run a WAV file from disk through the STT + correction/translation services, without recording.
+// Nothing is inserted or saved to history; the raw and processed text are returned.
+#[tauri::command]
+async fn transcribe_file(app: AppHandle, path: String) -> Result<serde_json::Value, TalkToMeError> {
+    DebugLogger::log_info(&format!("transcribe_file called: '{}'", path));
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| TalkToMeError::InvalidInput(format!("Failed to read '{}': {}", path, e)))?;
+    let (samples, sample_rate) = crate::audio::decode_wav(&bytes).map_err(TalkToMeError::InvalidInput)?;
+    // Same rate the recording pipeline hands to the STT service
+    let samples = crate::audio::resample(&samples, sample_rate, 16000);
+    DebugLogger::log_info(&format!(
+        "transcribe_file: decoded {} samples at {}Hz ({:.1}s)",
+        samples.len(), sample_rate, samples.len() as f32 / 16000.0
+    ));
+
+    let stored = SettingsStore::load(&app).map_err(TalkToMeError::StorageError)?;
+    let settings = overlay_saved_settings(&AppSettings::default(), &stored).map_err(TalkToMeError::StorageError)?;
+    let api_key = resolve_api_key(&app, &settings.api_endpoint, settings.require_api_key)?;
+    validate_api_credentials(&settings.api_endpoint, &api_key, settings.require_api_key)?;
+
+    let stt_service = stt_service_for(&settings, api_key.clone());
+    let raw = stt_service
+        .transcribe_chunk(samples, 16000, Some("file"))
+        .await
+        .map_err(TalkToMeError::ApiError)?;
+    let raw = apply_spoken_punctuation(settings.spoken_punctuation, &settings.spoken_punctuation_words, raw);
+
+    let processed = if raw.trim().is_empty() {
+        raw.clone()
+    } else {
+        let translation_service = translation_service_for(&settings, api_key);
+        match translation_service
+            .process_text(
+                &raw,
+                &settings.spoken_language,
+                &settings.translation_language,
+                settings.translation_enabled,
+            )
+            .await
+        {
+            Ok(processed) => finalize_text(&settings, processed, translation_service.corrects_text()),
+            Err(e) => {
+                // Same fallback as the recording pipeline: keep the raw transcript
+                DebugLogger::log_pipeline_error("translation", &e);
+                finalize_text(&settings, raw.clone(), false)
+            }
+        }
+    };
+
+    Ok(serde_json::json!({
+        "raw": raw,
+        "final": processed
+    }))
+}
+
 // New commands for localStorage-based settings
 #[tauri::command]
 async fn load_settings_from_frontend() -> Result<String, String> {
@@ -1688,8 +3961,7 @@ async fn save_settings_from_frontend(
     // (Note: we now send empty string for security, so API key is stored separately via store_api_key command)
     if let Some(api_key_val) = api_key {
         if !api_key_val.is_empty() {
-            let settings = AppSettings::default();
-            settings.store_api_key(&app, api_key_val)?;
+            save_api_key(&app, api_key_val)?;
             DebugLogger::log_info("API key stored securely in backend");
         }
     }
@@ -1703,8 +3975,20 @@ async fn save_settings_from_frontend(
 }
 
 #[tauri::command]
-async fn init_debug_logging(app: AppHandle, enabled: bool) -> Result<(), String> {
+async fn init_debug_logging(app: AppHandle, enabled: bool, max_log_mb: Option<u32>, log_format: Option<String>, log_level: Option<String>, wav_dump_retention: Option<u32>) -> Result<(), String> {
     DebugLogger::log_info(&format!("Debug logging manually set to: {}", enabled));
+    if let Some(mb) = max_log_mb {
+        DebugLogger::set_max_log_mb(mb);
+    }
+    if let Some(keep) = wav_dump_retention {
+        DebugLogger::set_wav_dump_retention(keep);
+    }
+    if let Some(format) = log_format {
+        DebugLogger::set_log_format(&format);
+    }
+    if let Some(level) = log_level {
+        DebugLogger::set_min_level(LogLevel::from_setting(&level));
+    }
     DebugLogger::init_with_state(&app, enabled)?;
     Ok(())
 }
@@ -1732,9 +4016,24 @@ async fn save_persistent_settings(app: AppHandle, settings: serde_json::Value) -
     match serde_json::from_value::<storage::PersistentSettings>(settings.clone()) {
         Ok(parsed_settings) => {
             DebugLogger::log_info(&format!("SETTINGS_SAVE_PERSISTENT: Successfully parsed settings object"));
+            for (field, hotkey) in HOTKEY_SETTING_FIELDS.iter().zip([
+                &parsed_settings.hands_free_hotkey,
+                &parsed_settings.start_recording_hotkey,
+                &parsed_settings.stop_recording_hotkey,
+                &parsed_settings.cancel_hotkey,
+                &parsed_settings.reinsert_hotkey,
+            ]) {
+                validate_hotkey_setting(field, hotkey).map_err(|e| {
+                    DebugLogger::log_warn(&format!("SETTINGS_SAVE_PERSISTENT: Rejected {}='{}': {}", field, hotkey, e));
+                    e
+                })?;
+            }
             match SettingsStore::save(&app, &parsed_settings) {
                 Ok(_) => {
                     DebugLogger::log_info("SETTINGS_SAVE_PERSISTENT: Successfully saved to store");
+                    if let Err(e) = sync_runtime_settings(&app, &parsed_settings) {
+                        DebugLogger::log_warn(&format!("SETTINGS_SAVE_PERSISTENT: Runtime settings not updated: {}", e));
+                    }
                     Ok(())
                 }
                 Err(e) => {
@@ -1744,7 +4043,8 @@ async fn save_persistent_settings(app: AppHandle, settings: serde_json::Value) -
             }
         }
         Err(deserialize_err) => {
-            let error_msg = format!("SETTINGS_SAVE_PERSISTENT: Failed to deserialize settings - missing fields error: {}", deserialize_err);
+            // Missing fields fall back to defaults, so this is a wrongly typed value
+            let error_msg = format!("SETTINGS_SAVE_PERSISTENT: Failed to deserialize settings: {}", deserialize_err);
             DebugLogger::log_pipeline_error("settings_deserialize", &error_msg);
             DebugLogger::log_info(&format!("SETTINGS_SAVE_PERSISTENT: Incoming JSON was: {}", settings));
             Err(error_msg)
@@ -1752,18 +4052,158 @@ async fn save_persistent_settings(app: AppHandle, settings: serde_json::Value) -
     }
 }
 
+#[tauri::command]
+async fn list_profiles(app: AppHandle) -> Result<ProfileList, String> {
+    ProfileStore::list(&app)
+}
+
+#[tauri::command]
+async fn save_profile(app: AppHandle, name: String) -> Result<(), String> {
+    ProfileStore::save_as(&app, &name)
+}
+
+#[tauri::command]
+async fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    ProfileStore::delete(&app, &name)
+}
+
+// Load a profile into the live settings and re-register its hotkeys; the frontend reloads
+// the rest (recording parameters) from the "profile-switched" payload
+#[tauri::command]
+async fn switch_profile(app: AppHandle, name: String) -> Result<serde_json::Value, String> {
+    let settings = ProfileStore::switch(&app, &name)?;
+
+    let mut hotkeys = HashMap::new();
+    hotkeys.insert("hands_free".to_string(), settings.hands_free_hotkey.clone());
+    hotkeys.insert("start_recording".to_string(), settings.start_recording_hotkey.clone());
+    hotkeys.insert("stop_recording".to_string(), settings.stop_recording_hotkey.clone());
+    hotkeys.insert("cancel".to_string(), settings.cancel_hotkey.clone());
+    hotkeys.insert("reinsert_last".to_string(), settings.reinsert_hotkey.clone());
+    register_hotkeys(app.clone(), hotkeys, Some(settings.hotkey_mode.clone()), app.state::<HotkeyRegistry>()).await?;
+    app.state::<HotkeySMState>().set_debounce_ms(settings.hotkey_debounce_ms.min(1000) as u64);
+    app.state::<HotkeySMState>().set_tap_threshold_ms(settings.tap_threshold_ms.min(1000) as u64);
+    retry::set_max_concurrent_requests(settings.max_concurrent_requests);
+    sync_runtime_settings(&app, &settings)?;
+
+    let value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    let _ = app.emit("profile-switched", serde_json::json!({ "name": name.trim(), "settings": value }));
+    Ok(value)
+}
+
+#[tauri::command]
+async fn get_persistent_setting(app: AppHandle, field: String) -> Result<serde_json::Value, String> {
+    SettingsStore::get_field(&app, &field)
+}
+
 #[tauri::command]
 async fn update_persistent_setting(app: AppHandle, field: String, value: serde_json::Value) -> Result<(), String> {
+    if HOTKEY_SETTING_FIELDS.contains(&field.as_str()) {
+        if let Some(hotkey) = value.as_str() {
+            validate_hotkey_setting(&field, hotkey)?;
+        }
+    }
     SettingsStore::update_field(&app, &field, value)?;
+    if let Err(e) = SettingsStore::load(&app).and_then(|saved| sync_runtime_settings(&app, &saved)) {
+        DebugLogger::log_warn(&format!("Runtime settings not updated after '{}' changed: {}", field, e));
+    }
     Ok(())
 }
 
+// Arm (or disarm with 0) the standby pre-roll stream so the first recording also gets pre-roll
+#[tauri::command]
+async fn configure_pre_roll(
+    app: AppHandle,
+    audio_manager: State<'_, AudioManagerHandle>,
+    audio_device: Option<String>,
+    pre_roll_ms: u32,
+    mono_mixdown: Option<String>,
+) -> Result<(), String> {
+    let stored = SettingsStore::load(&app).unwrap_or_default();
+    let sender = audio_manager.lock().map_err(|e| e.to_string())?;
+    sender
+        .send(AudioManagerCommand::ConfigurePreRoll {
+            audio_device: audio_device.unwrap_or(stored.audio_device),
+            pre_roll_ms: pre_roll_ms.min(2000),
+            mono_mixdown: crate::audio::MonoMixdown::from_setting(&mono_mixdown.unwrap_or(stored.mono_mixdown)),
+        })
+        .map_err(|e| format!("Failed to send pre-roll command to audio manager: {}", e))
+}
+
+#[tauri::command]
+async fn get_transcription_history(app: AppHandle, limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+    HistoryStore::recent(&app, limit.unwrap_or(50))
+}
+
+#[tauri::command]
+async fn clear_transcription_history(app: AppHandle) -> Result<(), String> {
+    HistoryStore::clear(&app)
+}
+
+// Usage dashboard: totals and averages over all sessions plus the most recent ones
+#[tauri::command]
+async fn get_usage_metrics(app: AppHandle, recent: Option<usize>) -> Result<serde_json::Value, String> {
+    MetricsStore::usage(&app, recent.unwrap_or(20))
+}
+
+// Remove everything the app has stored: the API key, all store files, the offline queue, logs and WAV
+// dumps. Any recording in progress is cancelled and in-memory state goes back to its defaults.
+// Each step runs even if an earlier one fails; failures are listed under "errors".
+#[tauri::command]
+async fn purge_all_data(app: AppHandle) -> Result<serde_json::Value, String> {
+    DebugLogger::log_info("PURGE: removing all stored data");
+    let cancel_app = app.clone();
+    let cancelled = tokio::task::spawn_blocking(move || cancel_active_recording(&cancel_app))
+        .await
+        .unwrap_or(false);
+
+    let mut errors: Vec<String> = Vec::new();
+    let api_key_removed = AppSettings::default()
+        .delete_api_key(&app)
+        .unwrap_or_else(|e| { errors.push(e); false });
+    let stores_cleared = storage::purge_stores(&app)
+        .unwrap_or_else(|e| { errors.push(e); Vec::new() });
+    let queued_recordings = OfflineQueue::clear(&app)
+        .unwrap_or_else(|e| { errors.push(e); 0 });
+    let (log_files, wav_dumps) = DebugLogger::purge_files(&app)
+        .unwrap_or_else(|e| { errors.push(e); (0, 0) });
+
+    let fsm = app.state::<HotkeySMState>();
+    fsm.force_set_state(hotkey_fsm::RecordingState::Idle)?;
+    fsm.reset_debounce()?;
+    if let Ok(mut settings) = app.state::<Mutex<AppSettings>>().lock() {
+        *settings = AppSettings::default();
+    }
+    if let Ok(mut last_failed) = app.state::<LastFailedRecording>().lock() {
+        *last_failed = None;
+    }
+    if let Ok(mut last_text) = app.state::<LastFinalText>().lock() {
+        *last_text = None;
+    }
+    if let Ok(mut cache) = MODELS_CACHE.lock() {
+        *cache = None;
+    }
+
+    for e in &errors {
+        DebugLogger::log_pipeline_error("purge_all_data", e);
+    }
+    Ok(serde_json::json!({
+        "recording_cancelled": cancelled,
+        "api_key_removed": api_key_removed,
+        "stores_cleared": stores_cleared,
+        "queued_recordings": queued_recordings,
+        "log_files": log_files,
+        "wav_dumps": wav_dumps,
+        "errors": errors,
+    }))
+}
+
 #[tauri::command]
 fn get_hotkey_fsm_state(fsm: State<'_, HotkeySMState>) -> Result<String, String> {
     let state = fsm.get_state()?;
     let state_str = match state {
         hotkey_fsm::RecordingState::Idle => "Idle",
         hotkey_fsm::RecordingState::Recording => "Recording",
+        hotkey_fsm::RecordingState::Processing => "Processing",
     };
     Ok(state_str.to_string())
 }
@@ -1786,10 +4226,36 @@ fn set_hotkey_fsm_recording(fsm: State<'_, HotkeySMState>, recording: bool) -> R
     Ok(())
 }
 
+// Update the hotkey debounce interval at runtime (no re-registration needed)
+#[tauri::command]
+fn set_hotkey_debounce(fsm: State<'_, HotkeySMState>, debounce_ms: u64) -> Result<(), String> {
+    if debounce_ms > 1000 {
+        return Err(format!("Hotkey debounce must be between 0 and 1000 ms, got {}", debounce_ms));
+    }
+    fsm.set_debounce_ms(debounce_ms);
+    DebugLogger::log_info(&format!("Hotkey debounce set to {}ms", debounce_ms));
+    Ok(())
+}
+
+// Update the push-to-talk tap-to-lock threshold at runtime; 0 disables latching
+#[tauri::command]
+fn set_hotkey_tap_threshold(fsm: State<'_, HotkeySMState>, tap_threshold_ms: u64) -> Result<(), String> {
+    if tap_threshold_ms > 1000 {
+        return Err(format!("Tap threshold must be between 0 and 1000 ms, got {}", tap_threshold_ms));
+    }
+    fsm.set_tap_threshold_ms(tap_threshold_ms);
+    DebugLogger::log_info(&format!("Hotkey tap threshold set to {}ms", tap_threshold_ms));
+    Ok(())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-    .plugin(tauri_plugin_single_instance::init(|_app, _argv, _cwd| {}))
+    // Must be the first plugin: a second launch exits before registering hotkeys or opening the mic
+    .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+        DebugLogger::log_info(&format!("Second instance launched (args: {:?}); focusing existing window", argv));
+        focus_existing_instance(app);
+    }))
     .plugin(tauri_plugin_opener::init())
     .plugin(tauri_plugin_global_shortcut::Builder::new().build())
     .plugin(tauri_plugin_notification::init())
@@ -1813,11 +4279,27 @@ pub fn run() {
             }
             
             DebugLogger::log_info("TalkToMe application starting up");
+
+            // The FSM needs the stored debounce and tap threshold, so it is managed here rather than on the builder
+            let (hotkey_debounce_ms, tap_threshold_ms, max_concurrent_requests) = SettingsStore::load(app.handle())
+                .map(|s| (s.hotkey_debounce_ms.min(1000), s.tap_threshold_ms.min(1000), s.max_concurrent_requests))
+                .unwrap_or((150, 0, retry::DEFAULT_MAX_CONCURRENT_REQUESTS));
+            retry::set_max_concurrent_requests(max_concurrent_requests);
+            let hotkey_fsm = HotkeySM::new(hotkey_debounce_ms as u64);
+            hotkey_fsm.set_tap_threshold_ms(tap_threshold_ms as u64);
+            app.manage(Arc::new(hotkey_fsm) as HotkeySMState);
+
+            // translate_text reads the managed AppSettings, so start it from what the user saved
+            if let Err(e) = SettingsStore::load(app.handle()).and_then(|saved| sync_runtime_settings(app.handle(), &saved)) {
+                DebugLogger::log_warn(&format!("Runtime settings left at defaults: {}", e));
+            }
             DebugLogger::log_info("Initialized with default settings for tray menu");
             
             // Create a simple system tray menu
             let tray_menu = {
                 let show_hide = MenuItemBuilder::with_id("show_hide", "Show/Hide TalkToMe").build(app)?;
+                let toggle_recording = MenuItemBuilder::with_id(tray_status::TOGGLE_RECORDING_ID, "Start Recording").build(app)?;
+                app.manage(tray_status::TrayRecordingItem(toggle_recording.clone()));
                 
                 let preferences = MenuItemBuilder::with_id("preferences", "Preferences").build(app)?;
                 let api_settings = MenuItemBuilder::with_id("api_settings", "API Settings").build(app)?;
@@ -1829,6 +4311,7 @@ pub fn run() {
                 MenuBuilder::new(app)
                     .items(&[
                         &show_hide,
+                        &toggle_recording,
                         &preferences,
                         &api_settings,
                         &language_settings, 
@@ -1840,8 +4323,8 @@ pub fn run() {
             };
 
             // Build the system tray
-            let _tray = TrayIconBuilder::with_id("main-tray")
-                .tooltip("TalkToMe - Voice to Text with Translation")
+            let _tray = TrayIconBuilder::with_id(tray_status::TRAY_ID)
+                .tooltip(tray_status::TrayStatus::Idle.tooltip())
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&tray_menu)
                 .show_menu_on_left_click(false)
@@ -1852,6 +4335,13 @@ pub fn run() {
                                 eprintln!("Failed to toggle window: {}", e);
                             }
                         }
+                        tray_status::TOGGLE_RECORDING_ID => {
+                            let ts_ms = std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .map(|d| d.as_millis())
+                                .unwrap_or(0);
+                            toggle_recording_from_hotkey(app, "tray", ts_ms);
+                        }
                         "preferences" => {
                             if let Some(window) = app.get_webview_window("main") {
                                 let _ = window.show();
@@ -1888,7 +4378,8 @@ pub fn run() {
                             }
                         }
                         "quit" => {
-                            app.exit(0);
+                            let app = app.clone();
+                            std::thread::spawn(move || shutdown(&app));
                         }
                         _ => {}
                     }
@@ -1919,6 +4410,26 @@ pub fn run() {
                     }
                 })
                 .build(app)?;
+            tray_status::listen_for_status_events(app.handle());
+
+            // Retry recordings that failed while offline once connectivity is back
+            let queue_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(QUEUE_RETRY_INTERVAL).await;
+                    let recording = queue_app
+                        .state::<RecordingState>()
+                        .lock()
+                        .map(|state| *state)
+                        .unwrap_or(false);
+                    if recording || OfflineQueue::list(&queue_app).map(|q| q.is_empty()).unwrap_or(true) {
+                        continue;
+                    }
+                    let _ = flush_offline_queue(&queue_app, true).await;
+                }
+            });
+
+            tauri::async_runtime::spawn(check_api_key_on_startup(app.handle().clone()));
 
             // Handle window close request (minimize to tray instead of closing)
             if let Some(window) = app.get_webview_window("main") {
@@ -1943,71 +4454,13 @@ pub fn run() {
         .manage(Arc::new(Mutex::new(None)) as AudioStopSender)
     .manage(Arc::new(Mutex::new(None)) as LastStopTime)
         .manage(Arc::new(Mutex::new(None)) as LastHotkey)
-        .manage(Arc::new(HotkeySM::new(150)) as HotkeySMState)
+        .manage(Arc::new(AtomicBool::new(false)) as CancelFlag)
+        .manage(Arc::new(Mutex::new(None)) as LastFailedRecording)
+        .manage(Arc::new(Mutex::new(None)) as LastFinalText)
+        // Runtime settings for translate_text; updated by apply_runtime_settings
+        .manage(Mutex::new(AppSettings::default()))
         // Spawn a dedicated single-thread audio manager to own non-Send AudioCapture
-        .manage({
-            // Create an mpsc channel for sending commands to the manager
-            let (cmd_tx, cmd_rx) = std_mpsc::channel::<AudioManagerCommand>();
-            // Spawn thread that owns AudioCapture and responds to commands
-            std::thread::spawn(move || {
-                DebugLogger::log_info("Audio manager thread starting");
-                // The audio capture instance is owned here on this single thread
-                let mut audio_capture_opt: Option<AudioCapture> = None;
-                for cmd in cmd_rx.iter() {
-                    match cmd {
-                        AudioManagerCommand::Start { reply, audio_chunking_enabled } => {
-                            DebugLogger::log_info("Audio manager received Start command");
-                            // If already started, return error
-                            if audio_capture_opt.is_some() {
-                                DebugLogger::log_info("Audio manager received duplicate Start - capture already running");
-                                let err_msg = "Audio capture already started; call stop_recording() before starting a new capture".to_string();
-                                // store for diagnostics
-                                if let Ok(mut last_err) = AUDIO_MANAGER_LAST_ERROR.lock() {
-                                    *last_err = Some(err_msg.clone());
-                                }
-                                let _ = reply.send(Err(err_msg));
-                                continue;
-                            }
-                            // Create and start capture (only once)
-                            let mut capture = AudioCapture::new();
-                            match capture.start_capture(audio_chunking_enabled) {
-                                Ok(rx) => {
-                                    audio_capture_opt = Some(capture);
-                                    DebugLogger::log_info("Audio manager successfully started capture and returned receiver");
-                                    let _ = reply.send(Ok(rx));
-                                }
-                                Err(e) => {
-                                    let msg = format!("Failed to start capture in manager: {}", e);
-                                    DebugLogger::log_pipeline_error("audio_manager", &msg);
-                                    let _ = reply.send(Err(msg));
-                                }
-                            }
-                        }
-                        AudioManagerCommand::Stop { reply } => {
-                            DebugLogger::log_info("Audio manager received Stop command");
-                            if let Some(mut cap) = audio_capture_opt.take() {
-                                DebugLogger::log_info("Audio manager is stopping active capture (cap was Some)");
-                                if let Err(e) = cap.stop_recording() {
-                                    DebugLogger::log_pipeline_error("audio_manager", &format!("Error stopping capture: {}", e));
-                                } else {
-                                    DebugLogger::log_info("Audio manager stop_recording() returned Ok");
-                                }
-                            } else {
-                                DebugLogger::log_info("Audio manager Stop called but no active capture was present (cap was None)");
-                                if let Ok(mut last_err) = AUDIO_MANAGER_LAST_ERROR.lock() {
-                                    *last_err = Some("Stop called but no active capture present".to_string());
-                                }
-                            }
-                            if let Some(r) = reply {
-                                let _ = r.send(Ok(()));
-                            }
-                        }
-                    }
-                }
-                DebugLogger::log_info("Audio manager thread exiting");
-            });
-            Arc::new(Mutex::new(cmd_tx)) as AudioManagerHandle
-        })
+        .manage(Arc::new(Mutex::new(spawn_audio_manager())) as AudioManagerHandle)
         .invoke_handler(tauri::generate_handler![
             greet, 
             start_recording, 
@@ -2023,32 +4476,72 @@ pub fn run() {
             debug_api_key_info,
             get_available_audio_devices,
             test_audio_capture,
+            record_test_clip,
             get_recording_status,
             get_debug_logs,
             clear_debug_logs,
             get_log_file_path,
+            clear_wav_dumps,
+            open_dump_folder,
+            get_mic_permission,
+            open_mic_privacy_settings,
+            get_accessibility_permission,
+            open_accessibility_settings,
             get_data_directory_info,
             frontend_log,
             test_text_insertion,
+            check_text_insertion_tools,
+            reinsert_last,
+            get_capabilities,
             translate_text,
+            transcribe_file,
+            retry_last_transcription,
+            get_pending_queue,
+            flush_queue,
             load_settings_from_frontend,
             save_settings_from_frontend,
             init_debug_logging,
             get_audio_manager_last_error,
             clear_audio_manager_last_error,
+            get_audio_manager_health,
+            restart_audio_manager,
             show_recording_timeout_notification,
             test_hotkey_parsing,
             show_recording_started_notification,
             show_recording_stopped_notification,
             load_persistent_settings,
             save_persistent_settings,
+            get_persistent_setting,
             update_persistent_setting,
+            list_profiles,
+            save_profile,
+            switch_profile,
+            delete_profile,
             get_hotkey_fsm_state,
             reset_hotkey_fsm,
             set_hotkey_fsm_recording,
+            set_hotkey_debounce,
+            set_hotkey_tap_threshold,
             confirm_recording,
-            cancel_recording
+            cancel_recording,
+            get_transcription_history,
+            clear_transcription_history,
+            purge_all_data,
+            get_usage_metrics,
+            apply_runtime_settings,
+            list_models,
+            configure_pre_roll
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Exits that didn't go through shutdown() (e.g. Cmd+Q on macOS) get the same cleanup first
+            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+                if !SHUTTING_DOWN.load(Ordering::SeqCst) {
+                    api.prevent_exit();
+                    let app = app.clone();
+                    std::thread::spawn(move || shutdown(&app));
+                }
+            }
+        });
 }
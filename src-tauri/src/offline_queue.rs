@@ -0,0 +1,118 @@
+// Recordings whose transcription failed (e.g. no connectivity), kept on disk until a retry succeeds.
+// Each entry is a 16 kHz WAV plus a JSON sidecar with the settings of the original recording.
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::settings::AppSettings;
+
+/// Automatic retries stop after this many failures; `flush_queue` still tries such entries
+pub const MAX_AUTO_ATTEMPTS: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QueuedRecording {
+    /// Also the file stem of the WAV and JSON files
+    pub id: String,
+    pub created_at: String,
+    pub duration_secs: f32,
+    pub attempts: u32,
+    pub last_error: String,
+    pub settings: AppSettings,
+}
+
+pub struct OfflineQueue;
+
+impl OfflineQueue {
+    const DIR_NAME: &'static str = "offline-queue";
+
+    fn dir(app: &AppHandle) -> Result<PathBuf, String> {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data dir: {}", e))?
+            .join(Self::DIR_NAME);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create queue dir: {}", e))?;
+        Ok(dir)
+    }
+
+    /// Write the recording and its metadata; returns the new entry
+    pub fn enqueue(
+        app: &AppHandle,
+        samples: &[f32],
+        sample_rate: u32,
+        settings: &AppSettings,
+        error: &str,
+    ) -> Result<QueuedRecording, String> {
+        let dir = Self::dir(app)?;
+        let now = chrono::Local::now();
+        let entry = QueuedRecording {
+            id: now.format("%Y%m%d_%H%M%S%.3f").to_string(),
+            created_at: now.to_rfc3339(),
+            duration_secs: samples.len() as f32 / sample_rate as f32,
+            attempts: 1,
+            last_error: error.to_string(),
+            settings: settings.clone(),
+        };
+        let wav = crate::audio::encode_wav_bytes(samples, sample_rate);
+        std::fs::write(dir.join(format!("{}.wav", entry.id)), wav)
+            .map_err(|e| format!("Failed to write queued audio: {}", e))?;
+        Self::update(app, &entry)?;
+        Ok(entry)
+    }
+
+    /// Rewrite an entry's metadata (attempt count, last error)
+    pub fn update(app: &AppHandle, entry: &QueuedRecording) -> Result<(), String> {
+        let json = serde_json::to_vec_pretty(entry)
+            .map_err(|e| format!("Failed to serialize queue entry: {}", e))?;
+        std::fs::write(Self::dir(app)?.join(format!("{}.json", entry.id)), json)
+            .map_err(|e| format!("Failed to write queue entry: {}", e))
+    }
+
+    /// Pending entries, oldest first. Unreadable sidecars are skipped.
+    pub fn list(app: &AppHandle) -> Result<Vec<QueuedRecording>, String> {
+        let entries = std::fs::read_dir(Self::dir(app)?)
+            .map_err(|e| format!("Failed to read queue dir: {}", e))?;
+        let mut queued: Vec<QueuedRecording> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| std::fs::read(path).ok())
+            .filter_map(|bytes| serde_json::from_slice(&bytes).ok())
+            .collect();
+        queued.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(queued)
+    }
+
+    pub fn load_audio(app: &AppHandle, entry: &QueuedRecording) -> Result<(Vec<f32>, u32), String> {
+        let bytes = std::fs::read(Self::dir(app)?.join(format!("{}.wav", entry.id)))
+            .map_err(|e| format!("Failed to read queued audio: {}", e))?;
+        crate::audio::decode_wav(&bytes)
+    }
+
+    /// Delete every queued recording, including unreadable leftovers; returns how many recordings there were
+    pub fn clear(app: &AppHandle) -> Result<usize, String> {
+        let entries = std::fs::read_dir(Self::dir(app)?)
+            .map_err(|e| format!("Failed to read queue dir: {}", e))?;
+        let mut recordings = 0;
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "wav") {
+                recordings += 1;
+            }
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+        Ok(recordings)
+    }
+
+    pub fn remove(app: &AppHandle, id: &str) -> Result<(), String> {
+        let dir = Self::dir(app)?;
+        for ext in ["wav", "json"] {
+            let path = dir.join(format!("{}.{}", id, ext));
+            if path.exists() {
+                std::fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            }
+        }
+        Ok(())
+    }
+}
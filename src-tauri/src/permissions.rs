@@ -0,0 +1,111 @@
+// OS privacy permissions that otherwise only show up as obscure capture failures or silent recordings.
+use serde::Serialize;
+
+/// System Settings > Privacy & Security > Microphone
+pub const MIC_PRIVACY_SETTINGS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone";
+
+/// System Settings > Privacy & Security > Accessibility
+pub const ACCESSIBILITY_PRIVACY_SETTINGS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+
+/// Microphone access as reported by the OS; platforms without a per-app permission report `Granted`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MicPermission {
+    Granted,
+    Denied,
+    // Blocked by a device policy; the user can't change it
+    Restricted,
+    // Never asked; macOS shows its prompt on the first request
+    NotDetermined,
+}
+
+impl MicPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MicPermission::Granted => "granted",
+            MicPermission::Denied => "denied",
+            MicPermission::Restricted => "restricted",
+            MicPermission::NotDetermined => "not_determined",
+        }
+    }
+}
+
+pub fn mic_permission() -> MicPermission {
+    #[cfg(target_os = "macos")]
+    {
+        macos::mic_permission()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        MicPermission::Granted
+    }
+}
+
+/// Show the system's microphone prompt (macOS, only while the permission is undetermined)
+pub fn request_mic_permission() {
+    #[cfg(target_os = "macos")]
+    macos::request_mic_access();
+}
+
+/// Whether synthetic keystrokes reach other apps. Without Accessibility access macOS drops them
+/// silently, so a paste "succeeds" without typing anything. Always true elsewhere.
+pub fn accessibility_trusted() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        macos::accessibility_trusted()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::MicPermission;
+    use crate::debug_logger::DebugLogger;
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: &'static AnyObject;
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+
+    pub fn accessibility_trusted() -> bool {
+        unsafe { AXIsProcessTrusted() }
+    }
+
+    pub fn mic_permission() -> MicPermission {
+        // AVAuthorizationStatus
+        let status: isize = unsafe {
+            msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio]
+        };
+        match status {
+            0 => MicPermission::NotDetermined,
+            1 => MicPermission::Restricted,
+            2 => MicPermission::Denied,
+            _ => MicPermission::Granted,
+        }
+    }
+
+    pub fn request_mic_access() {
+        let handler = block2::RcBlock::new(|granted: Bool| {
+            DebugLogger::log_info(&format!("Microphone permission prompt answered: granted={}", granted.as_bool()));
+        });
+        unsafe {
+            let _: () = msg_send![
+                class!(AVCaptureDevice),
+                requestAccessForMediaType: AVMediaTypeAudio,
+                completionHandler: &*handler
+            ];
+        }
+    }
+}
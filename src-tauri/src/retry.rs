@@ -0,0 +1,125 @@
+// Helpers shared by the HTTP services' retry loops
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::debug_logger::DebugLogger;
+
+// Slots for in-flight STT/translation requests, shared by every service instance, with their limit
+static REQUEST_SLOTS: Mutex<Option<(u32, Arc<Semaphore>)>> = Mutex::new(None);
+/// One request at a time keeps chunk results in order
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: u32 = 1;
+
+/// Upper bound on how long we honor a server-provided Retry-After
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Backoff ceiling for the first retry; it doubles with each further attempt up to `MAX_BACKOFF`
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Exponential backoff with jitter before retrying after failed attempt `attempt` (1-based).
+/// The jitter keeps chunks that failed together from retrying in lockstep.
+pub fn backoff_delay(attempt: u32) -> Duration {
+    use std::hash::BuildHasher;
+    // RandomState is freshly keyed per instance, which is random enough for jitter
+    let random = std::collections::hash_map::RandomState::new().hash_one(attempt);
+    jittered_backoff(attempt, (random % 1000) as f64 / 1000.0)
+}
+
+/// Half of the exponential ceiling is fixed, the other half scaled by `jitter` (0.0-1.0)
+fn jittered_backoff(attempt: u32, jitter: f64) -> Duration {
+    let ceiling = BASE_BACKOFF
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF);
+    ceiling / 2 + (ceiling / 2).mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+/// Change the in-flight request limit. Requests already holding a slot finish under the old limit.
+pub fn set_max_concurrent_requests(limit: u32) {
+    let limit = limit.max(1);
+    if let Ok(mut slots) = REQUEST_SLOTS.lock() {
+        if slots.as_ref().map(|(current, _)| *current) != Some(limit) {
+            *slots = Some((limit, Arc::new(Semaphore::new(limit as usize))));
+        }
+    }
+}
+
+/// Wait for a free request slot. Hold the permit until the response (including retries) is done;
+/// None only if the limiter is unavailable, in which case the request goes ahead unthrottled.
+pub async fn acquire_request_slot(label: &str) -> Option<OwnedSemaphorePermit> {
+    let semaphore = {
+        let mut slots = REQUEST_SLOTS.lock().ok()?;
+        slots
+            .get_or_insert_with(|| {
+                let limit = DEFAULT_MAX_CONCURRENT_REQUESTS;
+                (limit, Arc::new(Semaphore::new(limit as usize)))
+            })
+            .1
+            .clone()
+    };
+    if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+        return Some(permit);
+    }
+    DebugLogger::log_info(&format!("{}: waiting for a free request slot", label));
+    semaphore.acquire_owned().await.ok()
+}
+
+/// Delay requested by a 429/503 response's Retry-After header, capped at `MAX_RETRY_AFTER`
+pub fn retry_after_delay(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, chrono::Utc::now())
+}
+
+/// Parse a Retry-After value given either as delta-seconds or as an HTTP-date
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    let delay = if let Ok(secs) = value.parse::<u64>() {
+        Duration::from_secs(secs)
+    } else {
+        let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        // Dates in the past mean "retry now"
+        (date.with_timezone(&chrono::Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap()
+    }
+
+    #[test]
+    fn test_jittered_backoff() {
+        assert_eq!(jittered_backoff(1, 0.0), Duration::from_millis(250));
+        assert_eq!(jittered_backoff(1, 1.0), Duration::from_millis(500));
+        assert_eq!(jittered_backoff(3, 0.5), Duration::from_millis(1500));
+        assert_eq!(jittered_backoff(40, 1.0), MAX_BACKOFF);
+        let delay = backoff_delay(2);
+        assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_retry_after("5", now()), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after("3600", now()), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("soon", now()), None);
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now()),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now()),
+            Some(Duration::ZERO)
+        );
+    }
+}
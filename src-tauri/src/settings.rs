@@ -2,14 +2,46 @@ use serde::{Deserialize, Serialize};
 // std::fs was used by legacy file-based API key handling which has been removed
 use keyring::Entry;
 use serde_json::json;
+use crate::text_format::{SpokenPunctuation, TextReplacement};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 
+// Session-only copy of the API key, kept when the OS keyring can't be written (locked or missing
+// Secret Service). Never written to disk; gone when the app exits.
+static SESSION_API_KEY: Mutex<Option<String>> = Mutex::new(None);
+
+/// Where `store_api_key` put the key
+pub enum ApiKeyStorage {
+    Keyring,
+    /// The keyring write failed (with this error); the key only lasts for this session
+    SessionOnly(String),
+}
+
 #[derive(Serialize, Deserialize, Clone)]
+// Missing fields fall back to defaults so older queued recordings still deserialize
+#[serde(default)]
 pub struct AppSettings {
     pub spoken_language: String,
     pub translation_language: String,
     pub audio_device: String,
+    pub capture_source: String,
+    pub use_detected_language: bool,
+    // Find/replace rules run over the final text before insertion
+    pub text_replacements: Vec<TextReplacement>,
+    pub wav_dump_retention: u32,
+    pub max_concurrent_requests: u32,
+    pub min_recording_ms: u32,
+    pub api_mode: String,
+    pub local_only: bool,
+    pub denoise_strength: f32,
+    pub spoken_punctuation: bool,
+    pub spoken_punctuation_words: Vec<SpokenPunctuation>,
+    pub insert_prefix: String,
+    pub insert_suffix: String,
+    pub dedup_window_tokens: u32,
+    pub chunk_overlap_ms: u32,
     pub theme: String,
     pub auto_save: bool,
     pub api_endpoint: String,
@@ -22,14 +54,98 @@ pub struct AppSettings {
     pub text_insertion_enabled: bool,
     pub audio_chunking_enabled: bool,
     pub max_recording_time_minutes: u32,
+    pub hotkey_mode: String,
+    pub hotkey_debounce_ms: u32,
+    pub tap_threshold_ms: u32,
+    pub restore_clipboard: bool,
+    pub preserve_clipboard: bool,
+    pub notifications: String,
+    pub insertion_method: String,
+    pub auto_mute_mode: String,
+    pub duck_level: u32,
+    pub api_flavor: String,
+    pub upload_format: String,
+    pub response_format: String,
+    pub stt_prompt: String,
+    pub request_timeout_secs: u32,
+    pub max_retries: u32,
+    pub translation_prompt_template: String,
+    pub history_enabled: bool,
+    pub output_mode: String,
+    pub max_log_mb: u32,
+    pub log_format: String,
+    pub log_level: String,
+    pub noise_reduction_enabled: bool,
+    pub pre_roll_ms: u32,
+    pub max_upload_mb: u32,
+    pub trim_silence: bool,
+    pub max_silence_gap_ms: u32,
+    pub normalize_audio: bool,
+    pub mono_mixdown: String,
+    pub stt_temperature: f32,
+    pub translation_temperature: f32,
+    pub translation_max_tokens: u32,
+    pub require_api_key: bool,
+    pub correction_style: String,
+    pub offline_queue_enabled: bool,
+    pub local_formatting: bool,
+    pub paste_delay_ms: u32,
+    pub powershell_fallback: bool,
+    pub timeout_warning_secs: u32,
+    pub silence_threshold: f32,
+    pub min_duration_secs: f32,
+    pub validate_api_key_on_startup: bool,
+    // Extra headers sent with every STT/translation request (e.g. for gateways and proxies)
+    pub custom_headers: HashMap<String, String>,
+    pub allow_auth_headers: bool,
+    // Keyed by language code: spoken language for STT, target language for translation
+    pub model_overrides: HashMap<String, ModelOverride>,
     // SECURITY: API key is NEVER stored in this struct or localStorage
     // It's handled separately via secure file storage (backend only)
     // Frontend stores it only in memory during runtime
 }
 
+/// Models to use for one language instead of the global `stt_model`/`translation_model`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ModelOverride {
+    #[serde(default)]
+    pub stt_model: Option<String>,
+    #[serde(default)]
+    pub translation_model: Option<String>,
+}
+
+/// STT model for `language`, or `default` when there is no (non-empty) override
+pub fn resolve_stt_model<'a>(overrides: &'a HashMap<String, ModelOverride>, language: &str, default: &'a str) -> &'a str {
+    overrides
+        .get(language)
+        .and_then(|o| o.stt_model.as_deref())
+        .filter(|model| !model.trim().is_empty())
+        .unwrap_or(default)
+}
+
+/// Translation model for `language`, or `default` when there is no (non-empty) override
+pub fn resolve_translation_model<'a>(overrides: &'a HashMap<String, ModelOverride>, language: &str, default: &'a str) -> &'a str {
+    overrides
+        .get(language)
+        .and_then(|o| o.translation_model.as_deref())
+        .filter(|model| !model.trim().is_empty())
+        .unwrap_or(default)
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Hotkeys {
     pub hands_free: String,
+    // Optional dedicated keys; the hands-free toggle keeps working alongside them
+    #[serde(default)]
+    pub start_recording: Option<String>,
+    #[serde(default)]
+    pub stop_recording: Option<String>,
+    // Aborts the current recording without transcribing
+    #[serde(default)]
+    pub cancel: Option<String>,
+    // Inserts the last final text again
+    #[serde(default)]
+    pub reinsert_last: Option<String>,
 }
 
 impl Default for AppSettings {
@@ -38,6 +154,21 @@ impl Default for AppSettings {
             spoken_language: "auto".to_string(),
             translation_language: "none".to_string(),
             audio_device: "default".to_string(),
+            capture_source: "microphone".to_string(), // "microphone" | "system_loopback" | "both" (loopback: Windows)
+            use_detected_language: true, // With spoken_language "auto", prompt with the language STT detected
+            text_replacements: Vec::new(),
+            wav_dump_retention: 20, // Debug WAV dumps kept in the logs folder, newest first
+            max_concurrent_requests: 1, // STT/translation requests in flight at once; 1 keeps chunk order
+            min_recording_ms: 0, // Shorter recordings are discarded like a cancel; 0 disables
+            api_mode: "chat_completions".to_string(), // Correction/translation endpoint: "chat_completions" | "responses" | "completions"
+            local_only: false, // Refuse requests to any endpoint that isn't on localhost
+            denoise_strength: 1.0, // Share of denoised audio mixed with the original; clean input skips denoising
+            spoken_punctuation: false, // Turn spoken "comma", "new line", ... into symbols before correction
+            spoken_punctuation_words: crate::text_format::default_spoken_punctuation(),
+            insert_prefix: String::new(), // Added before inserted text; "\\n" for a newline
+            insert_suffix: " ".to_string(), // Added after inserted text so consecutive dictations don't run together
+            dedup_window_tokens: crate::text_format::DEFAULT_DEDUP_WINDOW_TOKENS, // Max words repeated across a chunk boundary that get merged; 0 disables
+            chunk_overlap_ms: 500, // Audio shared by neighbouring segments of a long recording, so boundary words aren't cut
             theme: "auto".to_string(),
             auto_save: true,
             api_endpoint: "https://api.openai.com/v1".to_string(),
@@ -45,6 +176,10 @@ impl Default for AppSettings {
             translation_model: "gpt-3.5-turbo".to_string(),
             hotkeys: Hotkeys {
                 hands_free: "Ctrl+Shift+Space".to_string(),
+                start_recording: None,
+                stop_recording: None,
+                cancel: None,
+                reinsert_last: None,
             },
             auto_mute: true,
             translation_enabled: false,
@@ -52,6 +187,50 @@ impl Default for AppSettings {
             text_insertion_enabled: true,
             audio_chunking_enabled: false, // Default to false - single recording mode only
             max_recording_time_minutes: 5, // Default to 5 minutes maximum recording time
+            hotkey_mode: "toggle".to_string(), // "toggle" | "push_to_talk"
+            hotkey_debounce_ms: 150, // 0..=1000
+            tap_threshold_ms: 0, // Push-to-talk: shorter presses latch recording on; 0 = off (default)
+            restore_clipboard: true,
+            preserve_clipboard: false, // Strict: type instead of pasting when the clipboard can't be restored
+            notifications: "all".to_string(), // "all" | "errors_only" | "off"
+            insertion_method: "paste".to_string(), // "paste" | "type"
+            auto_mute_mode: "mute".to_string(), // "off" | "mute" | "duck"
+            duck_level: 20, // Percent of the original volume while ducked
+            api_flavor: "openai".to_string(), // "openai" | "azure"
+            upload_format: "wav".to_string(), // "wav" | "flac" | "opus" (needs the `opus` feature, else FLAC)
+            response_format: "json".to_string(), // "json" | "verbose_json" | "text"
+            stt_prompt: String::new(), // Comma-separated jargon to bias recognition
+            request_timeout_secs: 15,
+            max_retries: 2, // Retries after the first attempt (3 attempts total)
+            translation_prompt_template: String::new(), // Empty = built-in prompts
+            history_enabled: false, // Opt-in: keeps dictated text on disk
+            output_mode: "insert".to_string(), // "insert" | "clipboard_only" | "both"
+            max_log_mb: 10, // Log file is rotated once it grows past this size
+            log_format: "text".to_string(), // "text" | "json" (JSON lines)
+            log_level: "info".to_string(), // "error" | "warn" | "info" | "debug" | "trace"
+            noise_reduction_enabled: true,
+            pre_roll_ms: 0, // > 0 keeps the microphone open between recordings
+            max_upload_mb: 25, // Larger recordings are split before upload
+            trim_silence: true,
+            max_silence_gap_ms: 0, // 0 keeps interior pauses intact
+            normalize_audio: false, // Boost quiet recordings to -3 dBFS before upload
+            mono_mixdown: "average".to_string(), // "average" | "first"
+            stt_temperature: 0.0, // 0 = not sent, the server default
+            translation_temperature: 0.3, // 0.0..=2.0
+            translation_max_tokens: 4096, // Ceiling; the request limit scales with input length
+            require_api_key: true, // localhost endpoints never require one
+            correction_style: "grammar_only".to_string(), // "verbatim" | "grammar_only" | "polished" | "concise"
+            offline_queue_enabled: true, // Failed uploads are saved to disk and retried later
+            local_formatting: false, // Capitalize + end punctuation when no correction ran
+            paste_delay_ms: 50, // Wait between setting the clipboard and sending the paste keystroke
+            powershell_fallback: true, // Windows: retry via powershell.exe when SendInput/clipboard APIs fail
+            timeout_warning_secs: 30, // Warn this long before max_recording_time_minutes; 0 = no warning
+            silence_threshold: 0.01, // Peak amplitude below which audio isn't sent to STT
+            min_duration_secs: 0.6, // Shorter audio isn't sent to STT
+            validate_api_key_on_startup: false, // Check the stored key against the endpoint at launch
+            custom_headers: HashMap::new(),
+            allow_auth_headers: false, // Lets custom_headers set Authorization/api-key
+            model_overrides: HashMap::new(),
         }
     }
 }
@@ -60,9 +239,28 @@ impl AppSettings {
     // Note: load() and save() methods removed - now using localStorage-only approach
     // AppSettings struct is kept for internal backend operations like tray menu updates
 
+    /// STT model for the spoken language
+    pub fn effective_stt_model(&self) -> &str {
+        resolve_stt_model(&self.model_overrides, &self.spoken_language, &self.stt_model)
+    }
+
+    /// Translation model for the target language (the spoken language when only correcting)
+    pub fn effective_translation_model(&self) -> &str {
+        let language = if self.translation_enabled && self.translation_language != "none" {
+            &self.translation_language
+        } else {
+            &self.spoken_language
+        };
+        resolve_translation_model(&self.model_overrides, language, &self.translation_model)
+    }
+
     /// Get API key from secure storage
     pub fn get_api_key(&self, _app_handle: &AppHandle) -> Result<String, String> {
-        // Try OS keyring first
+        // A session copy only exists when the last keyring write failed, so it is the newest key
+        if let Some(key) = SESSION_API_KEY.lock().ok().and_then(|key| key.clone()) {
+            return Ok(key);
+        }
+
         let service = "talktome_api_key";
         let username = whoami::username();
         let entry = Entry::new(service, &username);
@@ -78,8 +276,8 @@ impl AppSettings {
         }
     }
 
-    /// Store API key securely (keyring only, no file fallback)
-    pub fn store_api_key(&self, _app_handle: &AppHandle, api_key: String) -> Result<(), String> {
+    /// Store API key securely (keyring, or memory for this session only; never a file)
+    pub fn store_api_key(&self, _app_handle: &AppHandle, api_key: String) -> Result<ApiKeyStorage, String> {
         // Validate API key
         let trimmed_key = api_key.trim();
         if trimmed_key.is_empty() {
@@ -91,19 +289,53 @@ impl AppSettings {
         let username = whoami::username();
         let entry = Entry::new(service, &username);
 
-        match entry.set_password(trimmed_key) {
+        let stored = match entry.set_password(trimmed_key) {
             Ok(_) => {
                 println!("API_KEY: Successfully stored in keyring");
-                Ok(())
+                ApiKeyStorage::Keyring
             }
             Err(e) => {
                 println!("API_KEY: Failed to store in keyring: {}", e);
                 // Do NOT fallback to file-based storage for security reasons
-                Err(format!("Failed to store API key in secure storage: {}", e))
+                ApiKeyStorage::SessionOnly(e.to_string())
+            }
+        };
+        let mut session_key = SESSION_API_KEY
+            .lock()
+            .map_err(|e| format!("Failed to lock session API key: {}", e))?;
+        *session_key = match stored {
+            ApiKeyStorage::Keyring => None,
+            ApiKeyStorage::SessionOnly(_) => Some(trimmed_key.to_string()),
+        };
+        Ok(stored)
+    }
+
+    /// Remove the API key from the keyring; Ok(false) when there was none
+    pub fn delete_api_key(&self, _app_handle: &AppHandle) -> Result<bool, String> {
+        let had_session_key = SESSION_API_KEY
+            .lock()
+            .map(|mut key| key.take().is_some())
+            .unwrap_or(false);
+        let service = "talktome_api_key";
+        let username = whoami::username();
+        let entry = Entry::new(service, &username);
+
+        match entry.delete_password() {
+            Ok(()) => {
+                crate::debug_logger::DebugLogger::log_info("API_KEY: Removed from keyring");
+                Ok(true)
             }
+            Err(keyring::Error::NoEntry) => Ok(had_session_key),
+            Err(e) => Err(format!("Failed to remove API key from secure storage: {}", e)),
         }
     }
 
+    /// Whether the OS keyring can be reached (a missing entry still counts as reachable)
+    pub fn keyring_available(&self) -> bool {
+        let entry = Entry::new("talktome_api_key", &whoami::username());
+        matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry))
+    }
+
     /// Check if API key exists
     pub fn has_api_key(&self, app_handle: &AppHandle) -> bool {
         self.get_api_key(app_handle).is_ok()
@@ -142,6 +374,7 @@ impl AppSettings {
         let username = whoami::username();
         let entry = Entry::new(service, &username);
 
+        let session_only = SESSION_API_KEY.lock().map(|key| key.is_some()).unwrap_or(false);
         match entry.get_password() {
             Ok(pw) => {
                 let len = pw.len();
@@ -155,13 +388,15 @@ impl AppSettings {
                     "username": username,
                     "exists": true,
                     "length": len,
-                    "preview": preview
+                    "preview": preview,
+                    "session_only": session_only
                 }))
             }
             Err(_) => Ok(json!({
                 "service": service,
                 "username": username,
-                "exists": false
+                "exists": false,
+                "session_only": session_only
             })),
         }
     }
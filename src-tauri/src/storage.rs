@@ -1,46 +1,273 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::AppHandle;
+
+use crate::settings::ModelOverride;
+use crate::text_format::{SpokenPunctuation, TextReplacement};
 use tauri_plugin_store::StoreExt;
 
+/// Schema version written with every save; bump it and extend `migrate_settings_value`
+/// whenever stored fields are renamed or change meaning
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Keys missing from the JSON (older stores, or a frontend that doesn't know a newer field)
+/// take their values from `Default`, so one absent field never rejects the whole object
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
 pub struct PersistentSettings {
+    // Missing in stores written before versioning, which reads as version 0
+    #[serde(default)]
+    pub version: u32,
     pub spoken_language: String,
     pub translation_language: String,
     pub audio_device: String,
+    pub capture_source: String,
+    pub use_detected_language: bool,
+    pub text_replacements: Vec<TextReplacement>,
+    pub wav_dump_retention: u32,
+    pub max_concurrent_requests: u32,
+    pub min_recording_ms: u32,
+    pub api_mode: String,
+    pub local_only: bool,
+    pub denoise_strength: f32,
+    pub spoken_punctuation: bool,
+    pub spoken_punctuation_words: Vec<SpokenPunctuation>,
+    pub insert_prefix: String,
+    pub insert_suffix: String,
+    pub dedup_window_tokens: u32,
+    pub chunk_overlap_ms: u32,
     pub theme: String,
     pub api_endpoint: String,
     pub stt_model: String,
     pub translation_model: String,
     pub hands_free_hotkey: String,
+    pub start_recording_hotkey: String,
+    pub stop_recording_hotkey: String,
+    pub cancel_hotkey: String,
+    pub reinsert_hotkey: String,
     pub auto_mute: bool,
     pub translation_enabled: bool,
     pub debug_logging: bool,
     pub text_insertion_enabled: bool,
     pub max_recording_time_minutes: u32,
+    pub hotkey_mode: String,
+    pub hotkey_debounce_ms: u32,
+    pub tap_threshold_ms: u32,
+    pub restore_clipboard: bool,
+    pub preserve_clipboard: bool,
+    pub notifications: String,
+    pub insertion_method: String,
+    pub auto_mute_mode: String,
+    pub duck_level: u32,
+    pub api_flavor: String,
+    pub upload_format: String,
+    pub response_format: String,
+    pub stt_prompt: String,
+    pub request_timeout_secs: u32,
+    pub max_retries: u32,
+    pub translation_prompt_template: String,
+    pub history_enabled: bool,
+    pub output_mode: String,
+    pub max_log_mb: u32,
+    pub log_format: String,
+    pub log_level: String,
+    pub noise_reduction_enabled: bool,
+    pub pre_roll_ms: u32,
+    pub max_upload_mb: u32,
+    pub trim_silence: bool,
+    pub max_silence_gap_ms: u32,
+    pub normalize_audio: bool,
+    pub mono_mixdown: String,
+    pub stt_temperature: f32,
+    pub translation_temperature: f32,
+    pub translation_max_tokens: u32,
+    pub require_api_key: bool,
+    pub correction_style: String,
+    pub offline_queue_enabled: bool,
+    pub local_formatting: bool,
+    pub paste_delay_ms: u32,
+    pub powershell_fallback: bool,
+    pub timeout_warning_secs: u32,
+    pub silence_threshold: f32,
+    pub min_duration_secs: f32,
+    pub validate_api_key_on_startup: bool,
+    pub custom_headers: HashMap<String, String>,
+    pub allow_auth_headers: bool,
+    pub model_overrides: HashMap<String, ModelOverride>,
 }
 
 impl Default for PersistentSettings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             spoken_language: "auto".to_string(),
             translation_language: "en".to_string(),
             audio_device: "default".to_string(),
+            capture_source: "microphone".to_string(),
+            use_detected_language: true,
+            text_replacements: Vec::new(),
+            wav_dump_retention: 20,
+            max_concurrent_requests: crate::retry::DEFAULT_MAX_CONCURRENT_REQUESTS,
+            min_recording_ms: 0,
+            api_mode: "chat_completions".to_string(),
+            local_only: false,
+            denoise_strength: 1.0,
+            spoken_punctuation: false,
+            spoken_punctuation_words: crate::text_format::default_spoken_punctuation(),
+            insert_prefix: String::new(),
+            insert_suffix: " ".to_string(),
+            dedup_window_tokens: crate::text_format::DEFAULT_DEDUP_WINDOW_TOKENS,
+            chunk_overlap_ms: 500,
             theme: "auto".to_string(),
             api_endpoint: "https://api.openai.com/v1".to_string(),
             stt_model: "whisper-large-v3".to_string(),
             translation_model: "gpt-3.5-turbo".to_string(),
             hands_free_hotkey: "Ctrl+Shift+Space".to_string(),
+            start_recording_hotkey: String::new(),
+            stop_recording_hotkey: String::new(),
+            cancel_hotkey: String::new(),
+            reinsert_hotkey: String::new(),
             auto_mute: true,
             translation_enabled: false,
             debug_logging: false,
             text_insertion_enabled: true,
             max_recording_time_minutes: 2,
+            hotkey_mode: "toggle".to_string(),
+            hotkey_debounce_ms: 150,
+            tap_threshold_ms: 0,
+            restore_clipboard: true,
+            preserve_clipboard: false,
+            notifications: "all".to_string(),
+            insertion_method: "paste".to_string(),
+            auto_mute_mode: "mute".to_string(),
+            duck_level: 20,
+            api_flavor: "openai".to_string(),
+            upload_format: "wav".to_string(),
+            response_format: "json".to_string(),
+            stt_prompt: String::new(),
+            request_timeout_secs: 15,
+            max_retries: 2,
+            translation_prompt_template: String::new(),
+            history_enabled: false,
+            output_mode: "insert".to_string(),
+            max_log_mb: 10,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            noise_reduction_enabled: true,
+            pre_roll_ms: 0,
+            max_upload_mb: 25,
+            trim_silence: true,
+            max_silence_gap_ms: 0,
+            normalize_audio: false,
+            mono_mixdown: "average".to_string(),
+            stt_temperature: 0.0,
+            translation_temperature: 0.3,
+            translation_max_tokens: 4096,
+            require_api_key: true,
+            correction_style: "grammar_only".to_string(),
+            offline_queue_enabled: true,
+            local_formatting: false,
+            paste_delay_ms: 50,
+            powershell_fallback: true,
+            timeout_warning_secs: 30,
+            silence_threshold: 0.01,
+            min_duration_secs: 0.6,
+            validate_api_key_on_startup: false,
+            custom_headers: HashMap::new(),
+            allow_auth_headers: false,
+            model_overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Upgrade a stored settings object to `SETTINGS_VERSION`: renames legacy keys and fills
+/// fields that older versions didn't have with defaults. Returns true if anything changed.
+pub fn migrate_settings_value(value: &mut serde_json::Value) -> bool {
+    let Some(obj) = value.as_object_mut() else {
+        return false;
+    };
+    let stored_version = obj.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    let mut changed = false;
+
+    if stored_version < 1 {
+        // v0 -> v1: early frontend builds wrote camelCase keys and a nested hotkeys object
+        let legacy_hotkey = obj
+            .get("hotkeys")
+            .and_then(|h| h.get("handsFree").or_else(|| h.get("hands_free")))
+            .cloned();
+        if let Some(hotkey) = legacy_hotkey {
+            obj.entry("hands_free_hotkey").or_insert(hotkey);
+            obj.remove("hotkeys");
+            changed = true;
+        }
+        let camel_keys: Vec<String> = obj
+            .keys()
+            .filter(|k| k.chars().any(|c| c.is_ascii_uppercase()))
+            .cloned()
+            .collect();
+        for key in camel_keys {
+            if let Some(v) = obj.remove(&key) {
+                obj.entry(camel_to_snake(&key)).or_insert(v);
+                changed = true;
+            }
+        }
+    }
+
+    // Any version: fields added since the store was written get their defaults
+    if let Ok(serde_json::Value::Object(defaults)) = serde_json::to_value(PersistentSettings::default()) {
+        for (key, default) in defaults {
+            if !obj.contains_key(&key) {
+                obj.insert(key, default);
+                changed = true;
+            }
+        }
+    }
+
+    if stored_version != SETTINGS_VERSION as u64 {
+        obj.insert("version".to_string(), serde_json::json!(SETTINGS_VERSION));
+        changed = true;
+    }
+    changed
+}
+
+fn camel_to_snake(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
         }
     }
+    out
 }
 
 pub struct SettingsStore;
 
+// Serializes update_field's read-modify-write of the store
+static UPDATE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn invalid_value(field: &str, expected: &str, value: &serde_json::Value) -> String {
+    format!("Invalid value for {}: expected {}, got {}", field, expected, value)
+}
+
+fn expect_str<'a>(field: &str, value: &'a serde_json::Value) -> Result<&'a str, String> {
+    value.as_str().ok_or_else(|| invalid_value(field, "a string", value))
+}
+
+fn expect_bool(field: &str, value: &serde_json::Value) -> Result<bool, String> {
+    value.as_bool().ok_or_else(|| invalid_value(field, "true or false", value))
+}
+
+fn expect_u64(field: &str, value: &serde_json::Value) -> Result<u64, String> {
+    value.as_u64().ok_or_else(|| invalid_value(field, "a non-negative integer", value))
+}
+
+fn expect_f64(field: &str, value: &serde_json::Value) -> Result<f64, String> {
+    value.as_f64().ok_or_else(|| invalid_value(field, "a number", value))
+}
+
 impl SettingsStore {
     const STORE_FILE: &'static str = "talktome-settings.dat";
     const SETTINGS_KEY: &'static str = "app-settings";
@@ -51,9 +278,17 @@ impl SettingsStore {
             .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
 
         match store.get(Self::SETTINGS_KEY) {
-            Some(value) => {
-                match serde_json::from_value::<PersistentSettings>(value.clone()) {
+            Some(mut value) => {
+                let from_version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+                let migrated = migrate_settings_value(&mut value);
+                match serde_json::from_value::<PersistentSettings>(value) {
                     Ok(settings) => {
+                        if migrated {
+                            crate::debug_logger::DebugLogger::log_info(&format!("Migrated persistent settings from version {} to {}", from_version, SETTINGS_VERSION));
+                            if let Err(e) = Self::save(app, &settings) {
+                                crate::debug_logger::DebugLogger::log_warn(&format!("Failed to save migrated settings: {}", e));
+                            }
+                        }
                         crate::debug_logger::DebugLogger::log_info(&format!("Loaded persistent settings from store: spoken_language={}, translation_language={}, api_endpoint={}, stt_model={}",
                             settings.spoken_language, settings.translation_language, settings.api_endpoint, settings.stt_model));
                         Ok(settings)
@@ -81,8 +316,10 @@ impl SettingsStore {
             .store(Self::STORE_FILE)
             .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
 
-        let value = serde_json::to_value(settings)
+        let mut value = serde_json::to_value(settings)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        // Whatever shape the caller built, what lands on disk is the current schema
+        value["version"] = serde_json::json!(SETTINGS_VERSION);
 
         store.set(Self::SETTINGS_KEY.to_string(), value);
         
@@ -95,78 +332,275 @@ impl SettingsStore {
         Ok(())
     }
 
+    /// Current value of one stored field
+    pub fn get_field(app: &AppHandle, field: &str) -> Result<serde_json::Value, String> {
+        let value = serde_json::to_value(Self::load(app)?)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        value
+            .get(field)
+            .cloned()
+            .ok_or_else(|| format!("Unknown field: {}", field))
+    }
+
+    /// Set one field and save. A value of the wrong type or out of range is an error rather than
+    /// a silent no-op; numeric fields with a soft limit are clamped.
     pub fn update_field(
         app: &AppHandle,
         field: &str,
         value: serde_json::Value,
     ) -> Result<(), String> {
+        // Load-modify-save as one step so concurrent updates don't drop each other's change
+        let _guard = UPDATE_LOCK.lock().map_err(|e| format!("Failed to lock settings: {}", e))?;
         let mut settings = Self::load(app)?;
 
         match field {
             "spoken_language" => {
-                if let Some(s) = value.as_str() {
-                    settings.spoken_language = s.to_string();
-                }
+                settings.spoken_language = expect_str(field, &value)?.to_string();
             }
             "translation_language" => {
-                if let Some(s) = value.as_str() {
-                    settings.translation_language = s.to_string();
-                }
+                settings.translation_language = expect_str(field, &value)?.to_string();
             }
             "audio_device" => {
-                if let Some(s) = value.as_str() {
-                    settings.audio_device = s.to_string();
+                settings.audio_device = expect_str(field, &value)?.to_string();
+            }
+            "capture_source" => {
+                settings.capture_source = expect_str(field, &value)?.to_string();
+            }
+            "use_detected_language" => {
+                settings.use_detected_language = expect_bool(field, &value)?;
+            }
+            "text_replacements" => {
+                let rules: Vec<TextReplacement> = serde_json::from_value(value)
+                    .map_err(|e| format!("Invalid text replacements: {}", e))?;
+                for rule in &rules {
+                    rule.compile()?;
                 }
+                settings.text_replacements = rules;
             }
-            "theme" => {
-                if let Some(s) = value.as_str() {
-                    settings.theme = s.to_string();
+            "wav_dump_retention" => {
+                settings.wav_dump_retention = expect_u64(field, &value)?.clamp(1, 1000) as u32;
+            }
+            "max_concurrent_requests" => {
+                settings.max_concurrent_requests = expect_u64(field, &value)?.clamp(1, 8) as u32;
+            }
+            "min_recording_ms" => {
+                settings.min_recording_ms = expect_u64(field, &value)?.min(5000) as u32;
+            }
+            "api_mode" => {
+                settings.api_mode = expect_str(field, &value)?.to_string();
+            }
+            "local_only" => {
+                settings.local_only = expect_bool(field, &value)?;
+            }
+            "denoise_strength" => {
+                settings.denoise_strength = (expect_f64(field, &value)? as f32).clamp(0.0, 1.0);
+            }
+            "spoken_punctuation" => {
+                settings.spoken_punctuation = expect_bool(field, &value)?;
+            }
+            "spoken_punctuation_words" => {
+                let words: Vec<SpokenPunctuation> = serde_json::from_value(value)
+                    .map_err(|e| format!("Invalid spoken punctuation words: {}", e))?;
+                if words.iter().any(|w| w.phrase.trim().is_empty()) {
+                    return Err("Spoken punctuation entry has an empty phrase".to_string());
                 }
+                settings.spoken_punctuation_words = words;
+            }
+            "insert_prefix" => {
+                settings.insert_prefix = expect_str(field, &value)?.to_string();
+            }
+            "insert_suffix" => {
+                settings.insert_suffix = expect_str(field, &value)?.to_string();
+            }
+            "dedup_window_tokens" => {
+                settings.dedup_window_tokens = expect_u64(field, &value)?.min(50) as u32;
+            }
+            "chunk_overlap_ms" => {
+                settings.chunk_overlap_ms = expect_u64(field, &value)?.min(5000) as u32;
+            }
+            "theme" => {
+                settings.theme = expect_str(field, &value)?.to_string();
             }
             "api_endpoint" => {
-                if let Some(s) = value.as_str() {
-                    settings.api_endpoint = s.to_string();
-                }
+                settings.api_endpoint = expect_str(field, &value)?.to_string();
             }
             "stt_model" => {
-                if let Some(s) = value.as_str() {
-                    settings.stt_model = s.to_string();
-                }
+                settings.stt_model = expect_str(field, &value)?.to_string();
             }
             "translation_model" => {
-                if let Some(s) = value.as_str() {
-                    settings.translation_model = s.to_string();
-                }
+                settings.translation_model = expect_str(field, &value)?.to_string();
             }
             "hands_free_hotkey" => {
-                if let Some(s) = value.as_str() {
-                    settings.hands_free_hotkey = s.to_string();
-                }
+                settings.hands_free_hotkey = expect_str(field, &value)?.to_string();
+            }
+            "start_recording_hotkey" => {
+                settings.start_recording_hotkey = expect_str(field, &value)?.to_string();
+            }
+            "stop_recording_hotkey" => {
+                settings.stop_recording_hotkey = expect_str(field, &value)?.to_string();
+            }
+            "cancel_hotkey" => {
+                settings.cancel_hotkey = expect_str(field, &value)?.to_string();
+            }
+            "reinsert_hotkey" => {
+                settings.reinsert_hotkey = expect_str(field, &value)?.to_string();
             }
             "auto_mute" => {
-                if let Some(b) = value.as_bool() {
-                    settings.auto_mute = b;
-                }
+                settings.auto_mute = expect_bool(field, &value)?;
             }
             "translation_enabled" => {
-                if let Some(b) = value.as_bool() {
-                    settings.translation_enabled = b;
-                }
+                settings.translation_enabled = expect_bool(field, &value)?;
             }
             "debug_logging" => {
-                if let Some(b) = value.as_bool() {
-                    settings.debug_logging = b;
-                }
+                settings.debug_logging = expect_bool(field, &value)?;
             }
             "text_insertion_enabled" => {
-                if let Some(b) = value.as_bool() {
-                    settings.text_insertion_enabled = b;
-                }
+                settings.text_insertion_enabled = expect_bool(field, &value)?;
             }
             "max_recording_time_minutes" => {
-                if let Some(n) = value.as_u64() {
-                    settings.max_recording_time_minutes = n as u32;
+                let n = expect_u64(field, &value)?;
+                if !(1..=60).contains(&n) {
+                    return Err(format!("max_recording_time_minutes must be between 1 and 60 (got {})", n));
+                }
+                settings.max_recording_time_minutes = n as u32;
+            }
+            "hotkey_mode" => {
+                settings.hotkey_mode = expect_str(field, &value)?.to_string();
+            }
+            "hotkey_debounce_ms" => {
+                settings.hotkey_debounce_ms = expect_u64(field, &value)?.min(1000) as u32;
+            }
+            "tap_threshold_ms" => {
+                settings.tap_threshold_ms = expect_u64(field, &value)?.min(1000) as u32;
+            }
+            "restore_clipboard" => {
+                settings.restore_clipboard = expect_bool(field, &value)?;
+            }
+            "preserve_clipboard" => {
+                settings.preserve_clipboard = expect_bool(field, &value)?;
+            }
+            "notifications" => {
+                settings.notifications = expect_str(field, &value)?.to_string();
+            }
+            "insertion_method" => {
+                settings.insertion_method = expect_str(field, &value)?.to_string();
+            }
+            "auto_mute_mode" => {
+                settings.auto_mute_mode = expect_str(field, &value)?.to_string();
+            }
+            "duck_level" => {
+                settings.duck_level = expect_u64(field, &value)?.min(100) as u32;
+            }
+            "api_flavor" => {
+                settings.api_flavor = expect_str(field, &value)?.to_string();
+            }
+            "upload_format" => {
+                settings.upload_format = expect_str(field, &value)?.to_string();
+            }
+            "response_format" => {
+                settings.response_format = expect_str(field, &value)?.to_string();
+            }
+            "stt_prompt" => {
+                settings.stt_prompt = expect_str(field, &value)?.to_string();
+            }
+            "request_timeout_secs" => {
+                settings.request_timeout_secs = expect_u64(field, &value)?.clamp(5, 300) as u32;
+            }
+            "max_retries" => {
+                settings.max_retries = expect_u64(field, &value)?.min(10) as u32;
+            }
+            "translation_prompt_template" => {
+                settings.translation_prompt_template = expect_str(field, &value)?.to_string();
+            }
+            "history_enabled" => {
+                settings.history_enabled = expect_bool(field, &value)?;
+            }
+            "output_mode" => {
+                settings.output_mode = expect_str(field, &value)?.to_string();
+            }
+            "max_log_mb" => {
+                settings.max_log_mb = expect_u64(field, &value)?.clamp(1, 1024) as u32;
+            }
+            "log_format" => {
+                settings.log_format = expect_str(field, &value)?.to_string();
+            }
+            "log_level" => {
+                settings.log_level = expect_str(field, &value)?.to_string();
+            }
+            "noise_reduction_enabled" => {
+                settings.noise_reduction_enabled = expect_bool(field, &value)?;
+            }
+            "pre_roll_ms" => {
+                settings.pre_roll_ms = expect_u64(field, &value)?.min(2000) as u32;
+            }
+            "max_upload_mb" => {
+                settings.max_upload_mb = expect_u64(field, &value)?.clamp(1, 500) as u32;
+            }
+            "trim_silence" => {
+                settings.trim_silence = expect_bool(field, &value)?;
+            }
+            "max_silence_gap_ms" => {
+                settings.max_silence_gap_ms = expect_u64(field, &value)?.min(10_000) as u32;
+            }
+            "normalize_audio" => {
+                settings.normalize_audio = expect_bool(field, &value)?;
+            }
+            "mono_mixdown" => {
+                settings.mono_mixdown = expect_str(field, &value)?.to_string();
+            }
+            "stt_temperature" => {
+                settings.stt_temperature = expect_f64(field, &value)?.clamp(0.0, 1.0) as f32;
+            }
+            "translation_temperature" => {
+                settings.translation_temperature = expect_f64(field, &value)?.clamp(0.0, 2.0) as f32;
+            }
+            "translation_max_tokens" => {
+                settings.translation_max_tokens = expect_u64(field, &value)?.clamp(1, 32_000) as u32;
+            }
+            "require_api_key" => {
+                settings.require_api_key = expect_bool(field, &value)?;
+            }
+            "correction_style" => {
+                settings.correction_style = expect_str(field, &value)?.to_string();
+            }
+            "offline_queue_enabled" => {
+                settings.offline_queue_enabled = expect_bool(field, &value)?;
+            }
+            "local_formatting" => {
+                settings.local_formatting = expect_bool(field, &value)?;
+            }
+            "paste_delay_ms" => {
+                settings.paste_delay_ms = expect_u64(field, &value)?.min(2000) as u32;
+            }
+            "powershell_fallback" => {
+                settings.powershell_fallback = expect_bool(field, &value)?;
+            }
+            "timeout_warning_secs" => {
+                settings.timeout_warning_secs = expect_u64(field, &value)?.min(600) as u32;
+            }
+            "silence_threshold" => {
+                settings.silence_threshold = expect_f64(field, &value)?.clamp(0.0, 1.0) as f32;
+            }
+            "min_duration_secs" => {
+                settings.min_duration_secs = expect_f64(field, &value)?.clamp(0.0, 10.0) as f32;
+            }
+            "validate_api_key_on_startup" => {
+                settings.validate_api_key_on_startup = expect_bool(field, &value)?;
+            }
+            "custom_headers" => {
+                let headers: HashMap<String, String> = serde_json::from_value(value)
+                    .map_err(|e| format!("Invalid custom headers: {}", e))?;
+                for (name, value) in &headers {
+                    crate::api_flavor::check_custom_header(name.trim(), value.trim(), settings.allow_auth_headers)?;
                 }
+                settings.custom_headers = headers;
+            }
+            "allow_auth_headers" => {
+                settings.allow_auth_headers = expect_bool(field, &value)?;
+            }
+            "model_overrides" => {
+                settings.model_overrides = serde_json::from_value(value)
+                    .map_err(|e| format!("Invalid model overrides: {}", e))?;
             }
             _ => return Err(format!("Unknown field: {}", field)),
         }
@@ -175,3 +609,402 @@ impl SettingsStore {
         Ok(())
     }
 }
+
+/// Empty every store file (settings and profiles, history, usage metrics) and write it back to disk.
+/// Returns the files that were cleared.
+pub fn purge_stores(app: &AppHandle) -> Result<Vec<&'static str>, String> {
+    let files = [SettingsStore::STORE_FILE, HistoryStore::STORE_FILE, MetricsStore::STORE_FILE];
+    for file in files {
+        let store = app
+            .store(file)
+            .map_err(|e| format!("Failed to open store '{}': {}", file, e))?;
+        store.clear();
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store to disk: {}", e))?;
+    }
+    Ok(files.to_vec())
+}
+
+/// Names of the saved profiles plus the one currently active
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<String>,
+}
+
+/// Named snapshots of `PersistentSettings`. The active profile's values always live in the
+/// regular settings key, so everything that reads `SettingsStore::load` follows the active profile.
+pub struct ProfileStore;
+
+impl ProfileStore {
+    const STORE_FILE: &'static str = "talktome-settings.dat";
+    const PROFILES_KEY: &'static str = "profiles";
+    const ACTIVE_KEY: &'static str = "active-profile";
+    pub const DEFAULT_PROFILE: &'static str = "default";
+
+    fn load_profiles(app: &AppHandle) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+        let store = app
+            .store(Self::STORE_FILE)
+            .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
+        match store.get(Self::PROFILES_KEY) {
+            Some(serde_json::Value::Object(profiles)) => Ok(profiles),
+            _ => Ok(serde_json::Map::new()),
+        }
+    }
+
+    fn save_profiles(app: &AppHandle, profiles: serde_json::Map<String, serde_json::Value>, active: &str) -> Result<(), String> {
+        let store = app
+            .store(Self::STORE_FILE)
+            .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
+        store.set(Self::PROFILES_KEY.to_string(), serde_json::Value::Object(profiles));
+        store.set(Self::ACTIVE_KEY.to_string(), serde_json::json!(active));
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store to disk: {}", e))
+    }
+
+    pub fn active(app: &AppHandle) -> Result<String, String> {
+        let store = app
+            .store(Self::STORE_FILE)
+            .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
+        Ok(store
+            .get(Self::ACTIVE_KEY)
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| Self::DEFAULT_PROFILE.to_string()))
+    }
+
+    pub fn list(app: &AppHandle) -> Result<ProfileList, String> {
+        let active = Self::active(app)?;
+        let mut profiles: Vec<String> = Self::load_profiles(app)?.keys().cloned().collect();
+        if !profiles.contains(&active) {
+            profiles.push(active.clone());
+        }
+        profiles.sort();
+        Ok(ProfileList { active, profiles })
+    }
+
+    /// Save the current settings under `name` (and the outgoing active profile) and make it active
+    pub fn save_as(app: &AppHandle, name: &str) -> Result<(), String> {
+        let name = validate_profile_name(name)?;
+        let settings = SettingsStore::load(app)?;
+        let mut profiles = Self::load_profiles(app)?;
+        let current = serde_json::to_value(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        profiles.insert(Self::active(app)?, current.clone());
+        profiles.insert(name.to_string(), current);
+        Self::save_profiles(app, profiles, name)?;
+        crate::debug_logger::DebugLogger::log_info(&format!("Saved settings profile '{}'", name));
+        Ok(())
+    }
+
+    /// Snapshot the active profile, then load `name` into the live settings
+    pub fn switch(app: &AppHandle, name: &str) -> Result<PersistentSettings, String> {
+        let name = validate_profile_name(name)?;
+        let mut profiles = Self::load_profiles(app)?;
+        let active = Self::active(app)?;
+        let current = serde_json::to_value(SettingsStore::load(app)?)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        profiles.insert(active, current);
+
+        // Read after the snapshot so switching to the active profile keeps unsaved edits
+        let mut target = profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Profile '{}' does not exist", name))?;
+
+        migrate_settings_value(&mut target);
+        let settings: PersistentSettings = serde_json::from_value(target)
+            .map_err(|e| format!("Failed to read profile '{}': {}", name, e))?;
+        SettingsStore::save(app, &settings)?;
+        Self::save_profiles(app, profiles, name)?;
+        crate::debug_logger::DebugLogger::log_info(&format!("Switched to settings profile '{}'", name));
+        Ok(settings)
+    }
+
+    pub fn delete(app: &AppHandle, name: &str) -> Result<(), String> {
+        let active = Self::active(app)?;
+        if name == active {
+            return Err(format!("Cannot delete the active profile '{}'; switch to another profile first", name));
+        }
+        let mut profiles = Self::load_profiles(app)?;
+        if profiles.remove(name).is_none() {
+            return Err(format!("Profile '{}' does not exist", name));
+        }
+        Self::save_profiles(app, profiles, &active)?;
+        crate::debug_logger::DebugLogger::log_info(&format!("Deleted settings profile '{}'", name));
+        Ok(())
+    }
+}
+
+fn validate_profile_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if name.len() > 64 {
+        return Err("Profile name must be at most 64 characters".to_string());
+    }
+    Ok(name)
+}
+
+/// One finished dictation, kept so text can be recovered if insertion fails
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub timestamp: String,
+    pub raw_text: String,
+    pub processed_text: String,
+    pub source_language: String,
+    pub target_language: String,
+}
+
+pub struct HistoryStore;
+
+impl HistoryStore {
+    const STORE_FILE: &'static str = "talktome-history.dat";
+    const ENTRIES_KEY: &'static str = "entries";
+    const MAX_ENTRIES: usize = 500;
+
+    fn load_entries(app: &AppHandle) -> Result<Vec<HistoryEntry>, String> {
+        let store = app
+            .store(Self::STORE_FILE)
+            .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
+
+        Ok(store
+            .get(Self::ENTRIES_KEY)
+            .and_then(|value| serde_json::from_value::<Vec<HistoryEntry>>(value).ok())
+            .unwrap_or_default())
+    }
+
+    fn save_entries(app: &AppHandle, entries: &[HistoryEntry]) -> Result<(), String> {
+        let store = app
+            .store(Self::STORE_FILE)
+            .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
+
+        let value = serde_json::to_value(entries)
+            .map_err(|e| format!("Failed to serialize history: {}", e))?;
+        store.set(Self::ENTRIES_KEY.to_string(), value);
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store to disk: {}", e))
+    }
+
+    /// Append an entry, dropping the oldest ones beyond `MAX_ENTRIES`
+    pub fn append(app: &AppHandle, entry: HistoryEntry) -> Result<(), String> {
+        let mut entries = Self::load_entries(app)?;
+        entries.push(entry);
+        if entries.len() > Self::MAX_ENTRIES {
+            let excess = entries.len() - Self::MAX_ENTRIES;
+            entries.drain(..excess);
+        }
+        Self::save_entries(app, &entries)
+    }
+
+    /// Most recent entries first
+    pub fn recent(app: &AppHandle, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        let entries = Self::load_entries(app)?;
+        Ok(entries.into_iter().rev().take(limit).collect())
+    }
+
+    pub fn clear(app: &AppHandle) -> Result<(), String> {
+        Self::save_entries(app, &[])?;
+        crate::debug_logger::DebugLogger::log_info("Cleared transcription history");
+        Ok(())
+    }
+}
+
+/// Timings and counts for one recording session
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionMetrics {
+    pub timestamp: String,
+    /// Seconds of audio captured
+    pub capture_secs: f32,
+    /// Summed over every STT request of the session
+    pub stt_latency_ms: u64,
+    /// None when correction/translation didn't run
+    pub translation_latency_ms: Option<u64>,
+    /// From recording start to the end of processing
+    pub total_ms: u64,
+    pub words: usize,
+    pub success: bool,
+}
+
+/// Running totals over all sessions, kept apart from the capped session list so nothing is lost
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct UsageTotals {
+    pub sessions: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub total_words: u64,
+    pub total_capture_secs: f64,
+    pub total_stt_ms: u64,
+    pub translations: u64,
+    pub total_translation_ms: u64,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, session: &SessionMetrics) {
+        self.sessions += 1;
+        if session.success {
+            self.successes += 1;
+        } else {
+            self.failures += 1;
+        }
+        self.total_words += session.words as u64;
+        self.total_capture_secs += session.capture_secs as f64;
+        self.total_stt_ms += session.stt_latency_ms;
+        if let Some(ms) = session.translation_latency_ms {
+            self.translations += 1;
+            self.total_translation_ms += ms;
+        }
+    }
+
+    /// Aggregates returned by `get_usage_metrics`
+    pub fn summary(&self) -> serde_json::Value {
+        let average = |total: u64, count: u64| if count == 0 { 0 } else { total / count };
+        serde_json::json!({
+            "sessions": self.sessions,
+            "success_count": self.successes,
+            "failure_count": self.failures,
+            "total_words": self.total_words,
+            "total_minutes": self.total_capture_secs / 60.0,
+            "average_stt_latency_ms": average(self.total_stt_ms, self.sessions),
+            "average_translation_latency_ms": average(self.total_translation_ms, self.translations)
+        })
+    }
+}
+
+pub struct MetricsStore;
+
+impl MetricsStore {
+    const STORE_FILE: &'static str = "talktome-metrics.dat";
+    const TOTALS_KEY: &'static str = "totals";
+    const SESSIONS_KEY: &'static str = "sessions";
+    const MAX_SESSIONS: usize = 1000;
+
+    pub fn record(app: &AppHandle, session: SessionMetrics) -> Result<(), String> {
+        let store = app
+            .store(Self::STORE_FILE)
+            .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
+
+        let mut totals: UsageTotals = store
+            .get(Self::TOTALS_KEY)
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        let mut sessions: Vec<SessionMetrics> = store
+            .get(Self::SESSIONS_KEY)
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        totals.add(&session);
+        sessions.push(session);
+        if sessions.len() > Self::MAX_SESSIONS {
+            let excess = sessions.len() - Self::MAX_SESSIONS;
+            sessions.drain(..excess);
+        }
+
+        let totals = serde_json::to_value(&totals)
+            .map_err(|e| format!("Failed to serialize usage totals: {}", e))?;
+        let sessions = serde_json::to_value(&sessions)
+            .map_err(|e| format!("Failed to serialize session metrics: {}", e))?;
+        store.set(Self::TOTALS_KEY.to_string(), totals);
+        store.set(Self::SESSIONS_KEY.to_string(), sessions);
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store to disk: {}", e))
+    }
+
+    /// Aggregates plus the most recent sessions (newest first)
+    pub fn usage(app: &AppHandle, recent: usize) -> Result<serde_json::Value, String> {
+        let store = app
+            .store(Self::STORE_FILE)
+            .map_err(|e| format!("Failed to open store '{}': {}", Self::STORE_FILE, e))?;
+
+        let totals: UsageTotals = store
+            .get(Self::TOTALS_KEY)
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        let sessions: Vec<SessionMetrics> = store
+            .get(Self::SESSIONS_KEY)
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        let mut usage = totals.summary();
+        usage["recent"] = serde_json::to_value(sessions.into_iter().rev().take(recent).collect::<Vec<_>>())
+            .map_err(|e| format!("Failed to serialize session metrics: {}", e))?;
+        Ok(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_totals_summary() {
+        let session = |words, success, translation_latency_ms| SessionMetrics {
+            timestamp: String::new(),
+            capture_secs: 30.0,
+            stt_latency_ms: 900,
+            translation_latency_ms,
+            total_ms: 2000,
+            words,
+            success,
+        };
+        let mut totals = UsageTotals::default();
+        totals.add(&session(40, true, Some(600)));
+        totals.add(&session(0, false, None));
+        let summary = totals.summary();
+        assert_eq!(summary["total_words"], 40);
+        assert_eq!(summary["total_minutes"], 1.0);
+        assert_eq!(summary["average_stt_latency_ms"], 900);
+        assert_eq!(summary["average_translation_latency_ms"], 600);
+        assert_eq!(summary["success_count"], 1);
+        assert_eq!(summary["failure_count"], 1);
+    }
+
+    #[test]
+    fn test_migrate_v0_settings() {
+        let mut value = serde_json::json!({
+            "spoken_language": "pt",
+            "translationLanguage": "en",
+            "hotkeys": { "handsFree": "Alt+Space" }
+        });
+        assert!(migrate_settings_value(&mut value));
+        let settings: PersistentSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.spoken_language, "pt");
+        assert_eq!(settings.translation_language, "en");
+        assert_eq!(settings.hands_free_hotkey, "Alt+Space");
+        assert_eq!(settings.api_endpoint, PersistentSettings::default().api_endpoint);
+    }
+
+    #[test]
+    fn test_partial_settings_use_defaults() {
+        let settings: PersistentSettings =
+            serde_json::from_value(serde_json::json!({ "spoken_language": "de", "max_retries": 5 })).unwrap();
+        let defaults = PersistentSettings::default();
+        assert_eq!(settings.spoken_language, "de");
+        assert_eq!(settings.max_retries, 5);
+        assert_eq!(settings.api_endpoint, defaults.api_endpoint);
+        assert_eq!(settings.hands_free_hotkey, defaults.hands_free_hotkey);
+        // Still 0 when absent, so unversioned stores are recognized as v0
+        assert_eq!(settings.version, 0);
+    }
+
+    #[test]
+    fn test_expect_typed_values() {
+        let value = serde_json::json!("yes");
+        assert_eq!(expect_str("theme", &value), Ok("yes"));
+        assert_eq!(
+            expect_bool("auto_mute", &value),
+            Err("Invalid value for auto_mute: expected true or false, got \"yes\"".to_string())
+        );
+        assert!(expect_u64("max_retries", &serde_json::json!(-1)).is_err());
+        assert_eq!(expect_f64("stt_temperature", &serde_json::json!(1)), Ok(1.0));
+    }
+
+    #[test]
+    fn test_migrate_current_settings_is_noop() {
+        let mut value = serde_json::to_value(PersistentSettings::default()).unwrap();
+        assert!(!migrate_settings_value(&mut value));
+    }
+}
@@ -1,14 +1,184 @@
+use crate::api_flavor::ApiFlavor;
+use crate::audio_encoding::{self, UploadFormat};
 use crate::debug_logger::DebugLogger;
 use reqwest;
+use serde::Serialize;
 use serde_json::Value;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Called with (bytes sent, total bytes) as an audio upload is handed to the connection
+pub type UploadProgress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Granularity of upload progress reports
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Response format requested from the transcription endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    VerboseJson,
+    /// Plain-text body with just the transcript; sidesteps endpoints whose JSON is off-spec
+    Text,
+}
+
+impl ResponseFormat {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "verbose_json" | "verbose" => ResponseFormat::VerboseJson,
+            "text" => ResponseFormat::Text,
+            _ => ResponseFormat::Json,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "json",
+            ResponseFormat::VerboseJson => "verbose_json",
+            ResponseFormat::Text => "text",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionSegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionWord {
+    pub start: f64,
+    pub end: f64,
+    pub word: String,
+}
+
+/// Why a transcription came back without text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyReason {
+    TooQuiet,
+    TooShort,
+    NoSpeech,
+}
+
+impl EmptyReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmptyReason::TooQuiet => "too_quiet",
+            EmptyReason::TooShort => "too_short",
+            EmptyReason::NoSpeech => "no_speech",
+        }
+    }
+}
+
+/// Transcribed text plus timestamps (only populated for verbose_json responses)
+#[derive(Debug, Clone, Default)]
+pub struct Transcription {
+    pub text: String,
+    pub segments: Vec<TranscriptionSegment>,
+    pub words: Vec<TranscriptionWord>,
+    /// Set when the audio was never sent because it failed the quality checks
+    pub skipped: Option<EmptyReason>,
+    /// Language the service detected, as reported (e.g. "french" from Whisper's verbose_json)
+    pub language: Option<String>,
+}
+
+impl Transcription {
+    fn skipped(reason: EmptyReason) -> Self {
+        Self {
+            skipped: Some(reason),
+            ..Default::default()
+        }
+    }
+
+    /// `None` when there is text; otherwise why there isn't (the API returning nothing is "no speech")
+    pub fn empty_reason(&self) -> Option<EmptyReason> {
+        if !self.text.trim().is_empty() {
+            return None;
+        }
+        Some(self.skipped.unwrap_or(EmptyReason::NoSpeech))
+    }
+
+    /// Shift segment and word timestamps by `secs`, for audio that started that far into the recording
+    pub fn offset_by(&mut self, secs: f64) {
+        for segment in &mut self.segments {
+            segment.start += secs;
+            segment.end += secs;
+        }
+        for word in &mut self.words {
+            word.start += secs;
+            word.end += secs;
+        }
+    }
+
+    fn from_verbose_json(text: String, json: &Value) -> Self {
+        let segments = json["segments"]
+            .as_array()
+            .map(|segments| {
+                segments
+                    .iter()
+                    .map(|s| TranscriptionSegment {
+                        start: s["start"].as_f64().unwrap_or(0.0),
+                        end: s["end"].as_f64().unwrap_or(0.0),
+                        text: s["text"].as_str().unwrap_or("").trim().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let words = json["words"]
+            .as_array()
+            .map(|words| {
+                words
+                    .iter()
+                    .map(|w| TranscriptionWord {
+                        start: w["start"].as_f64().unwrap_or(0.0),
+                        end: w["end"].as_f64().unwrap_or(0.0),
+                        word: w["word"].as_str().unwrap_or("").trim().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            text,
+            segments,
+            words,
+            skipped: None,
+            language: detected_language(json),
+        }
+    }
+}
+
+/// The `language` field of a transcription response, when present and non-empty
+fn detected_language(json: &Value) -> Option<String> {
+    json["language"]
+        .as_str()
+        .map(str::trim)
+        .filter(|language| !language.is_empty())
+        .map(str::to_string)
+}
+
 pub struct STTService {
     client: reqwest::Client,
     api_endpoint: String,
     api_key: String,
     model: String,
     spoken_language: String,
+    stt_prompt: String,
+    api_flavor: ApiFlavor,
+    upload_format: UploadFormat,
+    response_format: ResponseFormat,
+    max_retries: u32,
+    max_upload_mb: u32,
+    normalize_audio: bool,
+    temperature: f32,
+    // Audio whose peak stays below this is skipped as silence
+    silence_threshold: f32,
+    // Audio shorter than this (seconds) is skipped as too short
+    min_duration_secs: f32,
+    // Refuse to send audio anywhere but localhost
+    local_only: bool,
+    upload_progress: Option<UploadProgress>,
 }
 
 impl STTService {
@@ -17,9 +187,23 @@ impl STTService {
         api_key: String,
         model: String,
         spoken_language: String,
+        stt_prompt: String,
+        api_flavor: ApiFlavor,
+        upload_format: UploadFormat,
+        response_format: ResponseFormat,
+        request_timeout_secs: u64,
+        max_retries: u32,
+        max_upload_mb: u32,
+        normalize_audio: bool,
+        temperature: f32,
+        silence_threshold: f32,
+        min_duration_secs: f32,
+        custom_headers: reqwest::header::HeaderMap,
+        local_only: bool,
     ) -> Self {
         let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(15)) // Reduced from 30s for better UX
+            .timeout(Duration::from_secs(request_timeout_secs))
+            .default_headers(custom_headers)
             .build()
             .unwrap_or_default();
 
@@ -29,9 +213,45 @@ impl STTService {
             api_key,
             model,
             spoken_language,
+            stt_prompt,
+            api_flavor,
+            upload_format,
+            response_format,
+            max_retries,
+            max_upload_mb,
+            normalize_audio,
+            temperature,
+            silence_threshold,
+            min_duration_secs,
+            local_only,
+            upload_progress: None,
         }
     }
 
+    /// Report progress while each request's audio uploads (single recordings can be several MB)
+    pub fn with_upload_progress(mut self, progress: impl Fn(u64, u64) + Send + Sync + 'static) -> Self {
+        self.upload_progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// The multipart file body; streamed in chunks when upload progress is wanted
+    fn audio_part(&self, audio_bytes: &[u8]) -> reqwest::multipart::Part {
+        let Some(progress) = self.upload_progress.clone() else {
+            return reqwest::multipart::Part::bytes(audio_bytes.to_vec());
+        };
+        let total = audio_bytes.len() as u64;
+        let chunks: Vec<Vec<u8>> = audio_bytes.chunks(UPLOAD_CHUNK_BYTES).map(<[u8]>::to_vec).collect();
+        let mut sent = 0;
+        progress(sent, total);
+        // Each chunk is counted when the connection pulls it, so this runs at most one chunk ahead of the wire
+        let stream = futures_util::stream::iter(chunks.into_iter().map(move |chunk| {
+            sent += chunk.len() as u64;
+            progress(sent, total);
+            Ok::<_, std::io::Error>(chunk)
+        }));
+        reqwest::multipart::Part::stream_with_length(reqwest::Body::wrap_stream(stream), total)
+    }
+
     /// Transcribe audio chunk with enhanced error handling
     pub async fn transcribe_chunk(
         &self,
@@ -39,6 +259,18 @@ impl STTService {
         sample_rate: u32,
         label: Option<&str>,
     ) -> Result<String, String> {
+        self.transcribe_chunk_detailed(audio_data, sample_rate, label)
+            .await
+            .map(|transcription| transcription.text)
+    }
+
+    /// Like `transcribe_chunk`, but keeps segment/word timestamps from verbose_json responses
+    pub async fn transcribe_chunk_detailed(
+        &self,
+        audio_data: Vec<f32>,
+        sample_rate: u32,
+        label: Option<&str>,
+    ) -> Result<Transcription, String> {
         DebugLogger::log_info("=== STT: transcribe_chunk() called ===");
         DebugLogger::log_info(&format!(
             "STT: Input audio_data.len()={}, sample_rate={}",
@@ -51,55 +283,82 @@ impl STTService {
             return Err("Empty audio data".to_string());
         }
 
+        // Boost quiet microphones before the quality check so they aren't dropped as silence
+        let mut audio_data = audio_data;
+        if self.normalize_audio {
+            let gain = crate::audio::normalize_peak(&mut audio_data);
+            if gain > 1.0 {
+                DebugLogger::log_info(&format!("STT: Normalized audio with gain {:.2}x", gain));
+            }
+        }
+
         // Check for audio quality - skip if too quiet
         let max_amplitude = audio_data.iter().map(|&x| x.abs()).fold(0.0, f32::max);
         DebugLogger::log_info(&format!(
-            "STT: Audio quality check - max_amplitude={:.6}, threshold=0.01",
-            max_amplitude
+            "STT: Audio quality check - max_amplitude={:.6}, threshold={}",
+            max_amplitude, self.silence_threshold
         ));
-        if max_amplitude < 0.01 {
+        if max_amplitude < self.silence_threshold {
             DebugLogger::log_info(&format!(
                 "Audio chunk too quiet (max_amplitude: {:.6}), returning empty",
                 max_amplitude
             ));
-            return Ok(String::new()); // Return empty string for silent audio
+            return Ok(Transcription::skipped(EmptyReason::TooQuiet)); // Return empty text for silent audio
         }
 
-        // Convert f32 samples to i16 for WAV encoding
-        DebugLogger::log_info("STT: Converting audio to WAV format");
+        // Encode to the configured upload format (16 kHz mono)
+        DebugLogger::log_info(&format!(
+            "STT: Encoding audio as {:?}",
+            self.upload_format
+        ));
         let encoding_start = std::time::Instant::now();
-        let audio_bytes = self.encode_wav(&audio_data, sample_rate).map_err(|e| {
+        let audio_bytes = self.encode_upload(&audio_data, sample_rate).map_err(|e| {
             let error_msg = format!("Audio encoding error: {}", e);
             DebugLogger::log_pipeline_error("stt", &error_msg);
             error_msg
         })?;
         let encoding_duration = encoding_start.elapsed();
         DebugLogger::log_info(&format!(
-            "STT: WAV encoding complete in {:.2}s, output size={} bytes",
+            "STT: {:?} encoding complete in {:.2}s, output size={} bytes",
+            self.upload_format,
             encoding_duration.as_secs_f32(),
             audio_bytes.len()
         ));
 
         // Skip very short audio (use duration threshold based on original sample_rate)
         let duration_secs = audio_data.len() as f32 / sample_rate as f32;
-        let min_duration = 0.6_f32; // seconds
         DebugLogger::log_info(&format!(
             "STT: Duration check - duration={:.3}s, threshold={:.3}s",
-            duration_secs, min_duration
+            duration_secs, self.min_duration_secs
         ));
-        if duration_secs < min_duration {
+        if duration_secs < self.min_duration_secs {
             DebugLogger::log_info(&format!(
                 "Audio chunk too short ({:.3}s), skipping",
                 duration_secs
             ));
-            return Ok(String::new());
+            return Ok(Transcription::skipped(EmptyReason::TooShort));
+        }
+
+        // Endpoints reject oversized files (413); split instead of retrying a request that can't succeed
+        if audio_bytes.len() > self.max_upload_bytes() {
+            return self
+                .transcribe_split(&audio_data, sample_rate, audio_bytes.len())
+                .await;
         }
 
         DebugLogger::log_transcription_request(audio_bytes.len(), &self.api_endpoint);
 
-        // Save exact WAV payload to logs for debugging (only for requests we actually send)
+        // Save a WAV copy of the request audio to logs for debugging (only for requests we actually send)
         let dump_label = label.unwrap_or("stt_request");
-        if let Some(path) = DebugLogger::save_wav_dump(dump_label, &audio_bytes) {
+        let dump_bytes = match self.upload_format {
+            UploadFormat::Wav => Some(audio_bytes.clone()),
+            _ if DebugLogger::is_debug_enabled() => self.encode_wav(&audio_data, sample_rate).ok(),
+            _ => None,
+        };
+        if let Some(path) = dump_bytes
+            .as_deref()
+            .and_then(|bytes| DebugLogger::save_wav_dump(dump_label, bytes))
+        {
             DebugLogger::log_info(&format!("STT: Saved WAV dump to {}", path.display()));
         } else {
             DebugLogger::log_info("STT: Could not save WAV dump (no log path yet?)");
@@ -108,23 +367,101 @@ impl STTService {
         self.send_transcription_request(audio_bytes).await
     }
 
-    async fn send_transcription_request(&self, audio_bytes: Vec<u8>) -> Result<String, String> {
+    fn max_upload_bytes(&self) -> usize {
+        self.max_upload_mb.max(1) as usize * 1024 * 1024
+    }
+
+    /// Upload audio that encodes above `max_upload_mb` as several requests, split at quiet
+    /// points, and stitch the transcripts (timestamps shifted to the full recording)
+    async fn transcribe_split(
+        &self,
+        audio_data: &[f32],
+        sample_rate: u32,
+        encoded_len: usize,
+    ) -> Result<Transcription, String> {
+        let max_upload_bytes = self.max_upload_bytes();
+        // Aim 10% under the limit; compressed size doesn't scale exactly with duration
+        let max_len = (audio_data.len() as f64 * max_upload_bytes as f64 / encoded_len as f64 * 0.9) as usize;
+        let pieces = crate::audio::split_at_silence(audio_data, sample_rate, max_len);
+        DebugLogger::log_info(&format!(
+            "STT: Encoded audio is {} bytes, above the {} MB upload limit; splitting into {} requests",
+            encoded_len,
+            self.max_upload_mb,
+            pieces.len()
+        ));
+
+        let mut combined = Transcription::default();
+        let piece_count = pieces.len();
+        for (index, range) in pieces.into_iter().enumerate() {
+            let offset = range.start as f64 / sample_rate as f64;
+            let audio_bytes = self.encode_upload(&audio_data[range], sample_rate).map_err(|e| {
+                let error_msg = format!("Audio encoding error: {}", e);
+                DebugLogger::log_pipeline_error("stt", &error_msg);
+                error_msg
+            })?;
+            if audio_bytes.len() > max_upload_bytes {
+                DebugLogger::log_warn(&format!(
+                    "STT: Split piece {}/{} is still {} bytes; sending anyway",
+                    index + 1,
+                    piece_count,
+                    audio_bytes.len()
+                ));
+            }
+            DebugLogger::log_transcription_request(audio_bytes.len(), &self.api_endpoint);
+            let part = self.send_transcription_request(audio_bytes).await?;
+
+            let text = part.text.trim();
+            if !text.is_empty() {
+                if !combined.text.is_empty() {
+                    combined.text.push(' ');
+                }
+                combined.text.push_str(text);
+            }
+            combined.segments.extend(part.segments.into_iter().map(|mut s| {
+                s.start += offset;
+                s.end += offset;
+                s
+            }));
+            combined.words.extend(part.words.into_iter().map(|mut w| {
+                w.start += offset;
+                w.end += offset;
+                w
+            }));
+            combined.language = combined.language.or(part.language);
+        }
+        Ok(combined)
+    }
+
+    async fn send_transcription_request(&self, audio_bytes: Vec<u8>) -> Result<Transcription, String> {
+        crate::api_flavor::check_local_only(&self.api_endpoint, self.local_only).inspect_err(|e| {
+            DebugLogger::log_pipeline_error("stt", e);
+        })?;
+        // Counts against max_concurrent_requests across all attempts, including backoff
+        let _slot = crate::retry::acquire_request_slot("STT").await;
         // Send request to Whisper API with retries
-        let url = format!("{}/audio/transcriptions", self.api_endpoint);
+        let url = self
+            .api_flavor
+            .endpoint_url(&self.api_endpoint, &self.model, "audio/transcriptions");
         DebugLogger::log_info(&format!("STT: Preparing request to URL: {}", url));
         DebugLogger::log_info(&format!(
             "STT: Audio payload size: {} bytes",
             audio_bytes.len()
         ));
 
-        for attempt in 1..=3 {
-            DebugLogger::log_info(&format!("STT attempt {}/3 to {}", attempt, url));
+        let max_attempts = self.max_retries + 1;
+        for attempt in 1..=max_attempts {
+            DebugLogger::log_info(&format!("STT attempt {}/{} to {}", attempt, max_attempts, url));
 
             // Create multipart form data fresh for each attempt
             DebugLogger::log_info("STT: Creating multipart form data");
             let mut form = reqwest::multipart::Form::new()
                 .text("model", self.model.clone())
-                .text("response_format", "json");
+                .text("response_format", self.response_format.as_str());
+            if self.response_format == ResponseFormat::VerboseJson {
+                form = form
+                    .text("timestamp_granularities[]", "segment")
+                    .text("timestamp_granularities[]", "word");
+            }
 
             // Only include language when explicitly set (not 'auto' or empty)
             let lang = self.spoken_language.trim();
@@ -135,11 +472,23 @@ impl STTService {
                 DebugLogger::log_info("STT: No language hint provided (auto-detect)");
             }
 
+            // Optional vocabulary/context prompt to bias recognition of domain terms
+            let prompt = self.stt_prompt.trim();
+            if !prompt.is_empty() {
+                DebugLogger::log_info(&format!("STT: Including prompt ({} chars)", prompt.len()));
+                form = form.text("prompt", prompt.to_string());
+            }
+
+            // 0 leaves the field out so servers keep their own default
+            if self.temperature > 0.0 {
+                form = form.text("temperature", self.temperature.to_string());
+            }
+
             form = form.part(
                 "file",
-                reqwest::multipart::Part::bytes(audio_bytes.clone())
-                    .file_name("audio.wav")
-                    .mime_str("audio/wav")
+                self.audio_part(&audio_bytes)
+                    .file_name(self.upload_format.file_name())
+                    .mime_str(self.upload_format.mime_type())
                     .map_err(|e| {
                         let error_msg = format!("Multipart error: {}", e);
                         DebugLogger::log_pipeline_error("stt", &error_msg);
@@ -151,9 +500,8 @@ impl STTService {
             DebugLogger::log_info("STT: Sending HTTP POST request");
             let api_start = std::time::Instant::now();
             let response = self
-                .client
-                .post(&url)
-                .header("Authorization", format!("Bearer {}", self.api_key))
+                .api_flavor
+                .authorize(self.client.post(&url), &self.api_key)
                 .multipart(form)
                 .send()
                 .await;
@@ -164,7 +512,7 @@ impl STTService {
                 Ok(resp) => {
                     let status = resp.status();
                     DebugLogger::log_info(&format!("STT API response status: {}", status));
-                    DebugLogger::log_info(&format!(
+                    DebugLogger::log_debug(&format!(
                         "STT API response headers: {:?}",
                         resp.headers()
                     ));
@@ -179,6 +527,15 @@ impl STTService {
 
                         DebugLogger::log_info(&format!("STT API raw response: {}", response_text));
 
+                        if self.response_format == ResponseFormat::Text {
+                            let text = response_text.trim().to_string();
+                            DebugLogger::log_info(&format!("STT extracted text: '{}'", text));
+                            return Ok(Transcription {
+                                text,
+                                ..Default::default()
+                            });
+                        }
+
                         DebugLogger::log_info("STT: Parsing JSON response");
                         let json: Value = serde_json::from_str(&response_text).map_err(|e| {
                             let error_msg = format!("JSON parsing error: {}", e);
@@ -186,14 +543,28 @@ impl STTService {
                             error_msg
                         })?;
 
-                        DebugLogger::log_info(&format!(
+                        DebugLogger::log_debug(&format!(
                             "STT: Parsed JSON: {}",
                             serde_json::to_string_pretty(&json).unwrap_or_default()
                         ));
 
                         if let Some(text) = json["text"].as_str() {
                             DebugLogger::log_info(&format!("STT extracted text: '{}'", text));
-                            return Ok(text.trim().to_string());
+                            let text = text.trim().to_string();
+                            if self.response_format == ResponseFormat::VerboseJson {
+                                let transcription = Transcription::from_verbose_json(text, &json);
+                                DebugLogger::log_info(&format!(
+                                    "STT: Parsed {} segments and {} words",
+                                    transcription.segments.len(),
+                                    transcription.words.len()
+                                ));
+                                return Ok(transcription);
+                            }
+                            return Ok(Transcription {
+                                text,
+                                language: detected_language(&json),
+                                ..Default::default()
+                            });
                         } else {
                             let error_msg = "No text in API response".to_string();
                             DebugLogger::log_pipeline_error("stt", &error_msg);
@@ -207,6 +578,11 @@ impl STTService {
                         DebugLogger::log_info(
                             "STT: Response status is not successful, reading error response",
                         );
+                        let retry_after = if status.as_u16() == 429 {
+                            crate::retry::retry_after_delay(resp.headers())
+                        } else {
+                            None
+                        };
                         let error_text = resp.text().await.unwrap_or_default();
                         DebugLogger::log_info(&format!("STT API error response: {}", error_text));
 
@@ -217,7 +593,7 @@ impl STTService {
                             return Err(error_msg);
                         }
 
-                        if attempt == 3 {
+                        if attempt == max_attempts {
                             let error_msg = format!(
                                 "API error after {} attempts: {} - {}",
                                 attempt, status, error_text
@@ -226,8 +602,8 @@ impl STTService {
                             return Err(error_msg);
                         }
 
-                        // Wait before retry
-                        let delay = Duration::from_millis(1000 * attempt);
+                        // Wait before retry, preferring the server's Retry-After on rate limits
+                        let delay = retry_after.unwrap_or_else(|| crate::retry::backoff_delay(attempt));
                         DebugLogger::log_info(&format!("Retrying in {}ms...", delay.as_millis()));
                         tokio::time::sleep(delay).await;
                     }
@@ -235,14 +611,14 @@ impl STTService {
                 Err(e) => {
                     DebugLogger::log_info(&format!("STT network error: {}", e));
 
-                    if attempt == 3 {
+                    if attempt == max_attempts {
                         let error_msg = format!("Network error after {} attempts: {}", attempt, e);
                         DebugLogger::log_pipeline_error("stt", &error_msg);
                         return Err(error_msg);
                     }
 
                     // Wait before retry
-                    let delay = Duration::from_millis(1000 * attempt);
+                    let delay = crate::retry::backoff_delay(attempt);
                     DebugLogger::log_info(&format!("Retrying in {}ms...", delay.as_millis()));
                     tokio::time::sleep(delay).await;
                 }
@@ -254,8 +630,27 @@ impl STTService {
         Err(error_msg)
     }
 
+    fn encode_upload(&self, samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+        match self.upload_format {
+            UploadFormat::Wav => self.encode_wav(samples, sample_rate),
+            UploadFormat::Flac | UploadFormat::Opus => {
+                let target_rate: u32 = 16_000;
+                let resampled = if sample_rate == target_rate {
+                    samples.to_vec()
+                } else {
+                    crate::audio::resample(samples, sample_rate, target_rate)
+                };
+                if self.upload_format == UploadFormat::Flac {
+                    audio_encoding::encode_flac(&resampled, target_rate)
+                } else {
+                    audio_encoding::encode_opus(&resampled, target_rate)
+                }
+            }
+        }
+    }
+
     fn encode_wav(&self, samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
-        // Downsample to 16 kHz mono PCM16 for Whisper
+        // Resample to 16 kHz mono PCM16 for Whisper (no-op when the capture pipeline already did it)
         let target_rate: u32 = 16_000;
         let (resampled, out_rate) = if sample_rate == target_rate {
             (samples.to_vec(), sample_rate)
@@ -263,21 +658,7 @@ impl STTService {
             if samples.is_empty() {
                 return Err("No samples to encode".into());
             }
-            let ratio = target_rate as f32 / sample_rate as f32;
-            let out_len = ((samples.len() as f32) * ratio).max(1.0).round() as usize;
-            let mut out = Vec::with_capacity(out_len);
-            for i in 0..out_len {
-                let src_pos = i as f32 / ratio;
-                let idx = src_pos.floor() as usize;
-                if idx + 1 < samples.len() {
-                    let frac = src_pos - idx as f32;
-                    let s = samples[idx] * (1.0 - frac) + samples[idx + 1] * frac;
-                    out.push(s);
-                } else {
-                    out.push(samples[samples.len() - 1]);
-                }
-            }
-            (out, target_rate)
+            (crate::audio::resample(samples, sample_rate, target_rate), target_rate)
         };
 
         // Convert to i16 PCM
@@ -310,3 +691,69 @@ impl STTService {
         Ok(wav_data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_verbose_json() {
+        let response = json!({
+            "text": " Bonjour tout le monde. ",
+            "language": "french",
+            "segments": [
+                { "start": 0.0, "end": 1.5, "text": " Bonjour" },
+                { "start": 1.5, "end": 3.25, "text": " tout le monde. " }
+            ],
+            "words": [{ "start": 0.0, "end": 0.8, "word": " Bonjour" }]
+        });
+        let transcription = Transcription::from_verbose_json("Bonjour tout le monde.".to_string(), &response);
+        assert_eq!(transcription.language.as_deref(), Some("french"));
+        assert_eq!(transcription.segments.len(), 2);
+        assert_eq!(transcription.segments[1].text, "tout le monde.");
+        assert_eq!((transcription.segments[1].start, transcription.segments[1].end), (1.5, 3.25));
+        assert_eq!(transcription.words[0].word, "Bonjour");
+        assert_eq!(transcription.empty_reason(), None);
+    }
+
+    #[test]
+    fn test_offset_by() {
+        let response = json!({
+            "segments": [{ "start": 0.0, "end": 1.5, "text": "hello" }],
+            "words": [{ "start": 0.5, "end": 1.0, "word": "hello" }]
+        });
+        let mut transcription = Transcription::from_verbose_json("hello".to_string(), &response);
+        transcription.offset_by(30.0);
+        assert_eq!((transcription.segments[0].start, transcription.segments[0].end), (30.0, 31.5));
+        assert_eq!((transcription.words[0].start, transcription.words[0].end), (30.5, 31.0));
+    }
+
+    #[test]
+    fn test_from_verbose_json_missing_fields() {
+        // Servers that only partly implement verbose_json: no words, blank language, sparse segments
+        let response = json!({
+            "text": "hi",
+            "language": " ",
+            "segments": [{ "text": "hi" }, { "start": "oops", "end": 2.0 }]
+        });
+        let transcription = Transcription::from_verbose_json("hi".to_string(), &response);
+        assert_eq!(transcription.language, None);
+        assert!(transcription.words.is_empty());
+        assert_eq!((transcription.segments[0].start, transcription.segments[0].end), (0.0, 0.0));
+        assert_eq!(transcription.segments[1].start, 0.0);
+        assert_eq!(transcription.segments[1].text, "");
+
+        let transcription = Transcription::from_verbose_json(String::new(), &json!({}));
+        assert!(transcription.segments.is_empty() && transcription.words.is_empty());
+        assert_eq!(transcription.language, None);
+    }
+
+    #[test]
+    fn test_empty_reason() {
+        let empty = Transcription::from_verbose_json("  ".to_string(), &json!({ "segments": [] }));
+        assert_eq!(empty.empty_reason(), Some(EmptyReason::NoSpeech));
+        assert_eq!(Transcription::skipped(EmptyReason::TooQuiet).empty_reason(), Some(EmptyReason::TooQuiet));
+        assert_eq!(EmptyReason::TooShort.as_str(), "too_short");
+    }
+}
@@ -2,76 +2,233 @@ use std::sync::Mutex;
 
 pub struct SystemAudioControl {
     is_muted: Mutex<bool>,
+    // Mute state of the output before we touched it, so unmute restores it exactly
+    previous_muted: Mutex<Option<bool>>,
+    // Output volume (1.0 = 100%) saved while ducked, restored afterwards
+    original_volume: Mutex<Option<f32>>,
 }
 
 impl SystemAudioControl {
     pub fn new() -> Result<Self, String> {
         Ok(Self {
             is_muted: Mutex::new(false),
+            previous_muted: Mutex::new(None),
+            original_volume: Mutex::new(None),
         })
     }
 
+    /// Whether mute/duck actually changes the output here (only Linux has a backend so far)
+    pub fn is_supported() -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            linux::Backend::detect().is_ok()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            false
+        }
+    }
+
     pub fn mute_system_audio(&self) -> Result<(), String> {
-        // Platform-specific implementation would go here
-        // For now, we'll just track the mute state
-        *self.is_muted.lock().unwrap() = true;
+        if self.is_muted() {
+            return Ok(());
+        }
+
+        // Windows (Core Audio endpoint volume) and macOS (CoreAudio) aren't implemented yet
+        #[cfg(not(target_os = "linux"))]
+        return Err(unsupported("mute"));
 
-        #[cfg(windows)]
+        #[cfg(target_os = "linux")]
         {
-            // On Windows, we could use the Windows API to mute system audio
-            // For now, this is a stub implementation
-            println!("Muting system audio (Windows stub)");
+            let backend = linux::Backend::detect()?;
+            let was_muted = backend.get_mute()?;
+            backend.set_mute(true)?;
+            *self.previous_muted.lock().unwrap() = Some(was_muted);
+            *self.is_muted.lock().unwrap() = true;
+            Ok(())
         }
+    }
 
-        #[cfg(target_os = "macos")]
-        {
-            // On macOS, we could use Core Audio APIs
-            println!("Muting system audio (macOS stub)");
+    pub fn unmute_system_audio(&self) -> Result<(), String> {
+        if !self.is_muted() {
+            return Ok(());
         }
 
         #[cfg(target_os = "linux")]
         {
-            // On Linux, we could use ALSA or PulseAudio
-            println!("Muting system audio (Linux stub)");
+            // Leave the output muted if the user had muted it before recording
+            let was_muted = self.previous_muted.lock().unwrap().unwrap_or(false);
+            if !was_muted {
+                linux::Backend::detect()?.set_mute(false)?;
+            }
         }
 
+        *self.previous_muted.lock().unwrap() = None;
+        *self.is_muted.lock().unwrap() = false;
         Ok(())
     }
 
-    pub fn unmute_system_audio(&self) -> Result<(), String> {
-        // Platform-specific implementation would go here
-        // For now, we'll just track the mute state
-        *self.is_muted.lock().unwrap() = false;
+    pub fn is_muted(&self) -> bool {
+        *self.is_muted.lock().unwrap()
+    }
 
-        #[cfg(windows)]
-        {
-            // On Windows, we could use the Windows API to unmute system audio
-            println!("Unmuting system audio (Windows stub)");
+    /// Reduce output volume to `level` (0.0-1.0) of its current value instead of muting
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    pub fn duck_system_audio(&self, level: f32) -> Result<(), String> {
+        if self.is_ducked() {
+            return Ok(());
         }
+        #[cfg(not(target_os = "linux"))]
+        return Err(unsupported("duck"));
 
-        #[cfg(target_os = "macos")]
+        #[cfg(target_os = "linux")]
         {
-            // On macOS, we could use Core Audio APIs
-            println!("Unmuting system audio (macOS stub)");
+            let level = level.clamp(0.0, 1.0);
+            let backend = linux::Backend::detect()?;
+            let original = backend.get_volume()?;
+            backend.set_volume(original * level)?;
+            *self.original_volume.lock().unwrap() = Some(original);
+            Ok(())
+        }
+    }
+
+    /// Restore the output volume saved by `duck_system_audio`
+    pub fn restore_system_audio(&self) -> Result<(), String> {
+        if !self.is_ducked() {
+            return Ok(());
         }
 
         #[cfg(target_os = "linux")]
         {
-            // On Linux, we could use ALSA or PulseAudio
-            println!("Unmuting system audio (Linux stub)");
+            let original = self.original_volume.lock().unwrap().unwrap_or(1.0);
+            linux::Backend::detect()?.set_volume(original)?;
         }
 
+        *self.original_volume.lock().unwrap() = None;
         Ok(())
     }
 
-    pub fn is_muted(&self) -> bool {
-        *self.is_muted.lock().unwrap()
+    pub fn is_ducked(&self) -> bool {
+        self.original_volume.lock().unwrap().is_some()
     }
 }
 
 impl Drop for SystemAudioControl {
     fn drop(&mut self) {
-        // Ensure we unmute when dropping
+        // Ensure we unmute and restore the volume when dropping
         let _ = self.unmute_system_audio();
+        let _ = self.restore_system_audio();
+    }
+}
+
+// Error for mute/duck requests on platforms without a backend, so callers don't report them as done
+#[cfg(not(target_os = "linux"))]
+fn unsupported(action: &str) -> String {
+    format!("Can't {} system audio: not supported on this platform yet", action)
+}
+
+// PulseAudio (pactl) with a PipeWire/WirePlumber (wpctl) fallback, driven through their CLIs
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::process::Command;
+
+    pub enum Backend {
+        Pactl,
+        Wpctl,
+    }
+
+    impl Backend {
+        pub fn detect() -> Result<Self, String> {
+            if tool_exists("pactl") {
+                Ok(Backend::Pactl)
+            } else if tool_exists("wpctl") {
+                Ok(Backend::Wpctl)
+            } else {
+                Err("Neither pactl (PulseAudio) nor wpctl (PipeWire) was found in PATH; install pulseaudio-utils or wireplumber to enable auto-mute".to_string())
+            }
+        }
+
+        pub fn get_mute(&self) -> Result<bool, String> {
+            match self {
+                Backend::Pactl => {
+                    // Output looks like "Mute: yes"
+                    let out = run("pactl", &["get-sink-mute", "@DEFAULT_SINK@"])?;
+                    Ok(out.to_lowercase().contains("yes"))
+                }
+                Backend::Wpctl => {
+                    // Output looks like "Volume: 0.40 [MUTED]"
+                    let out = run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"])?;
+                    Ok(out.contains("[MUTED]"))
+                }
+            }
+        }
+
+        /// Current output volume as a fraction (1.0 = 100%)
+        pub fn get_volume(&self) -> Result<f32, String> {
+            match self {
+                Backend::Pactl => {
+                    // Output looks like "Volume: front-left: 26214 /  40% / -23.88 dB, ..."
+                    let out = run("pactl", &["get-sink-volume", "@DEFAULT_SINK@"])?;
+                    out.split('/')
+                        .find_map(|part| part.trim().strip_suffix('%'))
+                        .and_then(|pct| pct.trim().parse::<f32>().ok())
+                        .map(|pct| pct / 100.0)
+                        .ok_or_else(|| format!("Could not parse pactl volume output: {}", out.trim()))
+                }
+                Backend::Wpctl => {
+                    // Output looks like "Volume: 0.40" (optionally followed by "[MUTED]")
+                    let out = run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"])?;
+                    out.split_whitespace()
+                        .nth(1)
+                        .and_then(|v| v.parse::<f32>().ok())
+                        .ok_or_else(|| format!("Could not parse wpctl volume output: {}", out.trim()))
+                }
+            }
+        }
+
+        pub fn set_volume(&self, volume: f32) -> Result<(), String> {
+            match self {
+                Backend::Pactl => {
+                    let pct = format!("{}%", (volume * 100.0).round() as u32);
+                    run("pactl", &["set-sink-volume", "@DEFAULT_SINK@", &pct])?
+                }
+                Backend::Wpctl => {
+                    let value = format!("{:.2}", volume);
+                    run("wpctl", &["set-volume", "@DEFAULT_AUDIO_SINK@", &value])?
+                }
+            };
+            Ok(())
+        }
+
+        pub fn set_mute(&self, muted: bool) -> Result<(), String> {
+            let flag = if muted { "1" } else { "0" };
+            match self {
+                Backend::Pactl => run("pactl", &["set-sink-mute", "@DEFAULT_SINK@", flag])?,
+                Backend::Wpctl => run("wpctl", &["set-mute", "@DEFAULT_AUDIO_SINK@", flag])?,
+            };
+            Ok(())
+        }
+    }
+
+    fn tool_exists(tool: &str) -> bool {
+        std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(tool).is_file()))
+            .unwrap_or(false)
+    }
+
+    fn run(tool: &str, args: &[&str]) -> Result<String, String> {
+        let output = Command::new(tool)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", tool, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} {} failed: {}",
+                tool,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 }
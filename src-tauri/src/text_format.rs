@@ -0,0 +1,286 @@
+// Local (no API call) clean-up of the final text before it is inserted.
+use crate::debug_logger::DebugLogger;
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// One user-defined find/replace rule from the `text_replacements` setting
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TextReplacement {
+    pub find: String,
+    /// `\n`, `\t` and `\\` are unescaped; regex rules may also reference groups (`$1`, `${name}`)
+    #[serde(default)]
+    pub replace: String,
+    /// Treat `find` as a regular expression instead of literal text
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+impl TextReplacement {
+    /// The rule's pattern; fails for an empty `find` or an invalid regex
+    pub fn compile(&self) -> Result<Regex, String> {
+        if self.find.is_empty() {
+            return Err("Replacement rule has an empty find pattern".to_string());
+        }
+        let pattern = if self.regex { self.find.clone() } else { regex::escape(&self.find) };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid replacement pattern '{}': {}", self.find, e))
+    }
+}
+
+/// Run the `text_replacements` rules over the text, in order. Invalid rules are logged and skipped.
+pub fn apply_replacements(text: &str, rules: &[TextReplacement]) -> String {
+    let mut result = text.to_string();
+    for rule in rules {
+        let pattern = match rule.compile() {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                DebugLogger::log_warn(&format!("Skipping text replacement: {}", e));
+                continue;
+            }
+        };
+        let replacement = unescape_replacement(&rule.replace);
+        result = if rule.regex {
+            pattern.replace_all(&result, replacement.as_str()).into_owned()
+        } else {
+            pattern.replace_all(&result, NoExpand(&replacement)).into_owned()
+        };
+    }
+    result
+}
+
+/// One trigger from the `spoken_punctuation_words` setting, editable so other languages can use their own words
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SpokenPunctuation {
+    /// Word or phrase as spoken, matched case-insensitively as whole words
+    pub phrase: String,
+    /// Inserted in its place; `\n`, `\t` and `\\` are unescaped
+    pub symbol: String,
+}
+
+/// English triggers, used until the user edits the list
+pub fn default_spoken_punctuation() -> Vec<SpokenPunctuation> {
+    [
+        ("new paragraph", "\\n\\n"),
+        ("new line", "\\n"),
+        ("comma", ","),
+        ("period", "."),
+        ("full stop", "."),
+        ("question mark", "?"),
+        ("exclamation mark", "!"),
+        ("exclamation point", "!"),
+        ("colon", ":"),
+        ("semicolon", ";"),
+    ]
+    .iter()
+    .map(|(phrase, symbol)| SpokenPunctuation { phrase: phrase.to_string(), symbol: symbol.to_string() })
+    .collect()
+}
+
+/// Replace spoken punctuation ("comma", "new line") with its symbol. Punctuation symbols attach to the
+/// previous word and replace whatever punctuation the STT service put around the trigger; line breaks
+/// keep the previous sentence's punctuation and drop the spaces around them.
+pub fn apply_spoken_punctuation(text: &str, words: &[SpokenPunctuation]) -> String {
+    let mut words: Vec<&SpokenPunctuation> = words.iter().filter(|w| !w.phrase.trim().is_empty()).collect();
+    // Longer phrases first, so a short trigger can't claim part of a longer one
+    words.sort_by_key(|w| std::cmp::Reverse(w.phrase.len()));
+    let mut result = text.to_string();
+    for word in words {
+        let symbol = unescape_replacement(&word.symbol);
+        let phrase: Vec<String> = word.phrase.split_whitespace().map(regex::escape).collect();
+        let pattern = if symbol.chars().all(char::is_whitespace) {
+            format!(r"[ \t]*\b{}\b[,.;:!?]*\s*", phrase.join(r"\s+"))
+        } else {
+            format!(r"[\s,.;:!?]*\b{}\b[,.;:!?]*", phrase.join(r"\s+"))
+        };
+        match RegexBuilder::new(&pattern).case_insensitive(true).build() {
+            Ok(pattern) => result = pattern.replace_all(&result, NoExpand(&symbol)).into_owned(),
+            Err(e) => DebugLogger::log_warn(&format!("Skipping spoken punctuation '{}': {}", word.phrase, e)),
+        }
+    }
+    result
+}
+
+/// The text as inserted: `insert_prefix` + text + `insert_suffix`, with the same escapes as replacements
+pub fn wrap_for_insertion(text: &str, prefix: &str, suffix: &str) -> String {
+    format!("{}{}{}", unescape_replacement(prefix), text, unescape_replacement(suffix))
+}
+
+/// Default for the `dedup_window_tokens` setting
+pub const DEFAULT_DEDUP_WINDOW_TOKENS: u32 = 8;
+
+/// Stitch the next transcript onto the text so far. When the last words of `agg` reappear at the start
+/// of `next` (overlapping audio transcribed twice), they're replaced by the later copy, which was
+/// transcribed with more context. Words are compared ignoring case and surrounding punctuation, up to
+/// `window_tokens` of them; a single repeated word isn't treated as overlap, since "that that" is also
+/// something people say. A window of 0 turns dedup off.
+pub fn append_dedup(agg: &mut String, next: &str, window_tokens: usize) {
+    let next = next.trim();
+    if next.is_empty() {
+        return;
+    }
+    let words: Vec<&str> = agg.split_whitespace().collect();
+    let tail = &words[words.len().saturating_sub(window_tokens)..];
+    let head: Vec<&str> = next.split_whitespace().take(window_tokens).collect();
+    let overlap = (2..=tail.len().min(head.len()))
+        .rev()
+        .find(|&n| tail[tail.len() - n..].iter().zip(&head[..n]).all(|(a, b)| same_token(a, b)))
+        .unwrap_or(0);
+    if overlap > 0 {
+        let text = agg.trim_end();
+        let keep = text[..text.len() - trailing_tokens_len(text, overlap)].trim_end().len();
+        agg.truncate(keep);
+    }
+    if !agg.is_empty() {
+        agg.push(' ');
+    }
+    agg.push_str(next);
+}
+
+fn same_token(a: &str, b: &str) -> bool {
+    let strip = |t: &str| t.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+    let (a_word, b_word) = (strip(a), strip(b));
+    if a_word.is_empty() && b_word.is_empty() {
+        a == b
+    } else {
+        a_word == b_word
+    }
+}
+
+// Byte length of the last `count` whitespace-delimited tokens of `text` (which has no trailing whitespace)
+fn trailing_tokens_len(text: &str, count: usize) -> usize {
+    let mut start = text.len();
+    for _ in 0..count {
+        let rest = text[..start].trim_end();
+        start = rest
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+    }
+    text.len() - start
+}
+
+/// `\n`, `\t` and `\\` typed in a settings field become a newline, tab and backslash
+fn unescape_replacement(replace: &str) -> String {
+    let mut out = String::with_capacity(replace.len());
+    let mut chars = replace.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Capitalize the first letter and end the text with a period when it has no terminal punctuation.
+/// Used when the correction step didn't run (verbatim style, or the request failed).
+pub fn format_locally(text: &str) -> String {
+    let trimmed = text.trim();
+    let mut chars = trimmed.chars();
+    let mut formatted = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => return String::new(),
+    };
+    // Only after a word: existing punctuation, a closing quote, bracket or emoji is left alone
+    if formatted.chars().last().is_some_and(char::is_alphanumeric) {
+        formatted.push('.');
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(find: &str, replace: &str, regex: bool) -> TextReplacement {
+        TextReplacement { find: find.to_string(), replace: replace.to_string(), regex, case_sensitive: false }
+    }
+
+    #[test]
+    fn test_apply_replacements() {
+        let rules = vec![
+            rule(" new line", "\\n", false),
+            rule("e-mail", "email", false),
+            rule(r"(\d+) percent", "$1%", true),
+            rule("(", "never applied: invalid regex", true),
+        ];
+        assert_eq!(
+            apply_replacements("Send the E-mail. New line 50 percent done", &rules),
+            "Send the email.\n 50% done"
+        );
+        // Literal replacements don't expand `$`
+        assert_eq!(apply_replacements("cost", &[rule("cost", "$5", false)]), "$5");
+    }
+
+    #[test]
+    fn test_apply_spoken_punctuation() {
+        let words = default_spoken_punctuation();
+        assert_eq!(apply_spoken_punctuation("Hello comma world period", &words), "Hello, world.");
+        assert_eq!(apply_spoken_punctuation("Hello, comma, world. Period.", &words), "Hello, world.");
+        assert_eq!(apply_spoken_punctuation("First line. New line. Second line", &words), "First line.\nSecond line");
+        assert_eq!(apply_spoken_punctuation("done new paragraph next", &words), "done\n\nnext");
+        assert_eq!(apply_spoken_punctuation("a semicolon b colon c", &words), "a; b: c");
+        // Only whole words
+        assert_eq!(apply_spoken_punctuation("commas and periodic", &words), "commas and periodic");
+
+        let custom = vec![SpokenPunctuation { phrase: "vírgula".to_string(), symbol: ",".to_string() }];
+        assert_eq!(apply_spoken_punctuation("olá Vírgula mundo", &custom), "olá, mundo");
+    }
+
+    #[test]
+    fn test_wrap_for_insertion() {
+        assert_eq!(wrap_for_insertion("Hi.", "", " "), "Hi. ");
+        assert_eq!(wrap_for_insertion("Hi.", "\\n", "\\t"), "\nHi.\t");
+        assert_eq!(wrap_for_insertion("Hi.", "", ""), "Hi.");
+    }
+
+    #[test]
+    fn test_append_dedup() {
+        let mut agg = String::new();
+        append_dedup(&mut agg, " the quick brown fox ", 8);
+        assert_eq!(agg, "the quick brown fox");
+        // Overlap longer than the old 12-char heuristic, with different punctuation and case
+        append_dedup(&mut agg, "Quick brown fox, jumps over", 8);
+        assert_eq!(agg, "the Quick brown fox, jumps over");
+        // A single repeated word is kept
+        let mut agg = "I think that".to_string();
+        append_dedup(&mut agg, "that is right", 8);
+        assert_eq!(agg, "I think that that is right");
+        // Overlap beyond the window isn't looked for; 0 disables dedup
+        let mut agg = "one two three".to_string();
+        append_dedup(&mut agg, "one two three four", 2);
+        assert_eq!(agg, "one two three one two three four");
+        let mut agg = "one two".to_string();
+        append_dedup(&mut agg, "one two three", 0);
+        assert_eq!(agg, "one two one two three");
+        let mut agg = "olá mundo".to_string();
+        append_dedup(&mut agg, "Olá mundo!", 8);
+        assert_eq!(agg, "Olá mundo!");
+    }
+
+    #[test]
+    fn test_format_locally() {
+        assert_eq!(format_locally("  hello world "), "Hello world.");
+        assert_eq!(format_locally("is it done?"), "Is it done?");
+        assert_eq!(format_locally("élan vital"), "Élan vital.");
+        assert_eq!(format_locally("he said \"ok\""), "He said \"ok\"");
+        assert_eq!(format_locally("3 items"), "3 items.");
+        assert_eq!(format_locally("   "), "");
+    }
+}
@@ -1,15 +1,175 @@
 use crate::debug_logger::DebugLogger;
 use arboard::Clipboard;
 use enigo::{Enigo, Key, Keyboard, Settings};
+use serde::Serialize;
+
+/// External tools probed by `check_tools`, across all platforms
+const INSERTION_TOOLS: &[&str] = &["xclip", "xdotool", "wl-copy", "wtype", "ydotool", "pbcopy", "powershell"];
+
+/// How transcribed text is delivered to the focused application
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertionMethod {
+    /// Put the text on the clipboard and send Ctrl+V / Cmd+V
+    Paste,
+    /// Type the text key-by-key without touching the clipboard
+    Type,
+}
+
+impl InsertionMethod {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "type" | "typing" => InsertionMethod::Type,
+            _ => InsertionMethod::Paste,
+        }
+    }
+}
 
-pub struct TextInsertionService;
+/// Where the final text ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Insert into the focused application (clipboard restored if configured)
+    Insert,
+    /// Only place the text on the clipboard; the user pastes manually
+    ClipboardOnly,
+    /// Insert and leave the text on the clipboard afterwards
+    Both,
+}
+
+impl OutputMode {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "clipboard_only" | "clipboard" => OutputMode::ClipboardOnly,
+            "both" => OutputMode::Both,
+            _ => OutputMode::Insert,
+        }
+    }
+}
+
+/// How `insert_text` ended up delivering the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertionOutcome {
+    Inserted,
+    /// `preserve_clipboard` was on and the clipboard couldn't be saved, so the text was typed
+    TypedToPreserveClipboard,
+    /// macOS without Accessibility permission: keystrokes would be dropped, so the text was only copied
+    CopiedWithoutAccessibility,
+}
+
+/// Clipboard contents saved before a paste so they can be put back afterwards
+enum ClipboardSnapshot {
+    Text(String),
+    Image(arboard::ImageData<'static>),
+}
+
+pub struct TextInsertionService {
+    restore_clipboard: bool,
+    // Strict mode: never paste unless the current clipboard can be saved and restored
+    preserve_clipboard: bool,
+    insertion_method: InsertionMethod,
+    output_mode: OutputMode,
+    // Time for the clipboard (and target app) to settle before Ctrl+V is sent
+    paste_delay_ms: u32,
+    // Windows: fall back to powershell.exe (Set-Clipboard/SendKeys) when the native path fails
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    powershell_fallback: bool,
+}
 
 impl TextInsertionService {
-    pub fn new() -> Self {
-        Self
+    pub fn new(
+        restore_clipboard: bool,
+        preserve_clipboard: bool,
+        insertion_method: InsertionMethod,
+        output_mode: OutputMode,
+        paste_delay_ms: u32,
+        powershell_fallback: bool,
+    ) -> Self {
+        Self {
+            restore_clipboard,
+            preserve_clipboard,
+            insertion_method,
+            output_mode,
+            paste_delay_ms,
+            powershell_fallback,
+        }
+    }
+
+    pub fn output_mode(&self) -> OutputMode {
+        self.output_mode
+    }
+
+    /// Which insertion tools are installed and which backend `insert_text` would use right now
+    pub fn check_tools(&self) -> InsertionToolReport {
+        let tools = INSERTION_TOOLS
+            .iter()
+            .map(|&name| ToolStatus { name, available: tool_available(name) })
+            .collect();
+        let typing = self.insertion_method == InsertionMethod::Type;
+
+        #[cfg(target_os = "linux")]
+        let (session, backend, fallback, missing) = {
+            let session = LinuxSession::detect();
+            let missing = if session == LinuxSession::Unknown {
+                // Nothing to install: neither toolchain works without a display
+                Vec::new()
+            } else if typing {
+                missing_typing_tools(session, tool_available)
+            } else {
+                missing_paste_tools(session, tool_available)
+            };
+            let tools_backend = match (session, typing) {
+                (LinuxSession::Wayland, true) if tool_available("wtype") => "wtype typing",
+                (LinuxSession::Wayland, true) => "ydotool typing",
+                (LinuxSession::Wayland, false) if tool_available("wtype") => "wl-copy + wtype",
+                (LinuxSession::Wayland, false) => "wl-copy + ydotool",
+                (_, true) => "xdotool typing",
+                (_, false) => "xclip + xdotool",
+            };
+            let native = if typing { "enigo typing" } else { "arboard clipboard + enigo" };
+            let (backend, fallback) = match session {
+                // The tool path is primary on Wayland and only usable with every tool present
+                LinuxSession::Wayland if !typing && missing.is_empty() => (tools_backend, Some(native)),
+                LinuxSession::Wayland if !typing => (native, None),
+                LinuxSession::Unknown => (native, None),
+                _ => (native, missing.is_empty().then_some(tools_backend)),
+            };
+            (Some(session.as_str()), backend, fallback, missing)
+        };
+
+        #[cfg(target_os = "windows")]
+        let (session, backend, fallback, missing) = {
+            let backend = if typing { "SendInput typing" } else { "clipboard API + SendInput" };
+            let fallback = if typing { "PowerShell SendKeys typing" } else { "PowerShell Set-Clipboard + SendKeys" };
+            let fallback = (self.powershell_fallback && tool_available("powershell")).then_some(fallback);
+            (None::<&'static str>, backend, fallback, Vec::<&'static str>::new())
+        };
+
+        #[cfg(target_os = "macos")]
+        let (session, backend, fallback, missing) = {
+            let (backend, fallback) = if typing {
+                ("enigo typing", Some("osascript keystroke"))
+            } else {
+                ("arboard clipboard + enigo (Cmd+V)", None)
+            };
+            (None::<&'static str>, backend, fallback, Vec::<&'static str>::new())
+        };
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+        let (session, backend, fallback, missing) =
+            (None::<&'static str>, "unsupported", None::<&'static str>, Vec::<&'static str>::new());
+
+        let hint = (!missing.is_empty()).then(|| format!("Install {}", missing.join(" and ")));
+        InsertionToolReport {
+            platform: std::env::consts::OS,
+            session,
+            tools,
+            backend,
+            fallback,
+            missing,
+            hint,
+        }
     }
 
-    pub fn insert_text(&self, text: &str) -> Result<(), String> {
+    pub fn insert_text(&self, text: &str) -> Result<InsertionOutcome, String> {
         DebugLogger::log_info("=== TEXT_INSERTION: insert_text() called ===");
         DebugLogger::log_info(&format!(
             "TEXT_INSERTION: Input text='{}', length={} chars",
@@ -17,6 +177,49 @@ impl TextInsertionService {
             text.len()
         ));
 
+        if self.output_mode == OutputMode::ClipboardOnly {
+            DebugLogger::log_info("TEXT_INSERTION: Clipboard-only mode, skipping paste keystroke");
+            Self::copy_to_clipboard(text)?;
+            DebugLogger::log_info("TEXT_INSERTION: insert_text() completed successfully");
+            return Ok(InsertionOutcome::Inserted);
+        }
+
+        if !crate::permissions::accessibility_trusted() {
+            DebugLogger::log_info("TEXT_INSERTION: Accessibility permission missing, copying to clipboard instead of sending keystrokes");
+            Self::copy_to_clipboard(text)?;
+            return Ok(InsertionOutcome::CopiedWithoutAccessibility);
+        }
+
+        if self.insertion_method == InsertionMethod::Type {
+            DebugLogger::log_info("TEXT_INSERTION: Using direct typing (clipboard untouched)");
+            self.type_text(text).map_err(|e| {
+                let error_msg = format!("Direct typing failed: {}", e);
+                DebugLogger::log_pipeline_error("text_insertion", &error_msg);
+                error_msg
+            })?;
+            if self.output_mode == OutputMode::Both {
+                Self::copy_to_clipboard(text)?;
+            }
+            DebugLogger::log_info("TEXT_INSERTION: insert_text() completed successfully");
+            return Ok(InsertionOutcome::Inserted);
+        }
+
+        // Remember what the user had copied so we can put it back after pasting
+        // (in "both" mode the dictated text is meant to stay on the clipboard)
+        let restore = (self.restore_clipboard || self.preserve_clipboard) && self.output_mode == OutputMode::Insert;
+        let previous_clipboard = if restore { Self::snapshot_clipboard() } else { None };
+
+        if self.preserve_clipboard && self.output_mode == OutputMode::Insert && previous_clipboard.is_none() {
+            DebugLogger::log_info("TEXT_INSERTION: Clipboard can't be saved for restore, typing instead of pasting");
+            self.type_text(text).map_err(|e| {
+                let error_msg = format!("Direct typing failed (clipboard left untouched): {}", e);
+                DebugLogger::log_pipeline_error("text_insertion", &error_msg);
+                error_msg
+            })?;
+            DebugLogger::log_info("TEXT_INSERTION: insert_text() completed successfully");
+            return Ok(InsertionOutcome::TypedToPreserveClipboard);
+        }
+
         // Try to insert text into the focused application
         #[cfg(target_os = "windows")]
         {
@@ -48,14 +251,84 @@ impl TextInsertionService {
             })?;
         }
 
+        if let Some(previous) = previous_clipboard {
+            Self::restore_clipboard_contents(previous);
+        }
+
         DebugLogger::log_info("TEXT_INSERTION: insert_text() completed successfully");
+        Ok(InsertionOutcome::Inserted)
+    }
+
+    /// Place text on the clipboard without sending any keystroke
+    fn copy_to_clipboard(text: &str) -> Result<(), String> {
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|e| {
+                let error_msg = format!("Failed to copy text to clipboard: {}", e);
+                DebugLogger::log_pipeline_error("text_insertion", &error_msg);
+                error_msg
+            })?;
+        DebugLogger::log_info("TEXT_INSERTION: Text copied to clipboard");
         Ok(())
     }
 
+    /// Save the current clipboard text or image. Returns None for empty or other contents
+    /// (e.g. copied files), in which case the clipboard is not restored afterwards.
+    fn snapshot_clipboard() -> Option<ClipboardSnapshot> {
+        let mut clipboard = match Clipboard::new() {
+            Ok(clipboard) => clipboard,
+            Err(e) => {
+                DebugLogger::log_warn(&format!("TEXT_INSERTION: Failed to open clipboard for restore: {}", e));
+                return None;
+            }
+        };
+        if let Ok(text) = clipboard.get_text() {
+            DebugLogger::log_info(&format!(
+                "TEXT_INSERTION: Saved previous clipboard text ({} chars) for restore",
+                text.len()
+            ));
+            return Some(ClipboardSnapshot::Text(text));
+        }
+        match clipboard.get_image() {
+            Ok(image) => {
+                DebugLogger::log_info(&format!(
+                    "TEXT_INSERTION: Saved previous clipboard image ({}x{}) for restore",
+                    image.width, image.height
+                ));
+                Some(ClipboardSnapshot::Image(image))
+            }
+            Err(e) => {
+                DebugLogger::log_info(&format!(
+                    "TEXT_INSERTION: Previous clipboard is empty or neither text nor image ({}), skipping restore",
+                    e
+                ));
+                None
+            }
+        }
+    }
+
+    /// Put the user's previous clipboard contents back once the target app has consumed the paste
+    fn restore_clipboard_contents(previous: ClipboardSnapshot) {
+        // Give the focused application time to read the clipboard before we replace it
+        std::thread::sleep(std::time::Duration::from_millis(150));
+
+        let restored = Clipboard::new().and_then(|mut clipboard| match previous {
+            ClipboardSnapshot::Text(text) => clipboard.set_text(text),
+            ClipboardSnapshot::Image(image) => clipboard.set_image(image),
+        });
+        match restored {
+            Ok(()) => DebugLogger::log_info("TEXT_INSERTION: Previous clipboard contents restored"),
+            Err(e) => DebugLogger::log_warn(&format!(
+                "TEXT_INSERTION: Failed to restore previous clipboard contents: {}",
+                e
+            )),
+        }
+    }
+
     #[cfg(target_os = "windows")]
     fn insert_text_windows(&self, text: &str) -> Result<(), String> {
         DebugLogger::log_info(
-            "TEXT_INSERTION: Windows - Using native Rust clipboard + enigo keyboard simulation",
+            "TEXT_INSERTION: Windows - Using native clipboard API + SendInput (enigo) keystrokes",
         );
 
         DebugLogger::log_info(&format!(
@@ -63,12 +336,18 @@ impl TextInsertionService {
             text
         ));
 
-        // Try native Rust approach first (much faster and more reliable)
+        // Windows apps expect CRLF; a bare \n pastes as a single line in some editors
+        let text = &to_crlf(text);
+
+        // Try native Rust approach first (much faster and more reliable, no process spawn)
         match self.insert_text_native(text) {
             Ok(()) => {
                 DebugLogger::log_info("TEXT_INSERTION: Windows - Native Rust method succeeded");
                 return Ok(());
             }
+            Err(e) if !self.powershell_fallback => {
+                return Err(format!("{} (PowerShell fallback disabled)", e));
+            }
             Err(e) => {
                 DebugLogger::log_info(&format!(
                     "TEXT_INSERTION: Windows - Native method failed: {}, trying PowerShell fallback",
@@ -100,8 +379,8 @@ impl TextInsertionService {
         // Step 2: Send Ctrl+V keystroke using enigo (much more reliable than SendKeys)
         DebugLogger::log_info("TEXT_INSERTION: Native - Sending keystroke with enigo");
 
-        // Small delay to ensure clipboard is ready
-        std::thread::sleep(std::time::Duration::from_millis(50));
+        // Delay to ensure clipboard (and the target app) is ready
+        std::thread::sleep(std::time::Duration::from_millis(self.paste_delay_ms as u64));
 
         let mut enigo = Enigo::new(&Settings::default())
             .map_err(|e| format!("Failed to initialize enigo keyboard: {}", e))?;
@@ -133,25 +412,197 @@ impl TextInsertionService {
         Ok(())
     }
 
+    /// Type text directly with enigo, falling back to the platform typing tool. The fallback only
+    /// runs when enigo failed before typing anything; retyping after a partial attempt would
+    /// duplicate the part that already went through.
+    fn type_text(&self, text: &str) -> Result<(), String> {
+        let mut typed_any = false;
+        match self.type_text_native(text, &mut typed_any) {
+            Ok(()) => {
+                DebugLogger::log_info("TEXT_INSERTION: Typing - Native enigo typing succeeded");
+                return Ok(());
+            }
+            Err(e) if typed_any => {
+                return Err(format!("Native typing stopped partway through: {}", e));
+            }
+            Err(e) => {
+                DebugLogger::log_info(&format!(
+                    "TEXT_INSERTION: Typing - Native method failed: {}, trying platform fallback",
+                    e
+                ));
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        let result = if self.powershell_fallback {
+            self.type_text_windows_fallback(text)
+        } else {
+            Err("Native typing failed and the PowerShell fallback is disabled".to_string())
+        };
+        #[cfg(target_os = "linux")]
+        let result = self.type_text_linux_fallback(text);
+        #[cfg(target_os = "macos")]
+        let result = self.type_text_macos_fallback(text);
+        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+        let result = Err("Direct typing is not supported on this platform".to_string());
+
+        result
+    }
+
+    // Native typing: newlines are sent as Return presses so multi-line text is preserved
+    // `typed_any` is set once a keystroke has reached the target app
+    fn type_text_native(&self, text: &str, typed_any: &mut bool) -> Result<(), String> {
+        let mut enigo = Enigo::new(&Settings::default())
+            .map_err(|e| format!("Failed to initialize enigo keyboard: {}", e))?;
+
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                enigo
+                    .key(Key::Return, enigo::Direction::Click)
+                    .map_err(|e| format!("Failed to press Return: {}", e))?;
+                *typed_any = true;
+            }
+            let line = line.trim_end_matches('\r');
+            if !line.is_empty() {
+                enigo
+                    .text(line)
+                    .map_err(|e| format!("Failed to type text: {}", e))?;
+                *typed_any = true;
+            }
+        }
+        Ok(())
+    }
+
+    // SendKeys typing fallback: escapes SendKeys metacharacters and maps newlines to {ENTER}
+    #[cfg(target_os = "windows")]
+    fn type_text_windows_fallback(&self, text: &str) -> Result<(), String> {
+        use std::process::Command;
+
+        let mut keys = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '+' | '^' | '%' | '~' | '(' | ')' | '{' | '}' | '[' | ']' => {
+                    keys.push('{');
+                    keys.push(c);
+                    keys.push('}');
+                }
+                '\n' => keys.push_str("{ENTER}"),
+                '\r' => {}
+                _ => keys.push(c),
+            }
+        }
+        let script = format!(
+            r#"
+            try {{
+                $keys = [System.Text.Encoding]::UTF8.GetString([System.Convert]::FromBase64String('{}'))
+                Add-Type -AssemblyName System.Windows.Forms
+                [System.Windows.Forms.SendKeys]::SendWait($keys)
+                exit 0
+            }} catch {{
+                Write-Error "SendKeys typing failed: $_"
+                exit 1
+            }}
+        "#,
+            base64_encode(keys.as_bytes())
+        );
+
+        let output = Command::new("powershell")
+            .arg("-NoProfile")
+            .arg("-WindowStyle")
+            .arg("Hidden")
+            .arg("-EncodedCommand")
+            .arg(powershell_encoded_command(&script))
+            .output()
+            .map_err(|e| format!("PowerShell execution failed: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("SendKeys typing failed: {}", stderr));
+        }
+
+        DebugLogger::log_info("TEXT_INSERTION: Windows - SendKeys typing completed successfully");
+        Ok(())
+    }
+
+    // xdotool (X11) / wtype (Wayland) typing fallback; arguments are passed without a shell
+    #[cfg(target_os = "linux")]
+    fn type_text_linux_fallback(&self, text: &str) -> Result<(), String> {
+        use std::process::Command;
+
+        let text = text.replace("\r\n", "\n");
+        let output = if LinuxSession::detect() == LinuxSession::Wayland {
+            if tool_available("wtype") {
+                DebugLogger::log_info("TEXT_INSERTION: Linux - Typing with wtype (Wayland)");
+                Command::new("wtype").arg("--").arg(&text).output()
+            } else if tool_available("ydotool") {
+                DebugLogger::log_info("TEXT_INSERTION: Linux - Typing with ydotool (Wayland)");
+                Command::new("ydotool").args(["type", "--"]).arg(&text).output()
+            } else {
+                return Err("Wayland session is missing wtype or ydotool".to_string());
+            }
+        } else if !tool_available("xdotool") {
+            return Err("X11 session is missing xdotool".to_string());
+        } else {
+            DebugLogger::log_info("TEXT_INSERTION: Linux - Typing with xdotool (X11)");
+            Command::new("xdotool")
+                .args(["type", "--clearmodifiers", "--delay", "5", "--"])
+                .arg(&text)
+                .output()
+        }
+        .map_err(|e| format!("Failed to run typing tool: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Typing tool failed: {}", stderr));
+        }
+        Ok(())
+    }
+
+    // osascript typing fallback: each line is a quoted keystroke, newlines become Return (key code 36)
+    #[cfg(target_os = "macos")]
+    fn type_text_macos_fallback(&self, text: &str) -> Result<(), String> {
+        use std::process::Command;
+
+        let statements: Vec<String> = text
+            .split('\n')
+            .map(|line| {
+                let escaped = line
+                    .trim_end_matches('\r')
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"");
+                format!("keystroke \"{}\"", escaped)
+            })
+            .collect();
+        let script = format!(
+            "tell application \"System Events\"\n{}\nend tell",
+            statements.join("\nkey code 36\n")
+        );
+
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(&script)
+            .output()
+            .map_err(|e| format!("osascript execution failed: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("osascript typing failed: {}", stderr));
+        }
+        Ok(())
+    }
+
     // PowerShell fallback method (only used if native method fails)
     #[cfg(target_os = "windows")]
     fn insert_text_windows_powershell_fallback(&self, text: &str) -> Result<(), String> {
         use std::process::Command;
 
-        // Escape text for PowerShell
-        let escaped_text = text
-            .replace("`", "``")
-            .replace("\"", "`\"")
-            .replace("'", "''")
-            .replace("\r\n", "`r`n")
-            .replace("\n", "`n")
-            .replace("\r", "`r");
-
+        // The text travels base64-encoded, so quotes, backticks, `$` and newlines need no escaping
         let script = format!(
             r#"
             try {{
-                Set-Clipboard -Value "{}"
-                Start-Sleep -Milliseconds 100
+                $text = [System.Text.Encoding]::UTF8.GetString([System.Convert]::FromBase64String('{}'))
+                Set-Clipboard -Value $text
+                Start-Sleep -Milliseconds {}
                 Add-Type -AssemblyName System.Windows.Forms
                 [System.Windows.Forms.SendKeys]::SendWait("^v")
                 Start-Sleep -Milliseconds 50
@@ -161,15 +612,17 @@ impl TextInsertionService {
                 exit 1
             }}
         "#,
-            escaped_text
+            base64_encode(text.as_bytes()),
+            // Set-Clipboard is slower than arboard, so never wait less than before
+            self.paste_delay_ms.max(100)
         );
 
         let output = Command::new("powershell")
             .arg("-NoProfile")
             .arg("-WindowStyle")
             .arg("Hidden")
-            .arg("-Command")
-            .arg(&script)
+            .arg("-EncodedCommand")
+            .arg(powershell_encoded_command(&script))
             .output()
             .map_err(|e| format!("PowerShell execution failed: {}", e))?;
 
@@ -184,18 +637,102 @@ impl TextInsertionService {
         Ok(())
     }
 
+    // X11: native arboard + enigo first, xclip + xdotool as fallback.
+    // Wayland: wl-copy + wtype/ydotool first (enigo only reaches XWayland windows), native as fallback.
     #[cfg(target_os = "linux")]
     fn insert_text_linux(&self, text: &str) -> Result<(), String> {
-        DebugLogger::log_info(
-            "TEXT_INSERTION: Linux - Using native Rust clipboard + enigo keyboard simulation",
-        );
+        let session = LinuxSession::detect();
         DebugLogger::log_info(&format!(
-            "TEXT_INSERTION: Linux - Setting clipboard content for text: '{}'",
+            "TEXT_INSERTION: Linux - Detected {} session, text: '{}'",
+            session.as_str(),
             text
         ));
 
-        // Use the shared native implementation
-        self.insert_text_native(text)
+        if session == LinuxSession::Wayland {
+            let tools_err = match self.paste_with_session_tools(session, text) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            DebugLogger::log_info(&format!(
+                "TEXT_INSERTION: Linux - {}, trying native clipboard + enigo",
+                tools_err
+            ));
+            return self
+                .insert_text_native(text)
+                .map_err(|e| format!("{}; native fallback failed: {}", tools_err, e));
+        }
+
+        let native_err = match self.insert_text_native(text) {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        if session == LinuxSession::Unknown {
+            return Err(format!("{} (no X11 or Wayland display detected)", native_err));
+        }
+        DebugLogger::log_info(&format!(
+            "TEXT_INSERTION: Linux - Native method failed: {}, trying xclip + xdotool",
+            native_err
+        ));
+        self.paste_with_session_tools(session, text)
+            .map_err(|e| format!("{}; native method failed: {}", e, native_err))
+    }
+
+    // Copy with the session's clipboard tool, then send Ctrl+V with its keystroke tool
+    #[cfg(target_os = "linux")]
+    fn paste_with_session_tools(&self, session: LinuxSession, text: &str) -> Result<(), String> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let missing = missing_paste_tools(session, tool_available);
+        if !missing.is_empty() {
+            return Err(format!(
+                "{} session is missing {}",
+                session.as_str(),
+                missing.join(" and ")
+            ));
+        }
+
+        let (copy_tool, copy_args, key_tool, key_args): (&str, &[&str], &str, &[&str]) = match session {
+            LinuxSession::Wayland if tool_available("wtype") => {
+                ("wl-copy", &[], "wtype", &["-M", "ctrl", "v", "-m", "ctrl"])
+            }
+            // Raw evdev codes: 29 = Left Ctrl, 47 = V
+            LinuxSession::Wayland => ("wl-copy", &[], "ydotool", &["key", "29:1", "47:1", "47:0", "29:0"]),
+            _ => ("xclip", &["-selection", "clipboard"], "xdotool", &["key", "--clearmodifiers", "ctrl+v"]),
+        };
+        DebugLogger::log_info(&format!("TEXT_INSERTION: Linux - Pasting with {} + {}", copy_tool, key_tool));
+
+        // Both clipboard tools fork a process that keeps serving the selection and holds on to
+        // inherited pipes, so only stdin is piped and we wait for the parent alone
+        let mut child = Command::new(copy_tool)
+            .args(copy_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", copy_tool, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("Failed to write to {}: {}", copy_tool, e))?;
+        }
+        let status = child.wait().map_err(|e| format!("{} failed: {}", copy_tool, e))?;
+        if !status.success() {
+            return Err(format!("{} exited with {}", copy_tool, status));
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(self.paste_delay_ms as u64));
+
+        let output = Command::new(key_tool)
+            .args(key_args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", key_tool, e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("{} failed: {}", key_tool, stderr.trim()));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        Ok(())
     }
 
     #[cfg(target_os = "macos")]
@@ -218,6 +755,193 @@ impl TextInsertionService {
             "=== TEXT_INSERTION_TEST: Testing with text='{}' ===",
             test_text
         ));
-        self.insert_text(test_text)
+        match self.insert_text(test_text)? {
+            InsertionOutcome::CopiedWithoutAccessibility => {
+                Err("Accessibility permission is missing; the text was only copied".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ToolStatus {
+    pub name: &'static str,
+    pub available: bool,
+}
+
+/// Result of `TextInsertionService::check_tools`, shown as a status line in the settings UI
+#[derive(Serialize, Debug)]
+pub struct InsertionToolReport {
+    pub platform: &'static str,
+    /// "X11" | "Wayland" | "unknown" on Linux, absent elsewhere
+    pub session: Option<&'static str>,
+    pub tools: Vec<ToolStatus>,
+    pub backend: &'static str,
+    pub fallback: Option<&'static str>,
+    /// Tools the preferred backend for this session still needs
+    pub missing: Vec<&'static str>,
+    pub hint: Option<String>,
+}
+
+/// Display server of the current Linux desktop session
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinuxSession {
+    X11,
+    Wayland,
+    Unknown,
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl LinuxSession {
+    pub fn detect() -> Self {
+        Self::from_env(
+            std::env::var("XDG_SESSION_TYPE").ok().as_deref(),
+            std::env::var_os("WAYLAND_DISPLAY").is_some(),
+            std::env::var_os("DISPLAY").is_some(),
+        )
+    }
+
+    /// `XDG_SESSION_TYPE` wins; otherwise whichever display variable is set (Wayland first)
+    fn from_env(session_type: Option<&str>, wayland_display: bool, x_display: bool) -> Self {
+        match session_type.map(|s| s.trim().to_lowercase()).as_deref() {
+            Some("wayland") => LinuxSession::Wayland,
+            Some("x11") => LinuxSession::X11,
+            _ if wayland_display => LinuxSession::Wayland,
+            _ if x_display => LinuxSession::X11,
+            _ => LinuxSession::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinuxSession::X11 => "X11",
+            LinuxSession::Wayland => "Wayland",
+            LinuxSession::Unknown => "unknown",
+        }
+    }
+}
+
+/// Tools the external paste path needs for `session` that `available` reports missing
+#[cfg(any(target_os = "linux", test))]
+fn missing_paste_tools(session: LinuxSession, available: impl Fn(&str) -> bool) -> Vec<&'static str> {
+    let mut missing = Vec::new();
+    match session {
+        LinuxSession::Wayland => {
+            if !available("wl-copy") {
+                missing.push("wl-copy (wl-clipboard)");
+            }
+            if !available("wtype") && !available("ydotool") {
+                missing.push("wtype or ydotool");
+            }
+        }
+        _ => {
+            if !available("xclip") {
+                missing.push("xclip");
+            }
+            if !available("xdotool") {
+                missing.push("xdotool");
+            }
+        }
+    }
+    missing
+}
+
+/// Tools the external typing fallback needs for `session` that `available` reports missing
+#[cfg(any(target_os = "linux", test))]
+fn missing_typing_tools(session: LinuxSession, available: impl Fn(&str) -> bool) -> Vec<&'static str> {
+    match session {
+        LinuxSession::Wayland if !available("wtype") && !available("ydotool") => vec!["wtype or ydotool"],
+        LinuxSession::Wayland => Vec::new(),
+        _ if !available("xdotool") => vec!["xdotool"],
+        _ => Vec::new(),
+    }
+}
+
+/// Whether an executable named `name` is on PATH (checked without spawning it)
+fn tool_available(name: &str) -> bool {
+    let candidates = if cfg!(target_os = "windows") {
+        vec![format!("{}.exe", name), name.to_string()]
+    } else {
+        vec![name.to_string()]
+    };
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths)
+                .any(|dir| candidates.iter().any(|candidate| dir.join(candidate).is_file()))
+        })
+        .unwrap_or(false)
+}
+
+/// Normalize line endings to CRLF (existing CRLF pairs are kept as-is)
+#[cfg(any(target_os = "windows", test))]
+fn to_crlf(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
+/// Standard base64 (with padding), used to hand text to PowerShell without quoting
+#[cfg(any(target_os = "windows", test))]
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// `-EncodedCommand` argument: the script as base64 of its UTF-16LE bytes
+#[cfg(any(target_os = "windows", test))]
+fn powershell_encoded_command(script: &str) -> String {
+    let utf16: Vec<u8> = script.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+    base64_encode(&utf16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode("`$\"x'\n".as_bytes()), "YCQieCcK");
+        assert_eq!(powershell_encoded_command("ab"), "YQBiAA==");
+    }
+
+    #[test]
+    fn test_linux_session_detection() {
+        assert_eq!(LinuxSession::from_env(Some("wayland"), false, true), LinuxSession::Wayland);
+        assert_eq!(LinuxSession::from_env(Some("x11"), true, true), LinuxSession::X11);
+        assert_eq!(LinuxSession::from_env(Some("tty"), true, true), LinuxSession::Wayland);
+        assert_eq!(LinuxSession::from_env(None, false, true), LinuxSession::X11);
+        assert_eq!(LinuxSession::from_env(None, false, false), LinuxSession::Unknown);
+    }
+
+    #[test]
+    fn test_missing_paste_tools() {
+        let only = |tools: &'static [&'static str]| move |name: &str| tools.contains(&name);
+        assert!(missing_paste_tools(LinuxSession::Wayland, only(&["wl-copy", "ydotool"])).is_empty());
+        assert_eq!(missing_paste_tools(LinuxSession::Wayland, only(&["wl-copy"])), vec!["wtype or ydotool"]);
+        assert_eq!(missing_paste_tools(LinuxSession::X11, only(&["xclip"])), vec!["xdotool"]);
+        assert!(missing_typing_tools(LinuxSession::Wayland, only(&["wtype"])).is_empty());
+        assert_eq!(missing_typing_tools(LinuxSession::X11, only(&[])), vec!["xdotool"]);
+    }
+
+    #[test]
+    fn test_to_crlf() {
+        assert_eq!(to_crlf("a\nb\r\nc"), "a\r\nb\r\nc");
     }
 }
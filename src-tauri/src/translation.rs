@@ -1,24 +1,266 @@
+use crate::api_flavor::ApiFlavor;
 use crate::debug_logger::DebugLogger;
 use reqwest;
 use serde_json::{Value, json};
+use std::time::Duration;
 
 pub struct TranslationService {
     client: reqwest::Client,
     api_endpoint: String,
     api_key: String,
     model: String,
+    api_flavor: ApiFlavor,
+    max_retries: u32,
+    prompt_template: String,
+    temperature: f32,
+    max_tokens: u32,
+    correction_style: CorrectionStyle,
+    api_mode: ApiMode,
+    local_only: bool,
+}
+
+/// Request/response shape of the endpoint used for correction and translation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiMode {
+    /// `/chat/completions` with `choices[0].message.content`
+    ChatCompletions,
+    /// `/responses` with `output[].content[].text`
+    Responses,
+    /// Legacy `/completions` with `choices[0].text`
+    Completions,
+}
+
+impl ApiMode {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "responses" => ApiMode::Responses,
+            "completions" | "legacy_completions" => ApiMode::Completions,
+            _ => ApiMode::ChatCompletions,
+        }
+    }
+
+    fn path(&self) -> &'static str {
+        match self {
+            ApiMode::ChatCompletions => "chat/completions",
+            ApiMode::Responses => "responses",
+            ApiMode::Completions => "completions",
+        }
+    }
+
+    fn request_body(&self, model: &str, system_prompt: Option<&str>, user_content: &str, temperature: f32, max_tokens: u32) -> Value {
+        match self {
+            ApiMode::ChatCompletions => {
+                // Instructions travel in the system message so models don't echo them back
+                let mut messages = Vec::new();
+                if let Some(system_prompt) = system_prompt {
+                    messages.push(json!({
+                        "role": "system",
+                        "content": system_prompt
+                    }));
+                }
+                messages.push(json!({
+                    "role": "user",
+                    "content": user_content
+                }));
+                json!({
+                    "model": model,
+                    "messages": messages,
+                    "temperature": temperature,
+                    "max_tokens": max_tokens
+                })
+            }
+            ApiMode::Responses => {
+                let mut body = json!({
+                    "model": model,
+                    "input": user_content,
+                    "temperature": temperature,
+                    "max_output_tokens": max_tokens
+                });
+                if let Some(system_prompt) = system_prompt {
+                    body["instructions"] = json!(system_prompt);
+                }
+                body
+            }
+            ApiMode::Completions => {
+                // No roles here: the instructions simply precede the text
+                let prompt = match system_prompt {
+                    Some(system_prompt) => format!("{}\n\n{}\n", system_prompt, user_content),
+                    None => user_content.to_string(),
+                };
+                json!({
+                    "model": model,
+                    "prompt": prompt,
+                    "temperature": temperature,
+                    "max_tokens": max_tokens
+                })
+            }
+        }
+    }
+
+    /// The generated text, or an error naming the shape this mode expected
+    fn extract_text(&self, json: &Value) -> Result<String, String> {
+        let text = match self {
+            ApiMode::ChatCompletions => json["choices"][0]["message"]["content"].as_str().map(str::to_string),
+            ApiMode::Completions => json["choices"][0]["text"].as_str().map(str::to_string),
+            ApiMode::Responses => json["output_text"].as_str().map(str::to_string).or_else(|| {
+                let parts: Vec<&str> = json["output"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|item| item["content"].as_array())
+                    .flatten()
+                    .filter(|part| part["type"] == "output_text")
+                    .filter_map(|part| part["text"].as_str())
+                    .collect();
+                (!parts.is_empty()).then(|| parts.concat())
+            }),
+        };
+        text.map(|text| text.trim().to_string()).ok_or_else(|| {
+            let expected = match self {
+                ApiMode::ChatCompletions => "choices[0].message.content",
+                ApiMode::Responses => "output[].content[].text",
+                ApiMode::Completions => "choices[0].text",
+            };
+            format!(
+                "No translation in response: expected {} from the /{} endpoint; check the api_mode setting",
+                expected,
+                self.path()
+            )
+        })
+    }
+}
+
+/// How much the built-in prompts may change the dictated wording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrectionStyle {
+    /// No correction at all; text is only translated (literally) when translation is on
+    Verbatim,
+    GrammarOnly,
+    Polished,
+    Concise,
+}
+
+impl CorrectionStyle {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "verbatim" => CorrectionStyle::Verbatim,
+            "polished" => CorrectionStyle::Polished,
+            "concise" => CorrectionStyle::Concise,
+            _ => CorrectionStyle::GrammarOnly,
+        }
+    }
+
+    /// System prompt for correction-only mode (`None` = leave the text untouched)
+    fn correction_prompt(&self) -> Option<&'static str> {
+        match self {
+            CorrectionStyle::Verbatim => None,
+            CorrectionStyle::GrammarOnly => Some(
+                "Correct any grammar, punctuation, and spelling errors in the user's text. \
+                Keep the same language and meaning, just fix any errors, remove duplicated adjacent words and normalize spaces. \
+                Provide only the corrected text without any additional commentary.",
+            ),
+            CorrectionStyle::Polished => Some(
+                "Rewrite the user's text for clarity and flow: fix grammar, punctuation, and spelling errors, \
+                smooth awkward phrasing and remove filler words and repetitions. \
+                Keep the same language, meaning and tone. Provide only the rewritten text without any additional commentary.",
+            ),
+            CorrectionStyle::Concise => Some(
+                "Tighten the user's text: fix grammar, punctuation, and spelling errors and remove filler words, \
+                repetitions and redundant phrasing so it is as short as possible without losing meaning. \
+                Keep the same language. Provide only the edited text without any additional commentary.",
+            ),
+        }
+    }
+
+    /// Extra guidance appended to the translation prompt
+    fn translation_hint(&self) -> &'static str {
+        match self {
+            CorrectionStyle::Verbatim | CorrectionStyle::GrammarOnly => "",
+            CorrectionStyle::Polished => " Smooth awkward phrasing so the result reads naturally.",
+            CorrectionStyle::Concise => " Keep the wording as concise as possible without losing meaning.",
+        }
+    }
+}
+
+/// Languages the built-in prompts can name, as (ISO 639-1 code, English name)
+const LANGUAGES: [(&str, &str); 10] = [
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("zh", "Chinese"),
+];
+
+/// Code for a language detected by the STT service, which may report a code ("fr") or an
+/// English name ("french"). None for languages the prompts don't know.
+pub fn detected_language_code(language: &str) -> Option<&'static str> {
+    let language = language.trim();
+    LANGUAGES
+        .iter()
+        .find(|(code, name)| code.eq_ignore_ascii_case(language) || name.eq_ignore_ascii_case(language))
+        .map(|(code, _)| *code)
+}
+
+/// Placeholders recognized in a custom prompt template; `{text}` is required
+pub const PROMPT_TEMPLATE_PLACEHOLDERS: [&str; 3] = ["{source_lang}", "{target_lang}", "{text}"];
+
+/// Lower bound for max_tokens so short inputs still leave room for the reply
+const MIN_MAX_TOKENS: u32 = 256;
+
+/// Output length roughly tracks input length: allow 1.5x the estimated input tokens
+/// (~4 chars per token), between `MIN_MAX_TOKENS` and the configured ceiling.
+fn max_tokens_for(text: &str, ceiling: u32) -> u32 {
+    let estimated = (text.chars().count() as u32).div_ceil(4);
+    (estimated.saturating_mul(3) / 2).max(MIN_MAX_TOKENS).min(ceiling)
 }
 
 impl TranslationService {
-    pub fn new(api_endpoint: String, api_key: String, model: String) -> Self {
+    pub fn new(
+        api_endpoint: String,
+        api_key: String,
+        model: String,
+        api_flavor: ApiFlavor,
+        request_timeout_secs: u64,
+        max_retries: u32,
+        prompt_template: String,
+        temperature: f32,
+        max_tokens: u32,
+        correction_style: CorrectionStyle,
+        custom_headers: reqwest::header::HeaderMap,
+        api_mode: ApiMode,
+        local_only: bool,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(request_timeout_secs))
+            .default_headers(custom_headers)
+            .build()
+            .unwrap_or_default();
+
         Self {
-            client: reqwest::Client::new(),
+            client,
             api_endpoint,
             api_key,
             model,
+            api_flavor,
+            max_retries,
+            prompt_template,
+            temperature,
+            max_tokens,
+            correction_style,
+            api_mode,
+            local_only,
         }
     }
 
+    /// Whether `process_text` edits the wording (false for verbatim style without a custom template)
+    pub fn corrects_text(&self) -> bool {
+        !self.prompt_template.trim().is_empty() || self.correction_style != CorrectionStyle::Verbatim
+    }
+
     /// Process text with optional translation - always corrects grammar and punctuation
     pub async fn process_text(
         &self,
@@ -33,36 +275,70 @@ impl TranslationService {
             text, source_lang, target_lang, translate_enabled
         ));
 
-        let prompt = if translate_enabled && target_lang != "none" && target_lang != source_lang {
+        let is_translation = translate_enabled && target_lang != "none" && target_lang != source_lang;
+        // Built-in prompts go in a system message with the transcription as the sole user content;
+        // a custom template embeds {text} itself, so it is sent as the user message as-is
+        let (system_prompt, user_content) = if !self.prompt_template.trim().is_empty() {
+            // User-supplied template replaces the built-in prompts
+            DebugLogger::log_info("TRANSLATION: Mode = Custom prompt template");
+            let source_name = if source_lang == "auto" {
+                "the original language"
+            } else {
+                self.get_language_name(source_lang)
+            };
+            let target_name = if is_translation {
+                self.get_language_name(target_lang)
+            } else {
+                source_name
+            };
+            let rendered = self
+                .prompt_template
+                .replace("{source_lang}", source_name)
+                .replace("{target_lang}", target_name)
+                .replace("{text}", text);
+            (None, rendered)
+        } else if is_translation && self.correction_style == CorrectionStyle::Verbatim {
+            // Translation only - no editing of the dictated wording
+            DebugLogger::log_info("TRANSLATION: Mode = Translation (verbatim)");
+            let source = if source_lang == "auto" {
+                String::new()
+            } else {
+                format!(" from {}", self.get_language_name(source_lang))
+            };
+            let instruction = format!(
+                "Translate the user's text{} to {} as literally as possible, without correcting, rephrasing \
+                 or removing anything. Return only the translation, with no extra commentary.",
+                source,
+                self.get_language_name(target_lang)
+            );
+            (Some(instruction), text.to_string())
+        } else if is_translation {
             // Translation + correction mode
             DebugLogger::log_info("TRANSLATION: Mode = Translation + Correction");
-            if source_lang == "auto" {
+            let instruction = if source_lang == "auto" {
                 format!(
-                    "Please correct any grammar, punctuation, or spelling errors, remove any adjacent duplicates, \
-                     and render the text in native-level {}. Return only the edited translation, with no extra commentary:\n\n{}",
-                    self.get_language_name(target_lang),
-                    text
+                    "Correct any grammar, punctuation, or spelling errors in the user's text, remove any adjacent duplicates, \
+                     and render it in native-level {}. Return only the edited translation, with no extra commentary.",
+                    self.get_language_name(target_lang)
                 )
             } else {
                 format!(
-                    "Please translate the following text from {} to {}, then correct any grammar, punctuation, or spelling errors, \
-                     remove any adjacent duplicates, and render the text in native-level {}. Return only the edited translation, \
-                     with no extra commentary:\n\n{}",
+                    "Translate the user's text from {} to {}, then correct any grammar, punctuation, or spelling errors, \
+                     remove any adjacent duplicates, and render it in native-level {}. Return only the edited translation, \
+                     with no extra commentary.",
                     self.get_language_name(source_lang),
                     self.get_language_name(target_lang),
-                    self.get_language_name(target_lang),
-                    text
+                    self.get_language_name(target_lang)
                 )
-            }
-        } else {
+            };
+            (Some(instruction + self.correction_style.translation_hint()), text.to_string())
+        } else if let Some(instruction) = self.correction_style.correction_prompt() {
             // Correction only mode
-            DebugLogger::log_info("TRANSLATION: Mode = Correction only");
-            format!(
-                "Please correct any grammar, punctuation, and spelling errors in the following text. \
-                Keep the same language and meaning, just fix any errors, remove duplicated adjacent words and normalize spaces. \
-                Provide only the corrected text without any additional commentary:\n\n{}",
-                text
-            )
+            DebugLogger::log_info(&format!("TRANSLATION: Mode = Correction only ({:?})", self.correction_style));
+            (Some(instruction.to_string()), text.to_string())
+        } else {
+            DebugLogger::log_info("TRANSLATION: Mode = Verbatim - returning text unchanged");
+            return Ok(text.to_string());
         };
 
         DebugLogger::log_translation_request(
@@ -70,55 +346,90 @@ impl TranslationService {
             source_lang,
             target_lang,
             translate_enabled,
-            &prompt,
+            system_prompt.as_deref().unwrap_or(&user_content),
         );
 
-        self.send_chat_request(&prompt).await
+        self.send_chat_request(system_prompt.as_deref(), &user_content).await
     }
 
-    async fn send_chat_request(&self, prompt: &str) -> Result<String, String> {
+    async fn send_chat_request(&self, system_prompt: Option<&str>, user_content: &str) -> Result<String, String> {
         DebugLogger::log_info("=== TRANSLATION: send_chat_request() called ===");
+        crate::api_flavor::check_local_only(&self.api_endpoint, self.local_only).inspect_err(|e| {
+            DebugLogger::log_pipeline_error("translation", e);
+        })?;
+        // Counts against max_concurrent_requests across all attempts, including backoff
+        let _slot = crate::retry::acquire_request_slot("TRANSLATION").await;
         DebugLogger::log_info(&format!(
-            "TRANSLATION: Prompt length: {} chars",
-            prompt.len()
+            "TRANSLATION: System prompt length: {} chars, user content length: {} chars",
+            system_prompt.map_or(0, str::len),
+            user_content.len()
         ));
 
-        // Create the request body
-        let body = json!({
-            "model": self.model,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ],
-            "temperature": 0.3,
-            "max_tokens": 1000
-        });
+        // Size the limit from the input so long dictations aren't cut off mid-sentence
+        let max_tokens = max_tokens_for(user_content, self.max_tokens);
+        DebugLogger::log_info(&format!(
+            "TRANSLATION: max_tokens = {} (ceiling {})",
+            max_tokens, self.max_tokens
+        ));
+
+        // Create the request body in the shape the configured api_mode expects
+        let body = self
+            .api_mode
+            .request_body(&self.model, system_prompt, user_content, self.temperature, max_tokens);
 
         // Log the full API request
-        let url = format!("{}/chat/completions", self.api_endpoint);
+        let url = self
+            .api_flavor
+            .endpoint_url(&self.api_endpoint, &self.model, self.api_mode.path());
         DebugLogger::log_api_payload(&body, &url);
 
-        // Send request to chat completion API
-        DebugLogger::log_info("TRANSLATION: Sending HTTP POST request");
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                let error_msg = format!("Request failed: {}", e);
-                DebugLogger::log_pipeline_error("translation", &error_msg);
-                error_msg
-            })?;
+        // Send the request, retrying network errors and server-side failures
+        let max_attempts = self.max_retries + 1;
+        let mut attempt = 1;
+        let response = loop {
+            DebugLogger::log_info(&format!(
+                "TRANSLATION: Sending HTTP POST request (attempt {}/{})",
+                attempt, max_attempts
+            ));
+            let result = self
+                .api_flavor
+                .authorize(self.client.post(&url), &self.api_key)
+                .header("Content-Type", "application/json")
+                .json(&body)
+                .send()
+                .await;
+
+            let retryable = match &result {
+                Ok(resp) => resp.status().is_server_error() || resp.status().as_u16() == 429,
+                Err(e) => {
+                    DebugLogger::log_info(&format!("Translation network error: {}", e));
+                    true
+                }
+            };
+            if !retryable || attempt >= max_attempts {
+                break result.map_err(|e| {
+                    let error_msg = format!("Request failed after {} attempts: {}", attempt, e);
+                    DebugLogger::log_pipeline_error("translation", &error_msg);
+                    error_msg
+                })?;
+            }
+
+            // Prefer the server's Retry-After on rate limits over our own backoff
+            let retry_after = match &result {
+                Ok(resp) if resp.status().as_u16() == 429 => {
+                    crate::retry::retry_after_delay(resp.headers())
+                }
+                _ => None,
+            };
+            let delay = retry_after.unwrap_or_else(|| crate::retry::backoff_delay(attempt));
+            DebugLogger::log_info(&format!("Retrying in {}ms...", delay.as_millis()));
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
 
         let status = response.status();
         DebugLogger::log_info(&format!("Translation API response status: {}", status));
-        DebugLogger::log_info(&format!(
+        DebugLogger::log_debug(&format!(
             "Translation API response headers: {:?}",
             response.headers()
         ));
@@ -140,29 +451,30 @@ impl TranslationService {
                 error_msg
             })?;
 
-            DebugLogger::log_info(&format!(
+            DebugLogger::log_debug(&format!(
                 "TRANSLATION: Parsed JSON: {}",
                 serde_json::to_string_pretty(&json).unwrap_or_default()
             ));
 
-            if let Some(translated_text) = json["choices"][0]["message"]["content"].as_str() {
-                let result = translated_text.trim().to_string();
-                DebugLogger::log_info(&format!("Translation API extracted text: '{}'", result));
-                Ok(result)
-            } else {
-                let error_msg = "No translation in response".to_string();
-                DebugLogger::log_pipeline_error("translation", &error_msg);
-                DebugLogger::log_info(&format!(
-                    "TRANSLATION: Available JSON structure: {}",
-                    serde_json::to_string_pretty(&json).unwrap_or_default()
-                ));
-                DebugLogger::log_translation_response(
-                    false,
-                    None,
-                    Some(&error_msg),
-                    Some(&response_text),
-                );
-                Err(error_msg)
+            match self.api_mode.extract_text(&json) {
+                Ok(result) => {
+                    DebugLogger::log_info(&format!("Translation API extracted text: '{}'", result));
+                    Ok(result)
+                }
+                Err(error_msg) => {
+                    DebugLogger::log_pipeline_error("translation", &error_msg);
+                    DebugLogger::log_info(&format!(
+                        "TRANSLATION: Available JSON structure: {}",
+                        serde_json::to_string_pretty(&json).unwrap_or_default()
+                    ));
+                    DebugLogger::log_translation_response(
+                        false,
+                        None,
+                        Some(&error_msg),
+                        Some(&response_text),
+                    );
+                    Err(error_msg)
+                }
             }
         } else {
             DebugLogger::log_info(
@@ -177,18 +489,66 @@ impl TranslationService {
     }
 
     fn get_language_name(&self, lang_code: &str) -> &str {
-        match lang_code {
-            "en" => "English",
-            "es" => "Spanish",
-            "fr" => "French",
-            "de" => "German",
-            "it" => "Italian",
-            "pt" => "Portuguese",
-            "ru" => "Russian",
-            "ja" => "Japanese",
-            "ko" => "Korean",
-            "zh" => "Chinese",
-            _ => "English", // Default to English
-        }
+        LANGUAGES
+            .iter()
+            .find(|(code, _)| *code == lang_code)
+            .map(|(_, name)| *name)
+            .unwrap_or("English") // Default to English
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_tokens_scales_with_input() {
+        assert_eq!(max_tokens_for("short", 4096), MIN_MAX_TOKENS);
+        assert_eq!(max_tokens_for(&"a".repeat(4000), 4096), 1500);
+        assert_eq!(max_tokens_for(&"a".repeat(40_000), 4096), 4096);
+        // The ceiling wins even over the floor
+        assert_eq!(max_tokens_for("short", 100), 100);
+    }
+
+    #[test]
+    fn test_api_mode_extract_text() {
+        let chat = json!({"choices": [{"message": {"content": " Hola "}}]});
+        assert_eq!(ApiMode::ChatCompletions.extract_text(&chat), Ok("Hola".to_string()));
+        let completion = json!({"choices": [{"text": "Hola\n"}]});
+        assert_eq!(ApiMode::Completions.extract_text(&completion), Ok("Hola".to_string()));
+        let responses = json!({"output": [
+            {"type": "reasoning", "content": []},
+            {"type": "message", "content": [{"type": "output_text", "text": "Hola"}]}
+        ]});
+        assert_eq!(ApiMode::Responses.extract_text(&responses), Ok("Hola".to_string()));
+        // A chat-shaped reply doesn't satisfy the responses mode
+        assert!(ApiMode::Responses.extract_text(&chat).is_err());
+    }
+
+    #[test]
+    fn test_api_mode_request_body() {
+        let body = ApiMode::Responses.request_body("m", Some("Fix it."), "teh text", 0.0, 256);
+        assert_eq!(body["instructions"], "Fix it.");
+        assert_eq!(body["input"], "teh text");
+        assert_eq!(body["max_output_tokens"], 256);
+        let body = ApiMode::Completions.request_body("m", Some("Fix it."), "teh text", 0.0, 256);
+        assert_eq!(body["prompt"], "Fix it.\n\nteh text\n");
+    }
+
+    #[test]
+    fn test_detected_language_code() {
+        assert_eq!(detected_language_code("french"), Some("fr"));
+        assert_eq!(detected_language_code("German"), Some("de"));
+        assert_eq!(detected_language_code("pt"), Some("pt"));
+        assert_eq!(detected_language_code("klingon"), None);
+    }
+
+    #[test]
+    fn test_correction_style_from_setting() {
+        assert_eq!(CorrectionStyle::from_setting("verbatim"), CorrectionStyle::Verbatim);
+        assert_eq!(CorrectionStyle::from_setting("Polished"), CorrectionStyle::Polished);
+        assert_eq!(CorrectionStyle::from_setting(""), CorrectionStyle::GrammarOnly);
+        assert!(CorrectionStyle::Verbatim.correction_prompt().is_none());
+        assert!(CorrectionStyle::Concise.correction_prompt().is_some());
     }
 }
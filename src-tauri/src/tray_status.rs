@@ -0,0 +1,151 @@
+// Tray icon/tooltip that follows the recording pipeline state
+use tauri::image::Image;
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Listener, Manager, Wry};
+
+use crate::debug_logger::DebugLogger;
+
+pub const TRAY_ID: &str = "main-tray";
+pub const TOGGLE_RECORDING_ID: &str = "toggle_recording";
+
+/// The tray's Start/Stop Recording entry, kept so its label can follow the state
+pub struct TrayRecordingItem(pub MenuItem<Wry>);
+
+const RECORDING_COLOR: [u8; 4] = [220, 38, 38, 255];
+const PROCESSING_COLOR: [u8; 4] = [245, 158, 11, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayStatus {
+    Idle,
+    Recording,
+    Processing,
+}
+
+impl TrayStatus {
+    pub fn tooltip(self) -> &'static str {
+        match self {
+            TrayStatus::Idle => "TalkToMe - Voice to Text with Translation",
+            TrayStatus::Recording => "TalkToMe - Recording",
+            TrayStatus::Processing => "TalkToMe - Processing",
+        }
+    }
+
+    fn menu_label(self) -> &'static str {
+        match self {
+            TrayStatus::Recording => "Stop Recording",
+            TrayStatus::Idle | TrayStatus::Processing => "Start Recording",
+        }
+    }
+}
+
+/// Swap the tray icon, tooltip and Start/Stop label to match `status`
+pub fn set_tray_status(app: &AppHandle, status: TrayStatus) {
+    if let Some(item) = app.try_state::<TrayRecordingItem>() {
+        let _ = item.0.set_text(status.menu_label());
+    }
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let Some(base) = app.default_window_icon() else {
+        return;
+    };
+    let icon = match status {
+        TrayStatus::Idle => base.clone(),
+        TrayStatus::Recording => with_badge(base, RECORDING_COLOR, false),
+        TrayStatus::Processing => with_badge(base, PROCESSING_COLOR, true),
+    };
+    if let Err(e) = tray.set_icon(Some(icon)) {
+        DebugLogger::log_warn(&format!("Failed to update tray icon: {}", e));
+    }
+    let _ = tray.set_tooltip(Some(status.tooltip()));
+}
+
+/// Follow the pipeline events that are already emitted to the frontend
+pub fn listen_for_status_events(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any("recording-started", move |_| {
+        set_tray_status(&handle, TrayStatus::Recording);
+    });
+
+    let handle = app.clone();
+    app.listen_any("processing-audio", move |event| {
+        let status = if event.payload() == "true" {
+            TrayStatus::Processing
+        } else if is_recording(&handle) {
+            // Chunked mode: a chunk finished but the session is still live
+            TrayStatus::Recording
+        } else {
+            TrayStatus::Idle
+        };
+        set_tray_status(&handle, status);
+    });
+
+    for event in ["recording-stopped", "recording-cancelled"] {
+        let handle = app.clone();
+        app.listen_any(event, move |_| {
+            // stop_recording emits this before the final transcription, which then
+            // reports itself through processing-audio
+            set_tray_status(&handle, TrayStatus::Idle);
+        });
+    }
+}
+
+fn is_recording(app: &AppHandle) -> bool {
+    app.try_state::<crate::RecordingState>()
+        .and_then(|state| state.lock().ok().map(|s| *s))
+        .unwrap_or(false)
+}
+
+fn with_badge(base: &Image<'_>, color: [u8; 4], ring: bool) -> Image<'static> {
+    let mut rgba = base.rgba().to_vec();
+    draw_badge(&mut rgba, base.width(), base.height(), color, ring);
+    Image::new_owned(rgba, base.width(), base.height())
+}
+
+/// Paint a dot (or a ring, for the "busy" glyph) over the bottom-right quarter of an RGBA image
+fn draw_badge(rgba: &mut [u8], width: u32, height: u32, color: [u8; 4], ring: bool) {
+    let size = width.min(height) as f32;
+    let radius = size * 0.22;
+    let inner = if ring { radius * 0.55 } else { 0.0 };
+    let cx = width as f32 - radius - size * 0.04;
+    let cy = height as f32 - radius - size * 0.04;
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist <= radius && dist >= inner {
+                let i = ((y * width + x) * 4) as usize;
+                if let Some(px) = rgba.get_mut(i..i + 4) {
+                    px.copy_from_slice(&color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(rgba: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * width + x) * 4) as usize;
+        [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+    }
+
+    #[test]
+    fn test_draw_badge_dot_and_ring() {
+        let (w, h) = (32, 32);
+        let center = (32.0 - 32.0 * 0.26) as u32;
+
+        let mut dot = vec![0u8; (w * h * 4) as usize];
+        draw_badge(&mut dot, w, h, RECORDING_COLOR, false);
+        assert_eq!(pixel(&dot, w, center, center), RECORDING_COLOR);
+        assert_eq!(pixel(&dot, w, 0, 0), [0, 0, 0, 0]);
+
+        let mut ring = vec![0u8; (w * h * 4) as usize];
+        draw_badge(&mut ring, w, h, PROCESSING_COLOR, true);
+        assert_eq!(pixel(&ring, w, center, center), [0, 0, 0, 0]);
+        assert!(ring.chunks(4).any(|px| px == PROCESSING_COLOR));
+    }
+}
@@ -544,6 +544,12 @@ function createSettingsStore() {
           }
         }
 
+        // Typed backend errors arrive as { kind, message }
+        if (error && typeof error === "object" && "message" in error) {
+          const { kind, message } = error as { kind?: string; message: string };
+          return { success: false, message, details: kind };
+        }
+
         // Fallback for unknown error types
         return {
           success: false,